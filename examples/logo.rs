@@ -41,13 +41,13 @@ struct Game {
 
 impl Game {
 	fn new(event_loop: &EventLoop<()>) -> Game {
-		let window = WindowBuilder::new().with_inner_size(Size::Physical(PhysicalSize::new(512, 512))).build(&event_loop).unwrap();
+		let window = WindowBuilder::new().with_inner_size(Size::Physical(PhysicalSize::new(512, 512))).build(event_loop).unwrap();
 		let mut renderer = Renderer::new(&window, window.inner_size().width, window.inner_size().height);
 		let mut compiler = Compiler::new().unwrap();
 		renderer.register_bind_group_layout::<Texture>();
 		renderer.register_bind_group_layout::<Uniform<Transform>>();
 		renderer.register_pipeline_layout(&[TypeId::of::<Texture>(), TypeId::of::<Uniform<Transform>>()]);
-		let pipeline = Pipeline::new(&mut renderer, &mut compiler, include_str!("vertex.glsl"), include_str!("fragment.glsl"), &[TypeId::of::<Texture>(), TypeId::of::<Uniform<Transform>>()]);
+		let pipeline = Pipeline::new(&renderer, &mut compiler, include_str!("vertex.glsl"), include_str!("fragment.glsl"), &[TypeId::of::<Texture>(), TypeId::of::<Uniform<Transform>>()]);
 		let mesh = Mesh::new(&renderer, &[
 			Vertex::new([-0.5, -0.5, 0.0], [1.0, 1.0, 1.0], [0.0, 0.0]),
 			Vertex::new([0.5, -0.5, 0.0], [1.0, 1.0, 1.0], [1.0, 0.0]),
@@ -73,20 +73,18 @@ impl Game {
 
 	fn handle_event(&mut self, event: Event<()>) -> ControlFlow {
 		match event {
-			Event::WindowEvent { window_id, event } => {
-				if window_id == self.window.id() {
-					match event {
-						WindowEvent::CloseRequested => {
-							return ControlFlow::Exit;
-						}
-						WindowEvent::Resized(size) => {
-							self.renderer.resize(size.width, size.height);
-						}
-						WindowEvent::ScaleFactorChanged { scale_factor: _, new_inner_size } => {
-							self.renderer.resize(new_inner_size.width, new_inner_size.height);
-						}
-						_ => ()
+			Event::WindowEvent { window_id, event } if window_id == self.window.id() => {
+				match event {
+					WindowEvent::CloseRequested => {
+						return ControlFlow::Exit;
+					}
+					WindowEvent::Resized(size) => {
+						self.renderer.resize(size.width, size.height);
+					}
+					WindowEvent::ScaleFactorChanged { scale_factor: _, new_inner_size } => {
+						self.renderer.resize(new_inner_size.width, new_inner_size.height);
 					}
+					_ => ()
 				}
 			}
 			Event::RedrawRequested(window_id) => {
@@ -0,0 +1,190 @@
+use crate::{Skeleton, Transform};
+use cgmath::{Matrix4, Quaternion, Vector3, VectorSpace, InnerSpace};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+	Step,
+	Linear,
+	CubicSpline,
+}
+
+#[derive(Clone, Debug)]
+pub enum ChannelValues {
+	Translation(Vec<Vector3<f32>>),
+	Rotation(Vec<Quaternion<f32>>),
+	Scale(Vec<Vector3<f32>>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+	pub target: usize,
+	pub interpolation: Interpolation,
+	pub times: Vec<f32>,
+	pub values: ChannelValues,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+	pub name: Option<String>,
+	pub channels: Vec<Channel>,
+	duration: f32,
+}
+
+impl AnimationClip {
+	pub fn new(name: Option<String>, channels: Vec<Channel>) -> AnimationClip {
+		let duration = channels.iter()
+			.filter_map(|channel| channel.times.last().copied())
+			.fold(0.0, f32::max);
+
+		AnimationClip {
+			name,
+			channels,
+			duration,
+		}
+	}
+
+	pub fn get_duration(&self) -> f32 {
+		self.duration
+	}
+
+	pub fn sample(&self, time: f32, pose: &mut [Transform]) {
+		for channel in &self.channels {
+			let transform = match pose.get_mut(channel.target) {
+				Some(transform) => transform,
+				None => continue,
+			};
+			match &channel.values {
+				ChannelValues::Translation(values) => {
+					if let Some(value) = sample_keyframes(&channel.times, values, channel.interpolation, time, |a, b, t| a.lerp(b, t)) {
+						transform.translation = value;
+					}
+				}
+				ChannelValues::Rotation(values) => {
+					if let Some(value) = sample_keyframes(&channel.times, values, channel.interpolation, time, nlerp) {
+						transform.rotation = value.normalize();
+					}
+				}
+				ChannelValues::Scale(values) => {
+					if let Some(value) = sample_keyframes(&channel.times, values, channel.interpolation, time, |a, b, t| a.lerp(b, t)) {
+						transform.scale = value;
+					}
+				}
+			}
+		}
+	}
+
+	pub fn retarget(&self, targets: &[usize]) -> AnimationClip {
+		let channels = self.channels.iter()
+			.filter_map(|channel| {
+				let target = targets.iter().position(|target| *target == channel.target)?;
+				Some(Channel {
+					target,
+					..channel.clone()
+				})
+			})
+			.collect();
+		AnimationClip::new(self.name.clone(), channels)
+	}
+}
+
+pub struct AnimationSampler {
+	pose: Vec<Transform>,
+	time: f32,
+	speed: f32,
+	looping: bool,
+}
+
+impl AnimationSampler {
+	pub fn new(skeleton: &Skeleton) -> AnimationSampler {
+		AnimationSampler {
+			pose: skeleton.get_rest_pose(),
+			time: 0.0,
+			speed: 1.0,
+			looping: true,
+		}
+	}
+
+	pub fn advance(&mut self, delta: f32) {
+		self.time += delta * self.speed;
+	}
+
+	pub fn set_time(&mut self, time: f32) {
+		self.time = time;
+	}
+
+	pub fn get_time(&self) -> f32 {
+		self.time
+	}
+
+	pub fn set_speed(&mut self, speed: f32) {
+		self.speed = speed;
+	}
+
+	pub fn get_speed(&self) -> f32 {
+		self.speed
+	}
+
+	pub fn set_looping(&mut self, looping: bool) {
+		self.looping = looping;
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.looping
+	}
+
+	pub fn get_pose(&self) -> &[Transform] {
+		&self.pose
+	}
+
+	pub fn sample(&mut self, skeleton: &Skeleton, clip: &AnimationClip) -> Vec<Matrix4<f32>> {
+		let duration = clip.get_duration();
+		let time = if duration <= 0.0 {
+			0.0
+		} else if self.looping {
+			self.time.rem_euclid(duration)
+		} else {
+			self.time.clamp(0.0, duration)
+		};
+		self.pose = skeleton.get_rest_pose();
+		clip.sample(time, &mut self.pose);
+		skeleton.compute_palette(&self.pose)
+	}
+}
+
+fn sample_keyframes<T: VectorSpace<Scalar = f32>, F: Fn(T, T, f32) -> T>(times: &[f32], values: &[T], interpolation: Interpolation, time: f32, lerp: F) -> Option<T> {
+	let count = times.len();
+	let stride = if interpolation == Interpolation::CubicSpline { 3 } else { 1 };
+	if count == 0 || values.len() < count * stride {
+		return None;
+	}
+	let value = |key: usize| values[key * stride + stride / 2];
+	if time <= times[0] {
+		return Some(value(0));
+	}
+	if time >= times[count - 1] {
+		return Some(value(count - 1));
+	}
+	let next = times.partition_point(|key| *key <= time);
+	let previous = next - 1;
+	let delta = times[next] - times[previous];
+	let t = if delta > 0.0 { (time - times[previous]) / delta } else { 0.0 };
+	Some(match interpolation {
+		Interpolation::Step => value(previous),
+		Interpolation::Linear => lerp(value(previous), value(next), t),
+		Interpolation::CubicSpline => {
+			let out_tangent = values[previous * 3 + 2];
+			let in_tangent = values[next * 3];
+			let t2 = t * t;
+			let t3 = t2 * t;
+			value(previous) * (2.0 * t3 - 3.0 * t2 + 1.0)
+				+ out_tangent * ((t3 - 2.0 * t2 + t) * delta)
+				+ value(next) * (-2.0 * t3 + 3.0 * t2)
+				+ in_tangent * ((t3 - t2) * delta)
+		}
+	})
+}
+
+fn nlerp(a: Quaternion<f32>, b: Quaternion<f32>, t: f32) -> Quaternion<f32> {
+	let b = if a.dot(b) < 0.0 { -b } else { b };
+	(a * (1.0 - t) + b * t).normalize()
+}
@@ -0,0 +1,188 @@
+use crate::{Command, Mesh, Pipeline, PositionVertex, Renderer, RenderTarget, Uniform};
+use crate::pipeline::{BlendMode, PipelineOptions};
+use crate::postprocess::{get_post_binds, PostUniform, HDR_FORMAT};
+use shaderc::Compiler;
+use std::any::TypeId;
+use wgpu::*;
+
+pub const MAX_BLOOM_LEVELS: usize = 6;
+
+const POST_VERTEX_SHADER: &str = include_str!("shaders/post_vertex.glsl");
+const BLOOM_BRIGHT_SHADER: &str = include_str!("shaders/bloom_bright.glsl");
+const BLOOM_DOWNSAMPLE_SHADER: &str = include_str!("shaders/bloom_downsample.glsl");
+const BLOOM_UPSAMPLE_SHADER: &str = include_str!("shaders/bloom_upsample.glsl");
+const BLOOM_COMPOSITE_SHADER: &str = include_str!("shaders/bloom_composite.glsl");
+
+pub struct Bloom {
+	bright: Pipeline<PositionVertex, u32>,
+	downsample: Pipeline<PositionVertex, u32>,
+	upsample: Pipeline<PositionVertex, u32>,
+	composite: Pipeline<PositionVertex, u32>,
+	levels: Vec<RenderTarget>,
+	level_uniforms: Vec<Uniform<PostUniform>>,
+	source_uniform: Uniform<PostUniform>,
+	composite_uniform: Uniform<PostUniform>,
+	width: u32,
+	height: u32,
+	threshold: f32,
+	knee: f32,
+	intensity: f32,
+	radius: f32,
+	enabled: bool,
+}
+
+impl Bloom {
+	pub fn new(renderer: &Renderer, compiler: &mut Compiler, threshold: f32, intensity: f32) -> Bloom {
+		let binds = get_post_binds();
+		let options = PipelineOptions {
+			cull_mode: CullMode::None,
+			blend: BlendMode::Replace,
+			color_format: Some(HDR_FORMAT),
+			..PipelineOptions::default()
+		};
+		let bright = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, BLOOM_BRIGHT_SHADER, &binds, &options);
+		let downsample = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, BLOOM_DOWNSAMPLE_SHADER, &binds, &options);
+		let composite = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, BLOOM_COMPOSITE_SHADER, &get_bloom_binds(), &options);
+		let options = PipelineOptions {
+			blend: BlendMode::Additive,
+			..options
+		};
+		let upsample = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, BLOOM_UPSAMPLE_SHADER, &binds, &options);
+		let (width, height) = (renderer.get_width(), renderer.get_height());
+		let knee = threshold * 0.5;
+
+		let mut bloom = Bloom {
+			bright,
+			downsample,
+			upsample,
+			composite,
+			levels: Vec::new(),
+			level_uniforms: Vec::new(),
+			source_uniform: Uniform::new(renderer, &PostUniform::new(width, height, [threshold, knee, 0.0, 0.0])),
+			composite_uniform: Uniform::new(renderer, &PostUniform::new(width, height, [intensity, 0.0, 0.0, 0.0])),
+			width,
+			height,
+			threshold,
+			knee,
+			intensity,
+			radius: 1.0,
+			enabled: true,
+		};
+		bloom.resize(renderer, width, height);
+		bloom
+	}
+
+	pub fn get_threshold(&self) -> f32 {
+		self.threshold
+	}
+
+	pub fn set_threshold(&mut self, renderer: &Renderer, threshold: f32) {
+		self.threshold = threshold;
+		self.knee = threshold * 0.5;
+		self.source_uniform.update(renderer, &PostUniform::new(self.width, self.height, [self.threshold, self.knee, 0.0, 0.0]));
+	}
+
+	pub fn get_intensity(&self) -> f32 {
+		self.intensity
+	}
+
+	pub fn set_intensity(&mut self, renderer: &Renderer, intensity: f32) {
+		self.intensity = intensity;
+		self.composite_uniform.update(renderer, &PostUniform::new(self.width, self.height, [self.intensity, 0.0, 0.0, 0.0]));
+	}
+
+	pub fn get_radius(&self) -> f32 {
+		self.radius
+	}
+
+	pub fn set_radius(&mut self, renderer: &Renderer, radius: f32) {
+		self.radius = radius;
+		for (level, uniform) in self.levels.iter().zip(&self.level_uniforms) {
+			uniform.update(renderer, &PostUniform::new(level.get_width(), level.get_height(), [0.0, 0.0, self.radius, 0.0]));
+		}
+	}
+
+	pub fn get_level_count(&self) -> usize {
+		self.levels.len()
+	}
+
+	pub fn get_level(&self, index: usize) -> &RenderTarget {
+		&self.levels[index]
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+	}
+
+	pub fn resize(&mut self, renderer: &Renderer, width: u32, height: u32) {
+		self.width = width;
+		self.height = height;
+		self.levels.clear();
+		self.level_uniforms.clear();
+		let (mut level_width, mut level_height) = (width / 2, height / 2);
+		while self.levels.len() < MAX_BLOOM_LEVELS && level_width > 0 && level_height > 0 {
+			self.levels.push(RenderTarget::new(renderer, level_width, level_height, HDR_FORMAT));
+			self.level_uniforms.push(Uniform::new(renderer, &PostUniform::new(level_width, level_height, [0.0, 0.0, self.radius, 0.0])));
+			level_width /= 2;
+			level_height /= 2;
+		}
+		self.source_uniform.update(renderer, &PostUniform::new(width, height, [self.threshold, self.knee, 0.0, 0.0]));
+		self.composite_uniform.update(renderer, &PostUniform::new(width, height, [self.intensity, 0.0, 0.0, 0.0]));
+	}
+
+	pub fn execute(&self, renderer: &Renderer, triangle: &Mesh<PositionVertex, u32>, input: &RenderTarget, output: &TextureView) {
+		let clear = [0.0; 4];
+		if let Some(first) = self.levels.first() {
+			let pass = [
+				Command::SetPipeline(&self.bright),
+				Command::SetBind(input, 0),
+				Command::SetBind(&self.source_uniform, 1),
+				Command::SetMesh(triangle),
+				Command::Draw(0..3),
+			];
+			Command::execute_to_view(renderer, first.get_view(), Some(&clear), false, &pass);
+		}
+		for index in 1..self.levels.len() {
+			let pass = [
+				Command::SetPipeline(&self.downsample),
+				Command::SetBind(&self.levels[index - 1], 0),
+				Command::SetBind(&self.level_uniforms[index - 1], 1),
+				Command::SetMesh(triangle),
+				Command::Draw(0..3),
+			];
+			Command::execute_to_view(renderer, self.levels[index].get_view(), Some(&clear), false, &pass);
+		}
+		for index in (1..self.levels.len()).rev() {
+			let pass = [
+				Command::SetPipeline(&self.upsample),
+				Command::SetBind(&self.levels[index], 0),
+				Command::SetBind(&self.level_uniforms[index], 1),
+				Command::SetMesh(triangle),
+				Command::Draw(0..3),
+			];
+			Command::execute_to_view(renderer, self.levels[index - 1].get_view(), None, false, &pass);
+		}
+		let bloom = self.levels.first().unwrap_or(input);
+		let pass = [
+			Command::SetPipeline(&self.composite),
+			Command::SetBind(input, 0),
+			Command::SetBind(&self.composite_uniform, 1),
+			Command::SetBind(bloom, 2),
+			Command::SetMesh(triangle),
+			Command::Draw(0..3),
+		];
+		Command::execute_to_view(renderer, output, Some(&clear), false, &pass);
+	}
+}
+
+pub fn get_bloom_binds() -> [TypeId; 3] {
+	[
+		TypeId::of::<RenderTarget>(),
+		TypeId::of::<Uniform<PostUniform>>(),
+		TypeId::of::<RenderTarget>(),
+	]
+}
\ No newline at end of file
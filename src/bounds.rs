@@ -0,0 +1,108 @@
+use cgmath::{Matrix4, Vector3, Vector4, InnerSpace};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+	pub min: Vector3<f32>,
+	pub max: Vector3<f32>,
+}
+
+impl Aabb {
+	pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Aabb {
+		Aabb {
+			min,
+			max,
+		}
+	}
+
+	pub fn from_points<T: IntoIterator<Item = [f32; 3]>>(points: T) -> Option<Aabb> {
+		let mut points = points.into_iter();
+		let first = Vector3::from(points.next()?);
+		let mut aabb = Aabb::new(first, first);
+		for point in points {
+			aabb.min = Vector3::new(aabb.min.x.min(point[0]), aabb.min.y.min(point[1]), aabb.min.z.min(point[2]));
+			aabb.max = Vector3::new(aabb.max.x.max(point[0]), aabb.max.y.max(point[1]), aabb.max.z.max(point[2]));
+		}
+		Some(aabb)
+	}
+
+	pub fn get_center(&self) -> Vector3<f32> {
+		(self.min + self.max) * 0.5
+	}
+
+	pub fn get_extents(&self) -> Vector3<f32> {
+		(self.max - self.min) * 0.5
+	}
+
+	pub fn transform(&self, matrix: &Matrix4<f32>) -> Aabb {
+		let center = matrix * self.get_center().extend(1.0);
+		let center = center.truncate() / center.w;
+		let extents = self.get_extents();
+		let extents = Vector3::new(
+			matrix.x.x.abs() * extents.x + matrix.y.x.abs() * extents.y + matrix.z.x.abs() * extents.z,
+			matrix.x.y.abs() * extents.x + matrix.y.y.abs() * extents.y + matrix.z.y.abs() * extents.z,
+			matrix.x.z.abs() * extents.x + matrix.y.z.abs() * extents.y + matrix.z.z.abs() * extents.z,
+		);
+		Aabb::new(center - extents, center + extents)
+	}
+
+	pub fn merge(&self, other: &Aabb) -> Aabb {
+		Aabb::new(
+			Vector3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+			Vector3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+		)
+	}
+
+	pub fn contains(&self, point: Vector3<f32>) -> bool {
+		point.x >= self.min.x && point.y >= self.min.y && point.z >= self.min.z
+			&& point.x <= self.max.x && point.y <= self.max.y && point.z <= self.max.z
+	}
+
+	pub fn to_bounding_sphere(&self) -> BoundingSphere {
+		BoundingSphere::new(self.get_center(), self.get_extents().magnitude())
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+	pub center: Vector3<f32>,
+	pub radius: f32,
+}
+
+impl BoundingSphere {
+	pub fn new(center: Vector3<f32>, radius: f32) -> BoundingSphere {
+		BoundingSphere {
+			center,
+			radius,
+		}
+	}
+
+	pub fn from_points(points: &[[f32; 3]]) -> Option<BoundingSphere> {
+		let first = Vector3::from(*points.first()?);
+		let farthest = |from: Vector3<f32>| {
+			points.iter()
+				.map(|point| Vector3::from(*point))
+				.fold(from, |best, point| if (point - from).magnitude2() > (best - from).magnitude2() { point } else { best })
+		};
+		let a = farthest(first);
+		let b = farthest(a);
+		let mut sphere = BoundingSphere::new((a + b) * 0.5, (b - a).magnitude() * 0.5);
+		for point in points {
+			let point = Vector3::from(*point);
+			let distance = (point - sphere.center).magnitude();
+			if distance > sphere.radius {
+				let radius = (sphere.radius + distance) * 0.5;
+				sphere.center += (point - sphere.center) * ((radius - sphere.radius) / distance);
+				sphere.radius = radius;
+			}
+		}
+		Some(sphere)
+	}
+
+	pub fn transform(&self, matrix: &Matrix4<f32>) -> BoundingSphere {
+		let center = matrix * Vector4::new(self.center.x, self.center.y, self.center.z, 1.0);
+		let scale = matrix.x.truncate().magnitude()
+			.max(matrix.y.truncate().magnitude())
+			.max(matrix.z.truncate().magnitude());
+		BoundingSphere::new(center.truncate() / center.w, self.radius * scale)
+	}
+}
@@ -0,0 +1,294 @@
+use crate::{Renderer, Frustum};
+use cgmath::{Matrix4, Point3, Vector2, Vector3, Rad, InnerSpace, EuclideanSpace, perspective, ortho};
+use bytemuck::{Pod, Zeroable};
+use std::f32::consts::FRAC_PI_2;
+
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+	1.0, 0.0, 0.0, 0.0,
+	0.0, 1.0, 0.0, 0.0,
+	0.0, 0.0, 0.5, 0.0,
+	0.0, 0.0, 0.5, 1.0,
+);
+
+const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.001;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+	Perspective {
+		fov_y: f32,
+		near: f32,
+		far: f32,
+	},
+	Orthographic {
+		height: f32,
+		near: f32,
+		far: f32,
+	},
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+	pub eye: Point3<f32>,
+	pub target: Point3<f32>,
+	pub up: Vector3<f32>,
+	pub projection: Projection,
+	pub aspect: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CameraUniform {
+	pub view: [[f32; 4]; 4],
+	pub projection: [[f32; 4]; 4],
+	pub view_projection: [[f32; 4]; 4],
+	pub position: [f32; 4],
+}
+
+unsafe impl Pod for CameraUniform {}
+
+unsafe impl Zeroable for CameraUniform {}
+
+impl Camera {
+	pub fn new_perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Camera {
+		Camera {
+			eye: Point3::new(0.0, 0.0, 1.0),
+			target: Point3::new(0.0, 0.0, 0.0),
+			up: Vector3::unit_y(),
+			projection: Projection::Perspective {
+				fov_y,
+				near,
+				far,
+			},
+			aspect,
+		}
+	}
+
+	pub fn new_orthographic(height: f32, aspect: f32, near: f32, far: f32) -> Camera {
+		Camera {
+			eye: Point3::new(0.0, 0.0, 1.0),
+			target: Point3::new(0.0, 0.0, 0.0),
+			up: Vector3::unit_y(),
+			projection: Projection::Orthographic {
+				height,
+				near,
+				far,
+			},
+			aspect,
+		}
+	}
+
+	pub fn look_at(&mut self, eye: Point3<f32>, target: Point3<f32>, up: Vector3<f32>) {
+		self.eye = eye;
+		self.target = target;
+		self.up = up;
+	}
+
+	pub fn set_viewport(&mut self, width: u32, height: u32) {
+		if width > 0 && height > 0 {
+			self.aspect = width as f32 / height as f32;
+		}
+	}
+
+	pub fn update_aspect(&mut self, renderer: &Renderer) {
+		self.set_viewport(renderer.get_width(), renderer.get_height());
+	}
+
+	pub fn get_forward(&self) -> Vector3<f32> {
+		(self.target - self.eye).normalize()
+	}
+
+	pub fn get_view_matrix(&self) -> Matrix4<f32> {
+		Matrix4::look_at(self.eye, self.target, self.up)
+	}
+
+	pub fn get_projection_matrix(&self) -> Matrix4<f32> {
+		let projection = match self.projection {
+			Projection::Perspective { fov_y, near, far } => perspective(Rad(fov_y), self.aspect, near, far),
+			Projection::Orthographic { height, near, far } => {
+				let half_height = height * 0.5;
+				let half_width = half_height * self.aspect;
+				ortho(-half_width, half_width, -half_height, half_height, near, far)
+			}
+		};
+		OPENGL_TO_WGPU_MATRIX * projection
+	}
+
+	pub fn get_view_projection_matrix(&self) -> Matrix4<f32> {
+		self.get_projection_matrix() * self.get_view_matrix()
+	}
+
+	pub fn get_frustum(&self) -> Frustum {
+		Frustum::from_matrix(&self.get_view_projection_matrix())
+	}
+
+	pub fn get_uniform(&self) -> CameraUniform {
+		let view = self.get_view_matrix();
+		let projection = self.get_projection_matrix();
+		CameraUniform {
+			view: view.into(),
+			projection: projection.into(),
+			view_projection: (projection * view).into(),
+			position: [self.eye.x, self.eye.y, self.eye.z, 1.0],
+		}
+	}
+}
+
+pub trait ControllerTrait {
+	fn update(&self, camera: &mut Camera);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitController {
+	pub target: Point3<f32>,
+	pub distance: f32,
+	pub yaw: f32,
+	pub pitch: f32,
+	pub min_distance: f32,
+	pub max_distance: f32,
+	pub rotate_speed: f32,
+	pub zoom_speed: f32,
+	pub pan_speed: f32,
+}
+
+impl OrbitController {
+	pub fn new(target: Point3<f32>, distance: f32) -> OrbitController {
+		OrbitController {
+			target,
+			distance,
+			yaw: 0.0,
+			pitch: 0.0,
+			min_distance: 0.01,
+			max_distance: f32::MAX,
+			rotate_speed: 0.005,
+			zoom_speed: 0.1,
+			pan_speed: 0.001,
+		}
+	}
+
+	pub fn rotate(&mut self, delta_x: f32, delta_y: f32) {
+		self.yaw -= delta_x * self.rotate_speed;
+		self.pitch = (self.pitch - delta_y * self.rotate_speed).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+	}
+
+	pub fn zoom(&mut self, delta: f32) {
+		self.distance = (self.distance * (1.0 - delta * self.zoom_speed)).clamp(self.min_distance, self.max_distance);
+	}
+
+	pub fn pan(&mut self, delta_x: f32, delta_y: f32) {
+		let offset = self.get_offset();
+		let forward = -offset.normalize();
+		let right = forward.cross(Vector3::unit_y()).normalize();
+		let up = right.cross(forward);
+		let scale = self.pan_speed * self.distance;
+		self.target += right * (-delta_x * scale) + up * (delta_y * scale);
+	}
+
+	pub fn get_eye(&self) -> Point3<f32> {
+		self.target + self.get_offset()
+	}
+
+	fn get_offset(&self) -> Vector3<f32> {
+		-get_direction(self.yaw, self.pitch) * self.distance
+	}
+}
+
+impl ControllerTrait for OrbitController {
+	fn update(&self, camera: &mut Camera) {
+		camera.look_at(self.get_eye(), self.target, Vector3::unit_y());
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlyController {
+	pub position: Point3<f32>,
+	pub yaw: f32,
+	pub pitch: f32,
+	pub move_speed: f32,
+	pub look_speed: f32,
+}
+
+impl FlyController {
+	pub fn new(position: Point3<f32>) -> FlyController {
+		FlyController {
+			position,
+			yaw: 0.0,
+			pitch: 0.0,
+			move_speed: 5.0,
+			look_speed: 0.003,
+		}
+	}
+
+	pub fn look(&mut self, delta_x: f32, delta_y: f32) {
+		self.yaw -= delta_x * self.look_speed;
+		self.pitch = (self.pitch - delta_y * self.look_speed).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+	}
+
+	pub fn translate(&mut self, forward: f32, right: f32, up: f32, delta_time: f32) {
+		let direction = self.get_forward();
+		let side = direction.cross(Vector3::unit_y()).normalize();
+		let distance = self.move_speed * delta_time;
+		self.position += direction * (forward * distance) + side * (right * distance) + Vector3::unit_y() * (up * distance);
+	}
+
+	pub fn get_forward(&self) -> Vector3<f32> {
+		get_direction(self.yaw, self.pitch)
+	}
+}
+
+impl ControllerTrait for FlyController {
+	fn update(&self, camera: &mut Camera) {
+		camera.look_at(self.position, self.position + self.get_forward(), Vector3::unit_y());
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PanController {
+	pub center: Vector2<f32>,
+	pub height: f32,
+	pub zoom: f32,
+	pub min_zoom: f32,
+	pub max_zoom: f32,
+	pub zoom_speed: f32,
+}
+
+impl PanController {
+	pub fn new(center: Vector2<f32>, height: f32) -> PanController {
+		PanController {
+			center,
+			height,
+			zoom: 1.0,
+			min_zoom: 0.01,
+			max_zoom: 100.0,
+			zoom_speed: 0.1,
+		}
+	}
+
+	pub fn pan(&mut self, delta_x: f32, delta_y: f32) {
+		let scale = self.height / self.zoom;
+		self.center += Vector2::new(-delta_x * scale, delta_y * scale);
+	}
+
+	pub fn zoom(&mut self, delta: f32) {
+		self.zoom = (self.zoom * (1.0 + delta * self.zoom_speed)).clamp(self.min_zoom, self.max_zoom);
+	}
+
+	pub fn screen_to_world(&self, camera: &Camera, screen: Vector2<f32>) -> Vector2<f32> {
+		let height = self.height / self.zoom;
+		let width = height * camera.aspect;
+		self.center + Vector2::new((screen.x - 0.5) * width, (0.5 - screen.y) * height)
+	}
+}
+
+impl ControllerTrait for PanController {
+	fn update(&self, camera: &mut Camera) {
+		let center = Point3::from_vec(self.center.extend(0.0));
+		camera.look_at(center + Vector3::unit_z(), center, Vector3::unit_y());
+		if let Projection::Orthographic { height, .. } = &mut camera.projection {
+			*height = self.height / self.zoom;
+		}
+	}
+}
+
+fn get_direction(yaw: f32, pitch: f32) -> Vector3<f32> {
+	Vector3::new(-pitch.cos() * yaw.sin(), pitch.sin(), -pitch.cos() * yaw.cos())
+}
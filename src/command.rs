@@ -1,4 +1,4 @@
-use crate::{Renderer, Bind, PipelineTrait, MeshTrait};
+use crate::{Renderer, Bind, PipelineTrait, MeshTrait, MaterialTrait};
 use std::ops::Range;
 use wgpu::*;
 
@@ -6,49 +6,117 @@ pub enum Command<'a> {
 	Pass,
 	SetPipeline(&'a dyn PipelineTrait),
 	SetMesh(&'a dyn MeshTrait),
+	SetStreams(&'a dyn MeshTrait, &'a [(u32, u32)]),
 	SetBind(&'a dyn Bind, u32),
+	SetMaterial(&'a dyn MaterialTrait),
 	Draw(Range<u32>),
 }
 
 impl<'a> Command<'a> {
 	pub fn execute(renderer: &mut Renderer, clear_color: &[f64; 4], commands: &[Command]) {
-		let frame = renderer.get_swap_chain_mut().get_next_texture().expect("texture_finding_failed");
+		Command::execute_pass(renderer, clear_color, commands, false);
+	}
+
+	pub fn execute_with_depth(renderer: &mut Renderer, clear_color: &[f64; 4], commands: &[Command]) {
+		Command::execute_pass(renderer, clear_color, commands, true);
+	}
+
+	pub fn execute_depth_only(renderer: &Renderer, depth_view: &TextureView, commands: &[Command]) {
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("execute_depth_only_encoder")
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		{
+			let render_pass_depth_attachment_desc = RenderPassDepthStencilAttachmentDescriptor {
+				attachment: depth_view,
+				depth_load_op: LoadOp::Clear,
+				depth_store_op: StoreOp::Store,
+				clear_depth: 1.0,
+				stencil_load_op: LoadOp::Clear,
+				stencil_store_op: StoreOp::Store,
+				clear_stencil: 0,
+			};
+			let render_pass_desc = RenderPassDescriptor {
+				color_attachments: &[],
+				depth_stencil_attachment: Some(render_pass_depth_attachment_desc),
+			};
+			let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
+			record(&mut render_pass, commands);
+		}
+		renderer.get_queue().submit(&[encoder.finish()]);
+	}
+
+	pub fn execute_to_view(renderer: &Renderer, view: &TextureView, clear_color: Option<&[f64; 4]>, depth: bool, commands: &[Command]) {
 		let encoder_desc = CommandEncoderDescriptor {
 			label: Some("execute_encoder")
 		};
 		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
 		{
 			let render_pass_color_attachment_desc = RenderPassColorAttachmentDescriptor {
-				attachment: &frame.view,
+				attachment: view,
 				resolve_target: None,
-				load_op: LoadOp::Clear,
+				load_op: if clear_color.is_some() { LoadOp::Clear } else { LoadOp::Load },
 				store_op: StoreOp::Store,
-				clear_color: Color { r: clear_color[0], g: clear_color[1], b: clear_color[2], a: clear_color[3] },
+				clear_color: clear_color.map_or(Color::BLACK, |color| Color { r: color[0], g: color[1], b: color[2], a: color[3] }),
+			};
+			let render_pass_depth_attachment_desc = RenderPassDepthStencilAttachmentDescriptor {
+				attachment: renderer.get_depth_view(),
+				depth_load_op: LoadOp::Clear,
+				depth_store_op: StoreOp::Store,
+				clear_depth: 1.0,
+				stencil_load_op: LoadOp::Clear,
+				stencil_store_op: StoreOp::Store,
+				clear_stencil: 0,
 			};
 			let render_pass_desc = RenderPassDescriptor {
 				color_attachments: &[render_pass_color_attachment_desc],
-				depth_stencil_attachment: None,
+				depth_stencil_attachment: if depth { Some(render_pass_depth_attachment_desc) } else { None },
 			};
 			let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
-			for command in commands {
-				match command {
-					Command::SetPipeline(pipeline) => {
-						render_pass.set_pipeline(pipeline.get_render_pipeline());
-					}
-					Command::SetMesh(mesh) => {
-						render_pass.set_vertex_buffer(0, mesh.get_vertex_buffer(), 0, 0);
-						render_pass.set_index_buffer(mesh.get_index_buffer(), 0, 0);
-					}
-					Command::SetBind(bind, pos) => {
-						render_pass.set_bind_group(*pos, bind.get_bind_group(), &[]);
-					}
-					Command::Draw(range) => {
-						render_pass.draw_indexed(range.clone(), 0, 0..1);
-					}
-					_ => ()
+			record(&mut render_pass, commands);
+		}
+		renderer.get_queue().submit(&[encoder.finish()]);
+	}
+
+	fn execute_pass(renderer: &mut Renderer, clear_color: &[f64; 4], commands: &[Command], depth: bool) {
+		let frame = renderer.get_swap_chain_mut().get_next_texture().expect("texture_finding_failed");
+		Command::execute_to_view(renderer, &frame.view, Some(clear_color), depth, commands);
+	}
+}
+
+fn record<'a>(render_pass: &mut RenderPass<'a>, commands: &'a [Command<'a>]) {
+	for command in commands {
+		match command {
+			Command::SetPipeline(pipeline) => {
+				render_pass.set_pipeline(pipeline.get_render_pipeline());
+			}
+			Command::SetMesh(mesh) => {
+				for (slot, buffer) in mesh.get_vertex_buffers().into_iter().enumerate() {
+					render_pass.set_vertex_buffer(slot as u32, buffer, 0, 0);
+				}
+				render_pass.set_index_buffer(mesh.get_index_buffer(), 0, 0);
+			}
+			Command::SetStreams(mesh, streams) => {
+				let buffers = mesh.get_vertex_buffers();
+				for (stream, slot) in streams.iter() {
+					let buffer = buffers.get(*stream as usize).expect("stream_slot_out_of_range");
+					render_pass.set_vertex_buffer(*slot, buffer, 0, 0);
+				}
+				render_pass.set_index_buffer(mesh.get_index_buffer(), 0, 0);
+			}
+			Command::SetBind(bind, pos) => {
+				render_pass.set_bind_group(*pos, bind.get_bind_group(), &[]);
+			}
+			Command::SetMaterial(material) => {
+				render_pass.set_pipeline(material.get_pipeline().get_render_pipeline());
+				for (bind, pos) in material.get_binds() {
+					render_pass.set_bind_group(pos, bind.get_bind_group(), &[]);
 				}
 			}
+			Command::Draw(range) => {
+				render_pass.draw_indexed(range.clone(), 0, 0..1);
+			}
+			_ => ()
 		}
-		renderer.get_queue().submit(&[encoder.finish()]);
 	}
 }
\ No newline at end of file
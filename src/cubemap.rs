@@ -0,0 +1,274 @@
+use crate::{Bind, Command, Mesh, Pipeline, PositionVertex, Renderer, Texture, TextureOptions, Uniform};
+use crate::hdr::HdrImage;
+use crate::texture::COPY_ROW_ALIGNMENT;
+use crate::pipeline::{BlendMode, PipelineOptions};
+use crate::postprocess::{PostUniform, HDR_FORMAT};
+use shaderc::Compiler;
+use std::any::TypeId;
+use wgpu::*;
+
+pub const CUBE_FACE_COUNT: u32 = 6;
+
+const POST_VERTEX_SHADER: &str = include_str!("shaders/post_vertex.glsl");
+const CUBE_EQUIRECTANGULAR_SHADER: &str = include_str!("shaders/cube_equirectangular.glsl");
+
+pub struct CubeMap {
+	texture: wgpu::Texture,
+	view: TextureView,
+	_sampler: Sampler,
+	bind_group: BindGroup,
+	size: u32,
+	mip_level_count: u32,
+	format: TextureFormat,
+}
+
+impl CubeMap {
+	pub fn new(renderer: &Renderer, size: u32, mip_level_count: u32, format: TextureFormat) -> CubeMap {
+		let texture_desc = TextureDescriptor {
+			size: Extent3d {
+				width: size,
+				height: size,
+				depth: 1,
+			},
+			array_layer_count: CUBE_FACE_COUNT,
+			mip_level_count,
+			sample_count: 1,
+			dimension: TextureDimension::D2,
+			format,
+			usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST | TextureUsage::OUTPUT_ATTACHMENT,
+			label: Some("cube_map"),
+		};
+		let texture = renderer.get_device().create_texture(&texture_desc);
+		let view = texture.create_view(&TextureViewDescriptor {
+			format,
+			dimension: TextureViewDimension::Cube,
+			aspect: TextureAspect::All,
+			base_mip_level: 0,
+			level_count: mip_level_count,
+			base_array_layer: 0,
+			array_layer_count: CUBE_FACE_COUNT,
+		});
+		let sampler_desc = SamplerDescriptor {
+			address_mode_u: AddressMode::ClampToEdge,
+			address_mode_v: AddressMode::ClampToEdge,
+			address_mode_w: AddressMode::ClampToEdge,
+			mag_filter: FilterMode::Linear,
+			min_filter: FilterMode::Linear,
+			mipmap_filter: FilterMode::Linear,
+			lod_min_clamp: 0.0,
+			lod_max_clamp: mip_level_count as f32,
+			compare: CompareFunction::Always,
+		};
+		let _sampler = renderer.get_device().create_sampler(&sampler_desc);
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("cube_map_type_not_registered"),
+			bindings: &[
+				Binding {
+					binding: 0,
+					resource: BindingResource::TextureView(&view),
+				},
+				Binding {
+					binding: 1,
+					resource: BindingResource::Sampler(&_sampler),
+				},
+			],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		CubeMap {
+			texture,
+			view,
+			_sampler,
+			bind_group,
+			size,
+			mip_level_count,
+			format,
+		}
+	}
+
+	pub fn from_images(renderer: &Renderer, faces: &[&[u8]; 6]) -> CubeMap {
+		let mut size = None;
+		let mut pixels = Vec::with_capacity(faces.len());
+		for face in faces.iter() {
+			let image = image::load_from_memory(face).expect("image_reading_failed").to_rgba8();
+			let (width, height) = image.dimensions();
+			if width != height || matches!(size, Some(size) if size != width) {
+				panic!("cube_face_size_mismatch");
+			}
+			size = Some(width);
+			pixels.push(image.into_raw());
+		}
+		let faces = [&pixels[0][..], &pixels[1][..], &pixels[2][..], &pixels[3][..], &pixels[4][..], &pixels[5][..]];
+		CubeMap::from_rgba(renderer, size.expect("cube_face_size_mismatch"), &faces, true)
+	}
+
+	pub fn from_rgba(renderer: &Renderer, size: u32, faces: &[&[u8]; 6], srgb: bool) -> CubeMap {
+		let format = if srgb { TextureFormat::Rgba8UnormSrgb } else { TextureFormat::Rgba8Unorm };
+		let cube_map = CubeMap::new(renderer, size, 1, format);
+		for (face, rgba) in faces.iter().enumerate() {
+			cube_map.write_face(renderer, face as u32, rgba, 4);
+		}
+		cube_map
+	}
+
+	pub fn from_hdr(renderer: &Renderer, compiler: &mut Compiler, image: &HdrImage, size: u32) -> CubeMap {
+		let options = TextureOptions {
+			mip_filter: None,
+			..TextureOptions::linear()
+		};
+		let source = Texture::from_hdr_with_options(renderer, image, &options);
+		CubeMap::from_equirectangular(renderer, compiler, &source, size)
+	}
+
+	pub fn from_equirectangular(renderer: &Renderer, compiler: &mut Compiler, source: &Texture, size: u32) -> CubeMap {
+		let cube_map = CubeMap::new(renderer, size, 1, HDR_FORMAT);
+		let pipeline: Pipeline<PositionVertex, u32> = Pipeline::new_with_options(
+			renderer,
+			compiler,
+			POST_VERTEX_SHADER,
+			CUBE_EQUIRECTANGULAR_SHADER,
+			&get_equirectangular_binds(),
+			&get_cube_face_options(),
+		);
+		let triangle = create_fullscreen_triangle(renderer);
+		for face in 0..CUBE_FACE_COUNT {
+			let uniform = Uniform::new(renderer, &PostUniform::new(size, size, [face as f32, 0.0, 0.0, 0.0]));
+			let pass = [
+				Command::SetPipeline(&pipeline),
+				Command::SetBind(source, 0),
+				Command::SetBind(&uniform, 1),
+				Command::SetMesh(&triangle),
+				Command::Draw(0..3),
+			];
+			Command::execute_to_view(renderer, &cube_map.get_face_view(face, 0), Some(&[0.0; 4]), false, &pass);
+		}
+		cube_map
+	}
+
+	pub fn get_face_view(&self, face: u32, mip_level: u32) -> TextureView {
+		if face >= CUBE_FACE_COUNT || mip_level >= self.mip_level_count {
+			panic!("cube_face_out_of_range");
+		}
+		self.texture.create_view(&TextureViewDescriptor {
+			format: self.format,
+			dimension: TextureViewDimension::D2,
+			aspect: TextureAspect::All,
+			base_mip_level: mip_level,
+			level_count: 1,
+			base_array_layer: face,
+			array_layer_count: 1,
+		})
+	}
+
+	pub fn get_view(&self) -> &TextureView {
+		&self.view
+	}
+
+	pub fn get_sampler(&self) -> &Sampler {
+		&self._sampler
+	}
+
+	pub fn get_size(&self) -> u32 {
+		self.size
+	}
+
+	pub fn get_mip_level_count(&self) -> u32 {
+		self.mip_level_count
+	}
+
+	pub fn get_format(&self) -> TextureFormat {
+		self.format
+	}
+
+	fn write_face(&self, renderer: &Renderer, face: u32, pixels: &[u8], bytes_per_pixel: usize) {
+		if pixels.len() != (self.size * self.size) as usize * bytes_per_pixel {
+			panic!("cube_face_size_mismatch");
+		}
+		let row_size = bytes_per_pixel * self.size as usize;
+		let padded_row_size = row_size.div_ceil(COPY_ROW_ALIGNMENT) * COPY_ROW_ALIGNMENT;
+		let mut padded = vec![0; padded_row_size * self.size as usize];
+		for (source, destination) in pixels.chunks_exact(row_size).zip(padded.chunks_exact_mut(padded_row_size)) {
+			destination[..row_size].copy_from_slice(source);
+		}
+		let buffer = renderer.get_device().create_buffer_with_data(&padded, BufferUsage::COPY_SRC);
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("cube_face_copy_encoder"),
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		let buffer_copy_view = BufferCopyView {
+			buffer: &buffer,
+			offset: 0,
+			bytes_per_row: padded_row_size as u32,
+			rows_per_image: self.size,
+		};
+		let texture_copy_view = TextureCopyView {
+			texture: &self.texture,
+			mip_level: 0,
+			array_layer: face,
+			origin: Origin3d::ZERO,
+		};
+		let copy_size = Extent3d {
+			width: self.size,
+			height: self.size,
+			depth: 1,
+		};
+		encoder.copy_buffer_to_texture(buffer_copy_view, texture_copy_view, copy_size);
+		renderer.get_queue().submit(&[encoder.finish()]);
+	}
+}
+
+impl Bind for CubeMap {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[
+				BindGroupLayoutEntry {
+					binding: 0,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::SampledTexture {
+						multisampled: false,
+						dimension: TextureViewDimension::Cube,
+						component_type: TextureComponentType::Float,
+					},
+				},
+				BindGroupLayoutEntry {
+					binding: 1,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::Sampler {
+						comparison: false,
+					},
+				},
+			],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+pub(crate) fn get_cube_face_options() -> PipelineOptions {
+	PipelineOptions {
+		cull_mode: CullMode::None,
+		blend: BlendMode::Replace,
+		color_format: Some(HDR_FORMAT),
+		..PipelineOptions::default()
+	}
+}
+
+pub(crate) fn create_fullscreen_triangle(renderer: &Renderer) -> Mesh<PositionVertex, u32> {
+	Mesh::new(renderer, &[
+		PositionVertex::new([-1.0, -1.0, 0.0]),
+		PositionVertex::new([3.0, -1.0, 0.0]),
+		PositionVertex::new([-1.0, 3.0, 0.0]),
+	], &[0, 1, 2])
+}
+
+pub fn get_equirectangular_binds() -> [TypeId; 2] {
+	[
+		TypeId::of::<Texture>(),
+		TypeId::of::<Uniform<PostUniform>>(),
+	]
+}
\ No newline at end of file
@@ -0,0 +1,217 @@
+use crate::{Bind, CameraUniform, Command, CubeMap, Mesh, Pipeline, PositionVertex, Renderer, Texture, Uniform};
+use crate::cubemap::{create_fullscreen_triangle, get_cube_face_options, get_equirectangular_binds, CUBE_FACE_COUNT};
+use crate::pipeline::{BlendMode, PipelineOptions};
+use crate::postprocess::{PostUniform, HDR_FORMAT};
+use shaderc::Compiler;
+use std::any::TypeId;
+use std::rc::Rc;
+use wgpu::*;
+
+pub const IRRADIANCE_SIZE: u32 = 32;
+pub const SPECULAR_SIZE: u32 = 128;
+pub const SPECULAR_MIP_LEVELS: u32 = 5;
+pub const SPECULAR_SAMPLE_COUNT: u32 = 512;
+
+const POST_VERTEX_SHADER: &str = include_str!("shaders/post_vertex.glsl");
+const CUBE_IRRADIANCE_SHADER: &str = include_str!("shaders/cube_irradiance.glsl");
+const CUBE_SPECULAR_SHADER: &str = include_str!("shaders/cube_specular.glsl");
+const SKYBOX_VERTEX_SHADER: &str = include_str!("shaders/skybox_vertex.glsl");
+const SKYBOX_FRAGMENT_SHADER: &str = include_str!("shaders/skybox_fragment.glsl");
+
+pub struct Environment {
+	irradiance: CubeMap,
+	specular: CubeMap,
+	bind_group: BindGroup,
+}
+
+impl Environment {
+	pub fn new(renderer: &Renderer, compiler: &mut Compiler, source: &CubeMap) -> Environment {
+		Environment::with_sizes(renderer, compiler, source, IRRADIANCE_SIZE, SPECULAR_SIZE, SPECULAR_MIP_LEVELS)
+	}
+
+	pub fn with_sizes(renderer: &Renderer, compiler: &mut Compiler, source: &CubeMap, irradiance_size: u32, specular_size: u32, specular_mip_levels: u32) -> Environment {
+		let specular_mip_levels = specular_mip_levels.clamp(1, 32 - specular_size.max(1).leading_zeros());
+		let binds = get_prefilter_binds();
+		let options = get_cube_face_options();
+		let triangle = create_fullscreen_triangle(renderer);
+		let source_size = source.get_size() as f32;
+
+		let irradiance = CubeMap::new(renderer, irradiance_size, 1, HDR_FORMAT);
+		let pipeline = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, CUBE_IRRADIANCE_SHADER, &binds, &options);
+		prefilter(renderer, &pipeline, &triangle, source, &irradiance, 0, [0.0, source_size, 0.0]);
+
+		let specular = CubeMap::new(renderer, specular_size, specular_mip_levels, HDR_FORMAT);
+		let pipeline = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, CUBE_SPECULAR_SHADER, &binds, &options);
+		for mip_level in 0..specular_mip_levels {
+			let roughness = if specular_mip_levels > 1 { mip_level as f32 / (specular_mip_levels - 1) as f32 } else { 0.0 };
+			prefilter(renderer, &pipeline, &triangle, source, &specular, mip_level, [roughness, source_size, SPECULAR_SAMPLE_COUNT as f32]);
+		}
+
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("environment_type_not_registered"),
+			bindings: &[
+				Binding {
+					binding: 0,
+					resource: BindingResource::TextureView(irradiance.get_view()),
+				},
+				Binding {
+					binding: 1,
+					resource: BindingResource::Sampler(irradiance.get_sampler()),
+				},
+				Binding {
+					binding: 2,
+					resource: BindingResource::TextureView(specular.get_view()),
+				},
+				Binding {
+					binding: 3,
+					resource: BindingResource::Sampler(specular.get_sampler()),
+				},
+			],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		Environment {
+			irradiance,
+			specular,
+			bind_group,
+		}
+	}
+
+	pub fn get_irradiance(&self) -> &CubeMap {
+		&self.irradiance
+	}
+
+	pub fn get_specular(&self) -> &CubeMap {
+		&self.specular
+	}
+}
+
+impl Bind for Environment {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let mut bindings = Vec::with_capacity(4);
+		for index in 0..2 {
+			bindings.push(BindGroupLayoutEntry {
+				binding: index * 2,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::SampledTexture {
+					multisampled: false,
+					dimension: TextureViewDimension::Cube,
+					component_type: TextureComponentType::Float,
+				},
+			});
+			bindings.push(BindGroupLayoutEntry {
+				binding: index * 2 + 1,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::Sampler {
+					comparison: false,
+				},
+			});
+		}
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &bindings,
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+pub struct Skybox {
+	pipeline: Pipeline<PositionVertex, u32>,
+	triangle: Mesh<PositionVertex, u32>,
+	cube_map: Rc<CubeMap>,
+}
+
+impl Skybox {
+	pub fn new(renderer: &Renderer, compiler: &mut Compiler, cube_map: Rc<CubeMap>, color_format: TextureFormat) -> Skybox {
+		let options = PipelineOptions {
+			cull_mode: CullMode::None,
+			depth_test: true,
+			depth_write: false,
+			blend: BlendMode::Replace,
+			color_format: Some(color_format),
+			..PipelineOptions::default()
+		};
+		let pipeline = Pipeline::new_with_options(renderer, compiler, SKYBOX_VERTEX_SHADER, SKYBOX_FRAGMENT_SHADER, &get_skybox_binds(), &options);
+
+		Skybox {
+			pipeline,
+			triangle: create_fullscreen_triangle(renderer),
+			cube_map,
+		}
+	}
+
+	pub fn get_cube_map(&self) -> &Rc<CubeMap> {
+		&self.cube_map
+	}
+
+	pub fn set_cube_map(&mut self, cube_map: Rc<CubeMap>) {
+		self.cube_map = cube_map;
+	}
+
+	pub fn get_commands<'a>(&'a self, camera: &'a dyn Bind) -> Vec<Command<'a>> {
+		vec![
+			Command::SetPipeline(&self.pipeline),
+			Command::SetBind(camera, 0),
+			Command::SetBind(self.cube_map.as_ref(), 1),
+			Command::SetMesh(&self.triangle),
+			Command::Draw(0..3),
+		]
+	}
+}
+
+fn prefilter(renderer: &Renderer, pipeline: &Pipeline<PositionVertex, u32>, triangle: &Mesh<PositionVertex, u32>, source: &CubeMap, target: &CubeMap, mip_level: u32, parameters: [f32; 3]) {
+	let size = (target.get_size() >> mip_level).max(1);
+	for face in 0..CUBE_FACE_COUNT {
+		let uniform = Uniform::new(renderer, &PostUniform::new(size, size, [face as f32, parameters[0], parameters[1], parameters[2]]));
+		let pass = [
+			Command::SetPipeline(pipeline),
+			Command::SetBind(source, 0),
+			Command::SetBind(&uniform, 1),
+			Command::SetMesh(triangle),
+			Command::Draw(0..3),
+		];
+		Command::execute_to_view(renderer, &target.get_face_view(face, mip_level), Some(&[0.0; 4]), false, &pass);
+	}
+}
+
+pub fn get_prefilter_binds() -> [TypeId; 2] {
+	[
+		TypeId::of::<CubeMap>(),
+		TypeId::of::<Uniform<PostUniform>>(),
+	]
+}
+
+pub fn get_skybox_binds() -> [TypeId; 2] {
+	[
+		TypeId::of::<Uniform<CameraUniform>>(),
+		TypeId::of::<CubeMap>(),
+	]
+}
+
+pub fn register_environment(renderer: &mut Renderer) {
+	if renderer.get_bind_group_layout::<Texture>().is_none() {
+		renderer.register_bind_group_layout::<Texture>();
+	}
+	if renderer.get_bind_group_layout::<CubeMap>().is_none() {
+		renderer.register_bind_group_layout::<CubeMap>();
+	}
+	if renderer.get_bind_group_layout::<Environment>().is_none() {
+		renderer.register_bind_group_layout::<Environment>();
+	}
+	if renderer.get_bind_group_layout::<Uniform<PostUniform>>().is_none() {
+		renderer.register_bind_group_layout::<Uniform<PostUniform>>();
+	}
+	if renderer.get_bind_group_layout::<Uniform<CameraUniform>>().is_none() {
+		renderer.register_bind_group_layout::<Uniform<CameraUniform>>();
+	}
+	for binds in [get_equirectangular_binds(), get_prefilter_binds(), get_skybox_binds()].iter() {
+		if renderer.get_pipeline_layout(binds).is_none() {
+			renderer.register_pipeline_layout(binds);
+		}
+	}
+}
\ No newline at end of file
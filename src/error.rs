@@ -0,0 +1,57 @@
+use std::fmt::{Display, Formatter, Result};
+use std::error::Error;
+use std::io;
+
+#[derive(Debug)]
+pub enum LoadError {
+	Io(io::Error),
+	Parse { line: usize, message: String },
+	Invalid(String),
+	Gltf(gltf::Error),
+}
+
+impl LoadError {
+	pub(crate) fn parse(line: usize, message: &str) -> LoadError {
+		LoadError::Parse {
+			line,
+			message: String::from(message),
+		}
+	}
+
+	pub(crate) fn invalid(message: &str) -> LoadError {
+		LoadError::Invalid(String::from(message))
+	}
+}
+
+impl Display for LoadError {
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		match self {
+			LoadError::Io(error) => write!(f, "io_error: {}", error),
+			LoadError::Parse { line, message } => write!(f, "parse_error at line {}: {}", line, message),
+			LoadError::Invalid(message) => write!(f, "invalid_data: {}", message),
+			LoadError::Gltf(error) => write!(f, "gltf_error: {}", error),
+		}
+	}
+}
+
+impl Error for LoadError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			LoadError::Io(error) => Some(error),
+			LoadError::Gltf(error) => Some(error),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for LoadError {
+	fn from(error: io::Error) -> LoadError {
+		LoadError::Io(error)
+	}
+}
+
+impl From<gltf::Error> for LoadError {
+	fn from(error: gltf::Error) -> LoadError {
+		LoadError::Gltf(error)
+	}
+}
@@ -0,0 +1,87 @@
+use crate::{Aabb, BoundingSphere};
+use cgmath::{Matrix4, Vector3, Vector4, InnerSpace};
+
+pub trait CullableTrait {
+	fn get_bounding_box(&self) -> Aabb;
+	fn get_transform(&self) -> Matrix4<f32>;
+}
+
+impl CullableTrait for (Aabb, Matrix4<f32>) {
+	fn get_bounding_box(&self) -> Aabb {
+		self.0
+	}
+
+	fn get_transform(&self) -> Matrix4<f32> {
+		self.1
+	}
+}
+
+pub struct CullResult<'a, T> {
+	pub visible: Vec<&'a T>,
+	pub visible_count: usize,
+	pub culled_count: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+	planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+	pub fn from_matrix(view_projection: &Matrix4<f32>) -> Frustum {
+		let m = view_projection;
+		let row = |i: usize| Vector4::new(m.x[i], m.y[i], m.z[i], m.w[i]);
+		let planes = [
+			row(3) + row(0),
+			row(3) - row(0),
+			row(3) + row(1),
+			row(3) - row(1),
+			row(2),
+			row(3) - row(2),
+		];
+		let mut normalized = [Vector4::new(0.0, 0.0, 0.0, 0.0); 6];
+		for (plane, result) in planes.iter().zip(normalized.iter_mut()) {
+			let length = plane.truncate().magnitude();
+			*result = if length > 0.0 { plane / length } else { *plane };
+		}
+
+		Frustum {
+			planes: normalized,
+		}
+	}
+
+	pub fn get_planes(&self) -> &[Vector4<f32>; 6] {
+		&self.planes
+	}
+
+	pub fn contains_point(&self, point: Vector3<f32>) -> bool {
+		self.planes.iter().all(|plane| plane.truncate().dot(point) + plane.w >= 0.0)
+	}
+
+	pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+		self.planes.iter().all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+	}
+
+	pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+		self.planes.iter().all(|plane| {
+			let positive = Vector3::new(
+				if plane.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+				if plane.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+				if plane.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+			);
+			plane.truncate().dot(positive) + plane.w >= 0.0
+		})
+	}
+
+	pub fn cull<'a, T: CullableTrait>(&self, items: &'a [T]) -> CullResult<'a, T> {
+		let visible: Vec<&'a T> = items.iter()
+			.filter(|item| self.intersects_aabb(&item.get_bounding_box().transform(&item.get_transform())))
+			.collect();
+
+		CullResult {
+			visible_count: visible.len(),
+			culled_count: items.len() - visible.len(),
+			visible,
+		}
+	}
+}
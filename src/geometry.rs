@@ -0,0 +1,78 @@
+use crate::AttributeTrait;
+use std::collections::HashMap;
+
+pub(crate) fn weld<V: AttributeTrait>(vertices: &[V], indices: &mut [u32], tolerance: f32) -> Vec<V> {
+	let cell_size = tolerance.max(1e-6);
+	let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+	let mut welded: Vec<V> = Vec::with_capacity(vertices.len());
+	let mut remap: Vec<u32> = Vec::with_capacity(vertices.len());
+	for vertex in vertices {
+		let position = vertex.get_position();
+		let cell = [
+			(position[0] / cell_size).floor() as i64,
+			(position[1] / cell_size).floor() as i64,
+			(position[2] / cell_size).floor() as i64,
+		];
+		let mut found = None;
+		'search: for x in -1..=1 {
+			for y in -1..=1 {
+				for z in -1..=1 {
+					if let Some(candidates) = cells.get(&[cell[0] + x, cell[1] + y, cell[2] + z]) {
+						for candidate in candidates {
+							if is_close(&welded[*candidate as usize], vertex, tolerance) {
+								found = Some(*candidate);
+								break 'search;
+							}
+						}
+					}
+				}
+			}
+		}
+		match found {
+			Some(index) => remap.push(index),
+			None => {
+				let index = welded.len() as u32;
+				welded.push(*vertex);
+				cells.entry(cell).or_default().push(index);
+				remap.push(index);
+			}
+		}
+	}
+	for index in indices.iter_mut() {
+		*index = remap[*index as usize];
+	}
+	welded
+}
+
+fn is_close<V: AttributeTrait>(a: &V, b: &V, tolerance: f32) -> bool {
+	slice_close(&a.get_position(), &b.get_position(), tolerance)
+		&& option_close(a.get_normal(), b.get_normal(), tolerance)
+		&& option_close(a.get_uv(), b.get_uv(), tolerance)
+		&& option_close(a.get_color(), b.get_color(), tolerance)
+		&& option_close(a.get_tangent(), b.get_tangent(), tolerance)
+		&& a.get_joints() == b.get_joints()
+		&& option_close(a.get_weights(), b.get_weights(), tolerance)
+}
+
+fn option_close<T: AsRef<[f32]>>(a: Option<T>, b: Option<T>, tolerance: f32) -> bool {
+	match (a, b) {
+		(Some(a), Some(b)) => slice_close(a.as_ref(), b.as_ref(), tolerance),
+		_ => true,
+	}
+}
+
+fn slice_close(a: &[f32], b: &[f32], tolerance: f32) -> bool {
+	a.iter().zip(b).all(|(a, b)| (a - b).abs() <= tolerance)
+}
+
+pub(crate) fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+	let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+	let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+	let normal = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+	let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+	if length > 0.0 {
+		[normal[0] / length, normal[1] / length, normal[2] / length]
+	} else {
+		[0.0; 3]
+	}
+}
@@ -0,0 +1,476 @@
+use crate::{AttributeTrait, Mesh, Renderer, Texture, TextureOptions, SamplerOptions, LoadError, Skeleton, AnimationClip, Transform};
+use crate::skeleton::Joint;
+use crate::morph::MorphTarget;
+use crate::animation::{Channel, ChannelValues, Interpolation};
+use ::gltf::{Gltf, Document, Error};
+use ::gltf::animation::Property;
+use ::gltf::animation::util::ReadOutputs;
+use cgmath::{Matrix4, Quaternion, Vector3, SquareMatrix};
+use ::gltf::image::Source;
+use ::gltf::mesh::Mode;
+use ::gltf::texture::{MagFilter, MinFilter, WrappingMode};
+use wgpu::{AddressMode, FilterMode};
+use std::path::Path;
+use std::fs;
+
+pub struct GltfScene<V: AttributeTrait> {
+	pub meshes: Vec<GltfMesh<V>>,
+	pub textures: Vec<Texture>,
+	pub materials: Vec<GltfMaterial>,
+	pub nodes: Vec<GltfNode>,
+	pub roots: Vec<usize>,
+	pub skins: Vec<GltfSkin>,
+	pub animations: Vec<AnimationClip>,
+}
+
+pub struct GltfMesh<V: AttributeTrait> {
+	pub name: Option<String>,
+	pub primitives: Vec<GltfPrimitive<V>>,
+	pub weights: Vec<f32>,
+}
+
+pub struct GltfPrimitive<V: AttributeTrait> {
+	pub mesh: Mesh<V, u32>,
+	pub index_count: u32,
+	pub material: Option<usize>,
+	pub vertex_count: u32,
+	pub targets: Vec<MorphTarget>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfNode {
+	pub name: Option<String>,
+	pub translation: [f32; 3],
+	pub rotation: [f32; 4],
+	pub scale: [f32; 3],
+	pub matrix: [[f32; 4]; 4],
+	pub mesh: Option<usize>,
+	pub skin: Option<usize>,
+	pub parent: Option<usize>,
+	pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfSkin {
+	pub name: Option<String>,
+	pub skeleton: Skeleton,
+	pub joints: Vec<usize>,
+	pub animations: Vec<AnimationClip>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GltfAlphaMode {
+	Opaque,
+	Mask,
+	Blend,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfMaterial {
+	pub name: Option<String>,
+	pub base_color_factor: [f32; 4],
+	pub base_color_texture: Option<usize>,
+	pub metallic_factor: f32,
+	pub roughness_factor: f32,
+	pub metallic_roughness_texture: Option<usize>,
+	pub normal_texture: Option<usize>,
+	pub normal_scale: f32,
+	pub occlusion_texture: Option<usize>,
+	pub occlusion_strength: f32,
+	pub emissive_factor: [f32; 3],
+	pub emissive_texture: Option<usize>,
+	pub alpha_mode: GltfAlphaMode,
+	pub alpha_cutoff: f32,
+	pub double_sided: bool,
+}
+
+pub fn import_gltf<V: AttributeTrait, P: AsRef<Path>>(renderer: &Renderer, path: P) -> Result<GltfScene<V>, LoadError> {
+	let path = path.as_ref();
+	let data = fs::read(path)?;
+	import(renderer, &data, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+pub fn import_gltf_slice<V: AttributeTrait>(renderer: &Renderer, data: &[u8]) -> Result<GltfScene<V>, LoadError> {
+	import(renderer, data, Path::new(""))
+}
+
+fn import<V: AttributeTrait>(renderer: &Renderer, data: &[u8], base: &Path) -> Result<GltfScene<V>, LoadError> {
+	let Gltf { document, blob } = Gltf::from_slice(data)?;
+	let buffers = import_buffers(&document, blob, base)?;
+	validate_accessors(&document, &buffers)?;
+	let textures = import_textures(renderer, &document, &buffers, base)?;
+	let materials = document.materials().map(import_material).collect();
+	let mut meshes = Vec::new();
+	for mesh in document.meshes() {
+		let mut primitives = Vec::new();
+		for primitive in mesh.primitives() {
+			let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+			let positions = reader.read_positions().ok_or_else(|| LoadError::invalid("missing_positions"))?;
+			if primitive.attributes().any(|(_, accessor)| accessor.count() != positions.len()) {
+				return Err(LoadError::invalid("attribute_count_mismatch"));
+			}
+			let mut vertices: Vec<V> = positions.map(|position| {
+				let mut vertex = V::zeroed();
+				vertex.set_position(position);
+				vertex.set_color([1.0; 4]);
+				vertex
+			}).collect();
+			if let Some(normals) = reader.read_normals() {
+				vertices.iter_mut().zip(normals).for_each(|(vertex, normal)| vertex.set_normal(normal));
+			}
+			if let Some(tangents) = reader.read_tangents() {
+				vertices.iter_mut().zip(tangents).for_each(|(vertex, tangent)| vertex.set_tangent(tangent));
+			}
+			if let Some(uvs) = reader.read_tex_coords(0) {
+				vertices.iter_mut().zip(uvs.into_f32()).for_each(|(vertex, uv)| vertex.set_uv([uv[0], 1.0 - uv[1]]));
+			}
+			if let Some(colors) = reader.read_colors(0) {
+				vertices.iter_mut().zip(colors.into_rgba_f32()).for_each(|(vertex, color)| vertex.set_color(color));
+			}
+			if let Some(joints) = reader.read_joints(0) {
+				vertices.iter_mut().zip(joints.into_u16()).for_each(|(vertex, joints)| vertex.set_joints(joints));
+			}
+			if let Some(weights) = reader.read_weights(0) {
+				vertices.iter_mut().zip(weights.into_f32()).for_each(|(vertex, weights)| vertex.set_weights(weights));
+			}
+			let indices: Vec<u32> = match reader.read_indices() {
+				Some(indices) => indices.into_u32().collect(),
+				None => (0..vertices.len() as u32).collect(),
+			};
+			if indices.iter().any(|index| *index as usize >= vertices.len()) {
+				return Err(LoadError::invalid("index_out_of_range"));
+			}
+			let indices = triangulate(primitive.mode(), indices)?;
+			let targets = reader.read_morph_targets().map(|(positions, normals, _)| {
+				MorphTarget::new(
+					None,
+					positions.map_or_else(|| vec![[0.0; 3]; vertices.len()], |positions| positions.collect()),
+					normals.map(|normals| normals.collect()),
+				)
+			}).collect();
+			primitives.push(GltfPrimitive {
+				mesh: Mesh::new(renderer, &vertices, &indices),
+				index_count: indices.len() as u32,
+				material: primitive.material().index(),
+				vertex_count: vertices.len() as u32,
+				targets,
+			});
+		}
+		meshes.push(GltfMesh {
+			name: mesh.name().map(String::from),
+			primitives,
+			weights: mesh.weights().map(Vec::from).unwrap_or_default(),
+		});
+	}
+	let mut nodes: Vec<GltfNode> = document.nodes().map(|node| {
+		let (translation, rotation, scale) = node.transform().decomposed();
+		GltfNode {
+			name: node.name().map(String::from),
+			translation,
+			rotation,
+			scale,
+			matrix: node.transform().matrix(),
+			mesh: node.mesh().map(|mesh| mesh.index()),
+			skin: node.skin().map(|skin| skin.index()),
+			parent: None,
+			children: node.children().map(|child| child.index()).collect(),
+		}
+	}).collect();
+	for index in 0..nodes.len() {
+		for child in nodes[index].children.clone() {
+			nodes[child].parent = Some(index);
+		}
+	}
+	let roots = match document.default_scene().or_else(|| document.scenes().next()) {
+		Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+		None => (0..nodes.len()).filter(|index| nodes[*index].parent.is_none()).collect(),
+	};
+	let animations: Vec<AnimationClip> = document.animations().map(|animation| import_animation(animation, &buffers)).collect::<Result<_, _>>()?;
+	let skins = document.skins().map(|skin| import_skin(skin, &nodes, &buffers, &animations)).collect();
+
+	Ok(GltfScene {
+		meshes,
+		textures,
+		materials,
+		nodes,
+		roots,
+		skins,
+		animations,
+	})
+}
+
+fn import_skin(skin: ::gltf::Skin, nodes: &[GltfNode], buffers: &[Vec<u8>], animations: &[AnimationClip]) -> GltfSkin {
+	let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+	let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+	let mut inverse_bind_matrices: Vec<Matrix4<f32>> = match reader.read_inverse_bind_matrices() {
+		Some(matrices) => matrices.map(Matrix4::from).collect(),
+		None => Vec::new(),
+	};
+	inverse_bind_matrices.resize(joints.len(), Matrix4::identity());
+	let skeleton = Skeleton::new(joints.iter().zip(inverse_bind_matrices).map(|(node, inverse_bind_matrix)| {
+		let node = &nodes[*node];
+		let mut parent = None;
+		let mut parent_matrix = Matrix4::identity();
+		let mut ancestor = node.parent;
+		for _ in 0..nodes.len() {
+			let index = match ancestor {
+				Some(index) => index,
+				None => break,
+			};
+			if let Some(joint) = joints.iter().position(|joint| *joint == index) {
+				parent = Some(joint);
+				break;
+			}
+			parent_matrix = Matrix4::from(nodes[index].matrix) * parent_matrix;
+			ancestor = nodes[index].parent;
+		}
+		let mut joint = Joint::new(
+			node.name.clone(),
+			parent,
+			Transform::new(
+				Vector3::from(node.translation),
+				Quaternion::new(node.rotation[3], node.rotation[0], node.rotation[1], node.rotation[2]),
+				Vector3::from(node.scale),
+			),
+			inverse_bind_matrix,
+		);
+		joint.parent_matrix = parent_matrix;
+		joint
+	}).collect());
+
+	GltfSkin {
+		name: skin.name().map(String::from),
+		skeleton,
+		animations: animations.iter().map(|animation| animation.retarget(&joints)).collect(),
+		joints,
+	}
+}
+
+fn import_animation(animation: ::gltf::Animation, buffers: &[Vec<u8>]) -> Result<AnimationClip, LoadError> {
+	let mut channels = Vec::new();
+	for channel in animation.channels() {
+		if channel.target().property() == Property::MorphTargetWeights {
+			continue;
+		}
+		let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+		let times: Vec<f32> = reader.read_inputs().ok_or_else(|| LoadError::invalid("missing_animation_inputs"))?.collect();
+		let values = match reader.read_outputs().ok_or_else(|| LoadError::invalid("missing_animation_outputs"))? {
+			ReadOutputs::Translations(values) => ChannelValues::Translation(values.map(Vector3::from).collect()),
+			ReadOutputs::Rotations(values) => ChannelValues::Rotation(values.into_f32().map(|value| Quaternion::new(value[3], value[0], value[1], value[2])).collect()),
+			ReadOutputs::Scales(values) => ChannelValues::Scale(values.map(Vector3::from).collect()),
+			ReadOutputs::MorphTargetWeights(_) => continue,
+		};
+		channels.push(Channel {
+			target: channel.target().node().index(),
+			interpolation: match channel.sampler().interpolation() {
+				::gltf::animation::Interpolation::Step => Interpolation::Step,
+				::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+				::gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+			},
+			times,
+			values,
+		});
+	}
+	Ok(AnimationClip::new(animation.name().map(String::from), channels))
+}
+
+fn import_textures(renderer: &Renderer, document: &Document, buffers: &[Vec<u8>], base: &Path) -> Result<Vec<Texture>, LoadError> {
+	let image_count = document.images().count();
+	let mut color = vec![false; image_count];
+	let mut linear = vec![false; image_count];
+	for material in document.materials() {
+		let pbr = material.pbr_metallic_roughness();
+		for texture in [pbr.base_color_texture().map(|info| info.texture()), material.emissive_texture().map(|info| info.texture())].iter().flatten() {
+			color[texture.source().index()] = true;
+		}
+		for texture in [
+			pbr.metallic_roughness_texture().map(|info| info.texture()),
+			material.normal_texture().map(|info| info.texture()),
+			material.occlusion_texture().map(|info| info.texture()),
+		].iter().flatten() {
+			linear[texture.source().index()] = true;
+		}
+	}
+	let mut samplers = vec![None; image_count];
+	for texture in document.textures() {
+		let sampler = &mut samplers[texture.source().index()];
+		if sampler.is_none() {
+			*sampler = Some(get_sampler_options(&texture.sampler()));
+		}
+	}
+	let mut textures = Vec::new();
+	for image in document.images() {
+		let data = match image.source() {
+			Source::View { view, .. } => {
+				let buffer = &buffers[view.buffer().index()];
+				buffer.get(view.offset()..view.offset() + view.length()).ok_or_else(|| LoadError::invalid("buffer_view_out_of_range"))?.to_vec()
+			}
+			Source::Uri { uri, .. } => read_uri(uri, base)?,
+		};
+		let options = TextureOptions {
+			srgb: !linear[image.index()] || color[image.index()],
+			sampler: samplers[image.index()].unwrap_or_else(SamplerOptions::repeat),
+			..TextureOptions::default()
+		};
+		let image = image::load_from_memory(&data).map_err(|_| LoadError::invalid("image_reading_failed"))?.to_rgba8();
+		textures.push(Texture::from_rgba_with_options(renderer, image.width(), image.height(), &image.into_raw(), &options));
+	}
+	Ok(textures)
+}
+
+fn get_sampler_options(sampler: &::gltf::texture::Sampler) -> SamplerOptions {
+	let address_mode = |mode| match mode {
+		WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+		WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+		WrappingMode::Repeat => AddressMode::Repeat,
+	};
+	let (min_filter, mipmap_filter) = match sampler.min_filter() {
+		Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => (FilterMode::Nearest, FilterMode::Nearest),
+		Some(MinFilter::NearestMipmapLinear) => (FilterMode::Nearest, FilterMode::Linear),
+		Some(MinFilter::Linear) | Some(MinFilter::LinearMipmapNearest) => (FilterMode::Linear, FilterMode::Nearest),
+		Some(MinFilter::LinearMipmapLinear) | None => (FilterMode::Linear, FilterMode::Linear),
+	};
+	SamplerOptions {
+		address_mode_u: address_mode(sampler.wrap_s()),
+		address_mode_v: address_mode(sampler.wrap_t()),
+		mag_filter: if sampler.mag_filter() == Some(MagFilter::Nearest) { FilterMode::Nearest } else { FilterMode::Linear },
+		min_filter,
+		mipmap_filter,
+		..SamplerOptions::default()
+	}
+}
+
+fn import_buffers(document: &Document, mut blob: Option<Vec<u8>>, base: &Path) -> Result<Vec<Vec<u8>>, LoadError> {
+	let mut buffers = Vec::new();
+	for buffer in document.buffers() {
+		let data = match buffer.source() {
+			::gltf::buffer::Source::Bin => blob.take().ok_or(Error::MissingBlob)?,
+			::gltf::buffer::Source::Uri(uri) => read_uri(uri, base)?,
+		};
+		if data.len() < buffer.length() {
+			return Err(LoadError::Gltf(Error::BufferLength {
+				buffer: buffer.index(),
+				expected: buffer.length(),
+				actual: data.len(),
+			}));
+		}
+		buffers.push(data);
+	}
+	Ok(buffers)
+}
+
+fn validate_accessors(document: &Document, buffers: &[Vec<u8>]) -> Result<(), LoadError> {
+	for view in document.views() {
+		let end = view.offset().checked_add(view.length());
+		if end.is_none_or(|end| end > buffers[view.buffer().index()].len()) {
+			return Err(LoadError::invalid("buffer_view_out_of_range"));
+		}
+	}
+	for accessor in document.accessors() {
+		if let Some(view) = accessor.view() {
+			let stride = view.stride().unwrap_or_else(|| accessor.size());
+			validate_range(view.length(), accessor.offset(), stride, accessor.count(), accessor.size())?;
+		}
+		if let Some(sparse) = accessor.sparse() {
+			let (indices, values) = (sparse.indices(), sparse.values());
+			let index_size = indices.index_type().size();
+			validate_range(indices.view().length(), indices.offset() as usize, index_size, sparse.count() as usize, index_size)?;
+			validate_range(values.view().length(), values.offset() as usize, accessor.size(), sparse.count() as usize, accessor.size())?;
+		}
+	}
+	Ok(())
+}
+
+fn validate_range(length: usize, offset: usize, stride: usize, count: usize, size: usize) -> Result<(), LoadError> {
+	if count == 0 {
+		return Ok(());
+	}
+	let end = stride.checked_mul(count - 1).and_then(|span| span.checked_add(offset)).and_then(|start| start.checked_add(size));
+	if end.is_none_or(|end| end > length) {
+		return Err(LoadError::invalid("accessor_out_of_range"));
+	}
+	Ok(())
+}
+
+fn read_uri(uri: &str, base: &Path) -> Result<Vec<u8>, LoadError> {
+	if uri.starts_with("data:") {
+		let position = uri.find(";base64,").ok_or(Error::UnsupportedScheme)?;
+		Ok(base64::decode(&uri[position + ";base64,".len()..]).map_err(Error::Base64)?)
+	} else if uri.starts_with("file:") {
+		Ok(fs::read(decode_uri(uri.trim_start_matches("file://").trim_start_matches("file:"))?)?)
+	} else if uri.contains(':') {
+		Err(LoadError::Gltf(Error::UnsupportedScheme))
+	} else {
+		Ok(fs::read(base.join(decode_uri(uri)?))?)
+	}
+}
+
+fn decode_uri(uri: &str) -> Result<String, LoadError> {
+	let bytes = uri.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut index = 0;
+	while index < bytes.len() {
+		if bytes[index] == b'%' {
+			let digit = |offset: usize| bytes.get(index + offset).and_then(|byte| (*byte as char).to_digit(16));
+			match (digit(1), digit(2)) {
+				(Some(high), Some(low)) => decoded.push((high * 16 + low) as u8),
+				_ => return Err(LoadError::invalid("invalid_uri_escape")),
+			}
+			index += 3;
+		} else {
+			decoded.push(bytes[index]);
+			index += 1;
+		}
+	}
+	String::from_utf8(decoded).map_err(|_| LoadError::invalid("invalid_uri_encoding"))
+}
+
+fn import_material(material: ::gltf::Material) -> GltfMaterial {
+	let pbr = material.pbr_metallic_roughness();
+	GltfMaterial {
+		name: material.name().map(String::from),
+		base_color_factor: pbr.base_color_factor(),
+		base_color_texture: pbr.base_color_texture().map(|info| info.texture().source().index()),
+		metallic_factor: pbr.metallic_factor(),
+		roughness_factor: pbr.roughness_factor(),
+		metallic_roughness_texture: pbr.metallic_roughness_texture().map(|info| info.texture().source().index()),
+		normal_texture: material.normal_texture().map(|info| info.texture().source().index()),
+		normal_scale: material.normal_texture().map_or(1.0, |info| info.scale()),
+		occlusion_texture: material.occlusion_texture().map(|info| info.texture().source().index()),
+		occlusion_strength: material.occlusion_texture().map_or(1.0, |info| info.strength()),
+		emissive_factor: material.emissive_factor(),
+		emissive_texture: material.emissive_texture().map(|info| info.texture().source().index()),
+		alpha_mode: match material.alpha_mode() {
+			::gltf::material::AlphaMode::Opaque => GltfAlphaMode::Opaque,
+			::gltf::material::AlphaMode::Mask => GltfAlphaMode::Mask,
+			::gltf::material::AlphaMode::Blend => GltfAlphaMode::Blend,
+		},
+		alpha_cutoff: material.alpha_cutoff(),
+		double_sided: material.double_sided(),
+	}
+}
+
+fn triangulate(mode: Mode, indices: Vec<u32>) -> Result<Vec<u32>, LoadError> {
+	match mode {
+		Mode::Triangles => Ok(indices),
+		Mode::TriangleStrip => {
+			let mut triangles = Vec::new();
+			for i in 2..indices.len() {
+				if i % 2 == 0 {
+					triangles.extend_from_slice(&[indices[i - 2], indices[i - 1], indices[i]]);
+				} else {
+					triangles.extend_from_slice(&[indices[i - 1], indices[i - 2], indices[i]]);
+				}
+			}
+			Ok(triangles)
+		}
+		Mode::TriangleFan => {
+			let mut triangles = Vec::new();
+			for i in 2..indices.len() {
+				triangles.extend_from_slice(&[indices[0], indices[i - 1], indices[i]]);
+			}
+			Ok(triangles)
+		}
+		_ => Err(LoadError::invalid("unsupported_primitive_mode")),
+	}
+}
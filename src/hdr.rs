@@ -0,0 +1,309 @@
+use crate::LoadError;
+use crate::quantize::half_to_f32;
+use image::codecs::hdr::HdrDecoder;
+use std::convert::TryInto;
+
+const EXR_MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const RADIANCE_MAGIC: &[u8] = b"#?";
+const EXR_TILED_FLAG: u32 = 0x200;
+const EXR_UNSUPPORTED_FLAGS: u32 = 0x1800;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HdrImage {
+	pub width: u32,
+	pub height: u32,
+	pub pixels: Vec<[f32; 4]>,
+}
+
+impl HdrImage {
+	pub fn get_pixel(&self, x: u32, y: u32) -> [f32; 4] {
+		self.pixels[(y * self.width + x) as usize]
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExrCompression {
+	None,
+	Rle,
+	Zips,
+	Zip,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExrPixelType {
+	Uint,
+	Half,
+	Float,
+}
+
+impl ExrPixelType {
+	fn get_size(self) -> usize {
+		match self {
+			ExrPixelType::Half => 2,
+			_ => 4,
+		}
+	}
+}
+
+struct ExrChannel {
+	pixel_type: ExrPixelType,
+	target: Option<usize>,
+	luminance: bool,
+}
+
+pub fn is_hdr(data: &[u8]) -> bool {
+	data.starts_with(&EXR_MAGIC) || data.starts_with(RADIANCE_MAGIC)
+}
+
+pub fn read_hdr(data: &[u8]) -> Result<HdrImage, LoadError> {
+	if data.starts_with(&EXR_MAGIC) {
+		read_exr(data)
+	} else if data.starts_with(RADIANCE_MAGIC) {
+		read_radiance(data)
+	} else {
+		Err(LoadError::invalid("unknown_hdr_format"))
+	}
+}
+
+pub fn read_radiance(data: &[u8]) -> Result<HdrImage, LoadError> {
+	let decoder = HdrDecoder::new(data).map_err(|_| LoadError::invalid("radiance_header_invalid"))?;
+	let metadata = decoder.metadata();
+	let pixels = decoder.read_image_hdr().map_err(|_| LoadError::invalid("radiance_data_invalid"))?;
+
+	Ok(HdrImage {
+		width: metadata.width,
+		height: metadata.height,
+		pixels: pixels.into_iter().map(|pixel| [pixel[0], pixel[1], pixel[2], 1.0]).collect(),
+	})
+}
+
+pub fn read_exr(data: &[u8]) -> Result<HdrImage, LoadError> {
+	if !data.starts_with(&EXR_MAGIC) {
+		return Err(LoadError::invalid("exr_magic_missing"));
+	}
+	let flags = read_u32(data, 4)?;
+	if flags & 0xff != 2 {
+		return Err(LoadError::invalid("exr_version_unsupported"));
+	}
+	if flags & EXR_TILED_FLAG != 0 {
+		return Err(LoadError::invalid("exr_tiled_unsupported"));
+	}
+	if flags & EXR_UNSUPPORTED_FLAGS != 0 {
+		return Err(LoadError::invalid("exr_multipart_unsupported"));
+	}
+
+	let mut position = 8;
+	let mut channels = None;
+	let mut compression = None;
+	let mut data_window = None;
+	loop {
+		let name = read_string(data, &mut position)?;
+		if name.is_empty() {
+			break;
+		}
+		let kind = read_string(data, &mut position)?;
+		let size = read_i32(data, position)?;
+		if size < 0 {
+			return Err(LoadError::invalid("exr_attribute_size_invalid"));
+		}
+		position += 4;
+		let value = data.get(position..position + size as usize).ok_or_else(|| LoadError::invalid("exr_attribute_truncated"))?;
+		position += size as usize;
+		match (name.as_str(), kind.as_str()) {
+			("channels", "chlist") => channels = Some(parse_channels(value)?),
+			("compression", "compression") => compression = Some(parse_compression(value)?),
+			("dataWindow", "box2i") => data_window = Some([read_i32(value, 0)?, read_i32(value, 4)?, read_i32(value, 8)?, read_i32(value, 12)?]),
+			_ => (),
+		}
+	}
+	let channels = channels.ok_or_else(|| LoadError::invalid("exr_channels_missing"))?;
+	let compression = compression.ok_or_else(|| LoadError::invalid("exr_compression_missing"))?;
+	let data_window = data_window.ok_or_else(|| LoadError::invalid("exr_data_window_missing"))?;
+	if data_window[2] < data_window[0] || data_window[3] < data_window[1] {
+		return Err(LoadError::invalid("exr_data_window_invalid"));
+	}
+	let width = (data_window[2] - data_window[0] + 1) as usize;
+	let height = (data_window[3] - data_window[1] + 1) as usize;
+
+	let lines_per_block = if compression == ExrCompression::Zip { 16 } else { 1 };
+	let block_count = height.div_ceil(lines_per_block);
+	let line_size: usize = channels.iter().map(|channel| channel.pixel_type.get_size() * width).sum();
+	let has_alpha = channels.iter().any(|channel| channel.target == Some(3));
+	let is_gray = channels.iter().any(|channel| channel.luminance) && !channels.iter().any(|channel| !channel.luminance && matches!(channel.target, Some(target) if target < 3));
+	let mut pixels = vec![[0.0, 0.0, 0.0, 1.0]; width * height];
+
+	for block in 0..block_count {
+		let offset = read_u64(data, position + block * 8)? as usize;
+		let y = read_i32(data, offset)?;
+		let size = read_i32(data, offset + 4)?;
+		if size < 0 {
+			return Err(LoadError::invalid("exr_block_size_invalid"));
+		}
+		let packed = data.get(offset + 8..offset + 8 + size as usize).ok_or_else(|| LoadError::invalid("exr_block_truncated"))?;
+		let first_line = (y - data_window[1]) as usize;
+		if y < data_window[1] || first_line >= height {
+			return Err(LoadError::invalid("exr_block_line_invalid"));
+		}
+		let line_count = lines_per_block.min(height - first_line);
+		let expected = line_size * line_count;
+		let unpacked = decompress(packed, compression, expected)?;
+
+		let mut cursor = 0;
+		for line in first_line..first_line + line_count {
+			for channel in &channels {
+				let sample_size = channel.pixel_type.get_size();
+				for x in 0..width {
+					let sample = &unpacked[cursor + x * sample_size..cursor + (x + 1) * sample_size];
+					let value = match channel.pixel_type {
+						ExrPixelType::Half => half_to_f32(u16::from_le_bytes([sample[0], sample[1]])),
+						ExrPixelType::Float => f32::from_le_bytes(sample.try_into().expect("exr_sample_size")),
+						ExrPixelType::Uint => u32::from_le_bytes(sample.try_into().expect("exr_sample_size")) as f32,
+					};
+					if let Some(target) = channel.target {
+						pixels[line * width + x][target] = value;
+					}
+				}
+				cursor += sample_size * width;
+			}
+		}
+	}
+
+	if is_gray {
+		for pixel in &mut pixels {
+			let value = pixel[0];
+			pixel[1] = value;
+			pixel[2] = value;
+		}
+	}
+	if !has_alpha {
+		for pixel in &mut pixels {
+			pixel[3] = 1.0;
+		}
+	}
+
+	Ok(HdrImage {
+		width: width as u32,
+		height: height as u32,
+		pixels,
+	})
+}
+
+fn parse_channels(value: &[u8]) -> Result<Vec<ExrChannel>, LoadError> {
+	let mut channels = Vec::new();
+	let mut position = 0;
+	loop {
+		let name = read_string(value, &mut position)?;
+		if name.is_empty() {
+			break;
+		}
+		let pixel_type = match read_i32(value, position)? {
+			0 => ExrPixelType::Uint,
+			1 => ExrPixelType::Half,
+			2 => ExrPixelType::Float,
+			_ => return Err(LoadError::invalid("exr_pixel_type_unsupported")),
+		};
+		let x_sampling = read_i32(value, position + 8)?;
+		let y_sampling = read_i32(value, position + 12)?;
+		if x_sampling != 1 || y_sampling != 1 {
+			return Err(LoadError::invalid("exr_subsampling_unsupported"));
+		}
+		position += 16;
+		let short_name = name.rsplit('.').next().unwrap_or("");
+		let target = match short_name {
+			"R" | "r" | "Y" | "y" => Some(0),
+			"G" | "g" => Some(1),
+			"B" | "b" => Some(2),
+			"A" | "a" => Some(3),
+			_ => None,
+		};
+		channels.push(ExrChannel {
+			pixel_type,
+			target,
+			luminance: short_name == "Y" || short_name == "y",
+		});
+	}
+	if channels.is_empty() {
+		return Err(LoadError::invalid("exr_channels_empty"));
+	}
+	Ok(channels)
+}
+
+fn parse_compression(value: &[u8]) -> Result<ExrCompression, LoadError> {
+	match value.first() {
+		Some(0) => Ok(ExrCompression::None),
+		Some(1) => Ok(ExrCompression::Rle),
+		Some(2) => Ok(ExrCompression::Zips),
+		Some(3) => Ok(ExrCompression::Zip),
+		_ => Err(LoadError::invalid("exr_compression_unsupported")),
+	}
+}
+
+fn decompress(packed: &[u8], compression: ExrCompression, expected: usize) -> Result<Vec<u8>, LoadError> {
+	if compression == ExrCompression::None || packed.len() == expected {
+		if packed.len() != expected {
+			return Err(LoadError::invalid("exr_block_size_mismatch"));
+		}
+		return Ok(packed.to_vec());
+	}
+	let mut unpacked = match compression {
+		ExrCompression::Rle => decompress_rle(packed)?,
+		_ => miniz_oxide::inflate::decompress_to_vec_zlib(packed).map_err(|_| LoadError::invalid("exr_zip_invalid"))?,
+	};
+	if unpacked.len() != expected {
+		return Err(LoadError::invalid("exr_block_size_mismatch"));
+	}
+	for index in 1..unpacked.len() {
+		unpacked[index] = (unpacked[index - 1] as i32 + unpacked[index] as i32 - 128) as u8;
+	}
+	let half = unpacked.len().div_ceil(2);
+	let mut interleaved = Vec::with_capacity(unpacked.len());
+	for index in 0..half {
+		interleaved.push(unpacked[index]);
+		if let Some(&byte) = unpacked.get(half + index) {
+			interleaved.push(byte);
+		}
+	}
+	Ok(interleaved)
+}
+
+fn decompress_rle(packed: &[u8]) -> Result<Vec<u8>, LoadError> {
+	let mut unpacked = Vec::new();
+	let mut position = 0;
+	while position < packed.len() {
+		let count = packed[position] as i8;
+		position += 1;
+		if count < 0 {
+			let length = -(count as i32) as usize;
+			let run = packed.get(position..position + length).ok_or_else(|| LoadError::invalid("exr_rle_truncated"))?;
+			unpacked.extend_from_slice(run);
+			position += length;
+		} else {
+			let value = *packed.get(position).ok_or_else(|| LoadError::invalid("exr_rle_truncated"))?;
+			unpacked.resize(unpacked.len() + count as usize + 1, value);
+			position += 1;
+		}
+	}
+	Ok(unpacked)
+}
+
+fn read_string(data: &[u8], position: &mut usize) -> Result<String, LoadError> {
+	let rest = data.get(*position..).ok_or_else(|| LoadError::invalid("exr_header_truncated"))?;
+	let length = rest.iter().position(|&byte| byte == 0).ok_or_else(|| LoadError::invalid("exr_header_truncated"))?;
+	let value = String::from_utf8_lossy(&rest[..length]).into_owned();
+	*position += length + 1;
+	Ok(value)
+}
+
+fn read_u32(data: &[u8], position: usize) -> Result<u32, LoadError> {
+	let bytes = data.get(position..position + 4).ok_or_else(|| LoadError::invalid("exr_data_truncated"))?;
+	Ok(u32::from_le_bytes(bytes.try_into().expect("exr_u32_size")))
+}
+
+fn read_i32(data: &[u8], position: usize) -> Result<i32, LoadError> {
+	read_u32(data, position).map(|value| value as i32)
+}
+
+fn read_u64(data: &[u8], position: usize) -> Result<u64, LoadError> {
+	let bytes = data.get(position..position + 8).ok_or_else(|| LoadError::invalid("exr_data_truncated"))?;
+	Ok(u64::from_le_bytes(bytes.try_into().expect("exr_u64_size")))
+}
\ No newline at end of file
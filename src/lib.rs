@@ -6,16 +6,105 @@ pub mod mesh;
 pub mod texture;
 pub mod bind;
 pub mod uniform;
+pub mod error;
+pub mod obj;
+pub mod gltf;
+pub mod stl;
+pub mod ply;
+pub mod bounds;
+pub mod mesh_data;
+pub mod primitive;
+pub mod lod;
+pub mod optimize;
+pub mod quantize;
+pub mod transform;
+pub mod skeleton;
+pub mod animation;
+pub mod morph;
+pub mod frustum;
+pub mod camera;
+pub mod material;
+pub mod scene;
+pub mod pbr;
+pub mod light;
+pub mod shadow;
+pub mod postprocess;
+pub mod shader;
+pub mod hdr;
+pub mod bloom;
+pub mod cubemap;
+pub mod environment;
+pub mod mipmap;
+pub mod sampler;
+mod geometry;
+mod simplify;
+mod tangent;
 
 pub use renderer::Renderer;
+pub use renderer::DEPTH_FORMAT;
 pub use command::Command;
 pub use pipeline::Pipeline;
 pub use pipeline::PipelineTrait;
+pub use pipeline::PipelineOptions;
 pub use vertex::Vertex;
 pub use vertex::VertexTrait;
 pub use vertex::IndexTrait;
+pub use vertex::VertexLayoutTrait;
+pub use vertex::AttributeTrait;
+pub use vertex::PositionVertex;
+pub use vertex::CompactVertex;
+pub use vertex::CompactNormalVertex;
+pub use vertex::SkinnedVertex;
+pub use vertex::PbrVertex;
 pub use mesh::Mesh;
 pub use mesh::MeshTrait;
+pub use mesh::StreamMesh;
+pub use lod::LodMesh;
+pub use mesh_data::MeshData;
 pub use texture::Texture;
 pub use bind::Bind;
-pub use uniform::Uniform;
\ No newline at end of file
+pub use uniform::Uniform;
+pub use error::LoadError;
+pub use bounds::Aabb;
+pub use bounds::BoundingSphere;
+pub use frustum::Frustum;
+pub use frustum::CullableTrait;
+pub use camera::Camera;
+pub use camera::CameraUniform;
+pub use camera::ControllerTrait;
+pub use optimize::CacheStatistics;
+pub use transform::Transform;
+pub use skeleton::Skeleton;
+pub use skeleton::JointPalette;
+pub use animation::AnimationClip;
+pub use animation::AnimationSampler;
+pub use morph::MorphTarget;
+pub use morph::MorphMesh;
+pub use morph::MorphTargetBuffer;
+pub use material::Material;
+pub use material::MaterialTrait;
+pub use scene::Scene;
+pub use scene::NodeId;
+pub use scene::Renderable;
+pub use light::Light;
+pub use light::LightSet;
+pub use shadow::ShadowMap;
+pub use postprocess::PostProcess;
+pub use postprocess::PostEffect;
+pub use postprocess::RenderTarget;
+pub use pbr::PbrParameters;
+pub use pbr::PbrDefaults;
+pub use pbr::PbrFrame;
+pub use pbr::PbrTextures;
+pub use postprocess::Tonemapper;
+pub use pipeline::BlendMode;
+pub use bloom::Bloom;
+pub use cubemap::CubeMap;
+pub use environment::Environment;
+pub use environment::Skybox;
+pub use mipmap::MipFilter;
+pub use sampler::Sampler;
+pub use sampler::SamplerOptions;
+pub use texture::TextureOptions;
+pub use texture::PixelFormat;
+pub use hdr::HdrImage;
\ No newline at end of file
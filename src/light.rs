@@ -0,0 +1,176 @@
+use crate::{Bind, Renderer};
+use bytemuck::{bytes_of, Pod, Zeroable};
+use std::mem::size_of;
+use wgpu::*;
+
+pub const MAX_LIGHTS: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Light {
+	Directional {
+		direction: [f32; 3],
+		color: [f32; 3],
+		intensity: f32,
+		shadow: Option<u32>,
+	},
+	Point {
+		position: [f32; 3],
+		color: [f32; 3],
+		intensity: f32,
+		range: f32,
+	},
+	Spot {
+		position: [f32; 3],
+		direction: [f32; 3],
+		color: [f32; 3],
+		intensity: f32,
+		range: f32,
+		inner_angle: f32,
+		outer_angle: f32,
+		shadow: Option<u32>,
+	},
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct LightData {
+	pub position_type: [f32; 4],
+	pub direction_range: [f32; 4],
+	pub color_intensity: [f32; 4],
+	pub cone: [f32; 4],
+}
+
+unsafe impl Pod for LightData {}
+
+unsafe impl Zeroable for LightData {}
+
+impl Light {
+	pub fn to_data(&self) -> LightData {
+		match *self {
+			Light::Directional { direction, color, intensity, shadow } => LightData {
+				position_type: [0.0, 0.0, 0.0, 0.0],
+				direction_range: [direction[0], direction[1], direction[2], 0.0],
+				color_intensity: [color[0], color[1], color[2], intensity],
+				cone: [0.0, 0.0, get_shadow_layer(shadow), 0.0],
+			},
+			Light::Point { position, color, intensity, range } => LightData {
+				position_type: [position[0], position[1], position[2], 1.0],
+				direction_range: [0.0, 0.0, -1.0, range],
+				color_intensity: [color[0], color[1], color[2], intensity],
+				cone: [0.0, 0.0, -1.0, 0.0],
+			},
+			Light::Spot { position, direction, color, intensity, range, inner_angle, outer_angle, shadow } => LightData {
+				position_type: [position[0], position[1], position[2], 2.0],
+				direction_range: [direction[0], direction[1], direction[2], range],
+				color_intensity: [color[0], color[1], color[2], intensity],
+				cone: [inner_angle.min(outer_angle).cos(), outer_angle.cos(), get_shadow_layer(shadow), 0.0],
+			},
+		}
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct LightsUniform {
+	counts: [u32; 4],
+	ambient: [f32; 4],
+	lights: [LightData; MAX_LIGHTS],
+}
+
+unsafe impl Pod for LightsUniform {}
+
+unsafe impl Zeroable for LightsUniform {}
+
+pub struct LightSet {
+	buffer: Buffer,
+	bind_group: BindGroup,
+	light_count: u32,
+}
+
+impl LightSet {
+	pub fn new(renderer: &Renderer, lights: &[Light], ambient: [f32; 3]) -> LightSet {
+		let data = pack(lights, ambient);
+		let buffer = renderer
+			.get_device()
+			.create_buffer_with_data(bytes_of(&data), BufferUsage::UNIFORM | BufferUsage::COPY_DST);
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("light_set_type_not_registered"),
+			bindings: &[Binding {
+				binding: 0,
+				resource: BindingResource::Buffer {
+					buffer: &buffer,
+					range: 0..size_of::<LightsUniform>() as BufferAddress,
+				},
+			}],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		LightSet {
+			buffer,
+			bind_group,
+			light_count: lights.len() as u32,
+		}
+	}
+
+	pub fn update(&mut self, renderer: &Renderer, lights: &[Light], ambient: [f32; 3]) {
+		let data = pack(lights, ambient);
+		let data = bytes_of(&data);
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("light_update_encoder"),
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		let staging_buffer = renderer.get_device().create_buffer_with_data(data, BufferUsage::COPY_SRC);
+		encoder.copy_buffer_to_buffer(&staging_buffer, 0, &self.buffer, 0, data.len() as u64);
+		renderer.get_queue().submit(&[encoder.finish()]);
+		self.light_count = lights.len() as u32;
+	}
+
+	pub fn get_light_count(&self) -> u32 {
+		self.light_count
+	}
+
+	pub fn get_buffer(&self) -> &Buffer {
+		&self.buffer
+	}
+}
+
+impl Bind for LightSet {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[BindGroupLayoutEntry {
+				binding: 0,
+				visibility: ShaderStage::VERTEX | ShaderStage::FRAGMENT,
+				ty: BindingType::UniformBuffer { dynamic: false },
+			}],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+fn pack(lights: &[Light], ambient: [f32; 3]) -> LightsUniform {
+	if lights.len() > MAX_LIGHTS {
+		panic!("light_count_exceeded");
+	}
+	let mut data = LightsUniform {
+		counts: [lights.len() as u32, 0, 0, 0],
+		ambient: [ambient[0], ambient[1], ambient[2], 0.0],
+		lights: [LightData::zeroed(); MAX_LIGHTS],
+	};
+	for (slot, light) in data.lights.iter_mut().zip(lights) {
+		*slot = light.to_data();
+	}
+	data
+}
+
+fn get_shadow_layer(shadow: Option<u32>) -> f32 {
+	match shadow {
+		Some(layer) => layer as f32,
+		None => -1.0,
+	}
+}
@@ -0,0 +1,99 @@
+use crate::{Renderer, VertexTrait, IndexTrait, AttributeTrait, Mesh, MeshTrait, MeshData, BoundingSphere};
+use cgmath::Vector3;
+use wgpu::Buffer;
+use std::ops::Range;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LodLevel {
+	pub range: Range<u32>,
+	pub error: f32,
+}
+
+pub struct LodMesh<V: VertexTrait, I: IndexTrait> {
+	mesh: Mesh<V, I>,
+	levels: Vec<LodLevel>,
+	bounding_sphere: BoundingSphere,
+}
+
+impl<V: AttributeTrait, I: IndexTrait> LodMesh<V, I> {
+	pub fn new(renderer: &Renderer, data: &MeshData<V, I>, ratios: &[f32], target_error: f32) -> LodMesh<V, I> {
+		let mut indices = data.indices.clone();
+		let mut levels = vec![LodLevel {
+			range: 0..indices.len() as u32,
+			error: 0.0,
+		}];
+		let triangle_count = data.get_triangle_count();
+		for ratio in ratios {
+			let target = (triangle_count as f32 * ratio.clamp(0.0, 1.0)) as usize;
+			let (level, error) = data.simplify(target, target_error);
+			let previous = levels.last().expect("missing_lod_level");
+			if level.indices.len() as u32 >= previous.range.end - previous.range.start {
+				continue;
+			}
+			let start = indices.len() as u32;
+			indices.extend_from_slice(&level.indices);
+			levels.push(LodLevel {
+				range: start..indices.len() as u32,
+				error: error.max(previous.error),
+			});
+		}
+
+		let mut mesh = Mesh::new(renderer, &data.vertices, &indices);
+		mesh.set_bounding_box(data.compute_bounding_box());
+
+		LodMesh {
+			mesh,
+			levels,
+			bounding_sphere: data.compute_bounding_sphere().unwrap_or_else(|| BoundingSphere::new(Vector3::new(0.0, 0.0, 0.0), 0.0)),
+		}
+	}
+}
+
+impl<V: VertexTrait, I: IndexTrait> LodMesh<V, I> {
+	pub fn select_level(&self, screen_size: f32, pixel_error: f32) -> usize {
+		let mut selected = 0;
+		for (index, level) in self.levels.iter().enumerate() {
+			if level.error * screen_size * 0.5 <= pixel_error {
+				selected = index;
+			}
+		}
+		selected
+	}
+
+	pub fn get_level(&self, level: usize) -> &LodLevel {
+		&self.levels[level]
+	}
+
+	pub fn get_level_range(&self, level: usize) -> Range<u32> {
+		self.levels[level].range.clone()
+	}
+
+	pub fn get_level_count(&self) -> usize {
+		self.levels.len()
+	}
+
+	pub fn get_bounding_sphere(&self) -> &BoundingSphere {
+		&self.bounding_sphere
+	}
+
+	pub fn get_mesh(&self) -> &Mesh<V, I> {
+		&self.mesh
+	}
+}
+
+impl<V: VertexTrait, I: IndexTrait> MeshTrait for LodMesh<V, I> {
+	fn get_vertex_buffers(&self) -> Vec<&Buffer> {
+		self.mesh.get_vertex_buffers()
+	}
+
+	fn get_index_buffer(&self) -> &Buffer {
+		self.mesh.get_index_buffer()
+	}
+}
+
+pub fn get_screen_size(radius: f32, distance: f32, fov_y: f32, viewport_height: f32) -> f32 {
+	if distance <= radius {
+		return f32::INFINITY;
+	}
+	radius / (distance * (fov_y * 0.5).tan()) * viewport_height
+}
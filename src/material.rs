@@ -0,0 +1,70 @@
+use crate::{Bind, PipelineTrait, Renderer, Uniform};
+use bytemuck::Pod;
+use std::rc::Rc;
+
+pub trait MaterialTrait {
+	fn get_pipeline(&self) -> &dyn PipelineTrait;
+	fn get_binds(&self) -> Vec<(&dyn Bind, u32)>;
+}
+
+pub struct Material<T: Pod> {
+	pipeline: Rc<dyn PipelineTrait>,
+	binds: Vec<(Rc<dyn Bind>, u32)>,
+	parameters: T,
+	parameter_uniform: Uniform<T>,
+	parameter_slot: u32,
+}
+
+impl<T: Pod> Material<T> {
+	pub fn new(renderer: &Renderer, pipeline: Rc<dyn PipelineTrait>, parameters: T, parameter_slot: u32) -> Material<T> {
+		Material {
+			pipeline,
+			binds: Vec::new(),
+			parameter_uniform: Uniform::new(renderer, &parameters),
+			parameters,
+			parameter_slot,
+		}
+	}
+
+	pub fn set_bind(&mut self, bind: Rc<dyn Bind>, slot: u32) {
+		if slot == self.parameter_slot {
+			panic!("material_slot_collision");
+		}
+		self.binds.retain(|(_, existing)| *existing != slot);
+		self.binds.push((bind, slot));
+	}
+
+	pub fn remove_bind(&mut self, slot: u32) {
+		self.binds.retain(|(_, existing)| *existing != slot);
+	}
+
+	pub fn get_parameters(&self) -> &T {
+		&self.parameters
+	}
+
+	pub fn set_parameters(&mut self, renderer: &Renderer, parameters: T) {
+		self.parameters = parameters;
+		self.parameter_uniform.update(renderer, &self.parameters);
+	}
+
+	pub fn update_parameters<F: FnOnce(&mut T)>(&mut self, renderer: &Renderer, update: F) {
+		update(&mut self.parameters);
+		self.parameter_uniform.update(renderer, &self.parameters);
+	}
+
+	pub fn get_parameter_slot(&self) -> u32 {
+		self.parameter_slot
+	}
+}
+
+impl<T: Pod> MaterialTrait for Material<T> {
+	fn get_pipeline(&self) -> &dyn PipelineTrait {
+		self.pipeline.as_ref()
+	}
+
+	fn get_binds(&self) -> Vec<(&dyn Bind, u32)> {
+		let mut binds: Vec<(&dyn Bind, u32)> = self.binds.iter().map(|(bind, slot)| (bind.as_ref(), *slot)).collect();
+		binds.push((&self.parameter_uniform, self.parameter_slot));
+		binds
+	}
+}
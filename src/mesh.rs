@@ -1,10 +1,11 @@
-use crate::{VertexTrait, Renderer, IndexTrait};
+use crate::{VertexTrait, Renderer, IndexTrait, AttributeTrait, MeshData, Aabb};
 use wgpu::*;
 use bytemuck::cast_slice;
 use std::marker::PhantomData;
+use std::any::TypeId;
 
 pub trait MeshTrait {
-	fn get_vertex_buffer(&self) -> &Buffer;
+	fn get_vertex_buffers(&self) -> Vec<&Buffer>;
 	fn get_index_buffer(&self) -> &Buffer;
 }
 
@@ -13,6 +14,7 @@ pub struct Mesh<V: VertexTrait, I: IndexTrait> {
 	index_buffer: Buffer,
 	vertex_buffer_size: usize,
 	index_buffer_size: usize,
+	bounding_box: Option<Aabb>,
 	vertex_marker: PhantomData<V>,
 	index_marker: PhantomData<I>,
 }
@@ -29,11 +31,20 @@ impl<V: VertexTrait, I: IndexTrait> Mesh<V, I> {
 			index_buffer,
 			vertex_buffer_size: vertex_data.len(),
 			index_buffer_size: index_data.len(),
+			bounding_box: None,
 			vertex_marker: PhantomData,
 			index_marker: PhantomData,
 		}
 	}
 
+	pub fn get_bounding_box(&self) -> Option<Aabb> {
+		self.bounding_box
+	}
+
+	pub fn set_bounding_box(&mut self, bounding_box: Option<Aabb>) {
+		self.bounding_box = bounding_box;
+	}
+
 	pub fn update_vertex(&mut self, renderer: &Renderer, vertices: &[V]) {
 		let data = cast_slice(vertices);
 		if data.len() <= self.vertex_buffer_size {
@@ -75,9 +86,91 @@ impl<V: VertexTrait, I: IndexTrait> Mesh<V, I> {
 	}
 }
 
+impl<V: AttributeTrait, I: IndexTrait> Mesh<V, I> {
+	pub fn from_data(renderer: &Renderer, data: &MeshData<V, I>) -> Mesh<V, I> {
+		let mut mesh = Mesh::new(renderer, &data.vertices, &data.indices);
+		mesh.bounding_box = data.compute_bounding_box();
+		mesh
+	}
+}
+
 impl<V: VertexTrait, I: IndexTrait> MeshTrait for Mesh<V, I> {
-	fn get_vertex_buffer(&self) -> &Buffer {
-		&self.vertex_buffer
+	fn get_vertex_buffers(&self) -> Vec<&Buffer> {
+		vec![&self.vertex_buffer]
+	}
+
+	fn get_index_buffer(&self) -> &Buffer {
+		&self.index_buffer
+	}
+}
+
+pub struct StreamMesh<I: IndexTrait> {
+	vertex_buffers: Vec<Buffer>,
+	vertex_buffer_sizes: Vec<usize>,
+	vertex_types: Vec<TypeId>,
+	index_buffer: Buffer,
+	index_buffer_size: usize,
+	index_marker: PhantomData<I>,
+}
+
+impl<I: IndexTrait> StreamMesh<I> {
+	pub fn new(renderer: &Renderer, indices: &[I]) -> StreamMesh<I> {
+		let index_data = cast_slice(indices);
+		let index_buffer = renderer.get_device().create_buffer_with_data(index_data, BufferUsage::INDEX | BufferUsage::COPY_DST);
+
+		StreamMesh {
+			vertex_buffers: Vec::new(),
+			vertex_buffer_sizes: Vec::new(),
+			vertex_types: Vec::new(),
+			index_buffer,
+			index_buffer_size: index_data.len(),
+			index_marker: PhantomData,
+		}
+	}
+
+	pub fn add_stream<V: 'static + VertexTrait>(&mut self, renderer: &Renderer, vertices: &[V]) -> u32 {
+		let data = cast_slice(vertices);
+		self.vertex_buffers.push(renderer.get_device().create_buffer_with_data(data, BufferUsage::VERTEX | BufferUsage::COPY_DST));
+		self.vertex_buffer_sizes.push(data.len());
+		self.vertex_types.push(TypeId::of::<V>());
+		(self.vertex_buffers.len() - 1) as u32
+	}
+
+	pub fn update_stream<V: 'static + VertexTrait>(&mut self, renderer: &Renderer, slot: u32, vertices: &[V]) {
+		let slot = slot as usize;
+		if slot >= self.vertex_buffers.len() {
+			panic!("stream_slot_out_of_range");
+		}
+		if self.vertex_types[slot] != TypeId::of::<V>() {
+			panic!("stream_type_mismatch");
+		}
+		let data = cast_slice(vertices);
+		if data.len() <= self.vertex_buffer_sizes[slot] {
+			copy_data_to_buffer(renderer, &self.vertex_buffers[slot], data);
+		} else {
+			self.vertex_buffer_sizes[slot] = data.len();
+			self.vertex_buffers[slot] = renderer.get_device().create_buffer_with_data(data, BufferUsage::VERTEX | BufferUsage::COPY_DST);
+		}
+	}
+
+	pub fn update_index(&mut self, renderer: &Renderer, indices: &[I]) {
+		let data = cast_slice(indices);
+		if data.len() <= self.index_buffer_size {
+			copy_data_to_buffer(renderer, &self.index_buffer, data);
+		} else {
+			self.index_buffer_size = data.len();
+			self.index_buffer = renderer.get_device().create_buffer_with_data(data, BufferUsage::INDEX | BufferUsage::COPY_DST);
+		}
+	}
+
+	pub fn get_stream_count(&self) -> u32 {
+		self.vertex_buffers.len() as u32
+	}
+}
+
+impl<I: IndexTrait> MeshTrait for StreamMesh<I> {
+	fn get_vertex_buffers(&self) -> Vec<&Buffer> {
+		self.vertex_buffers.iter().collect()
 	}
 
 	fn get_index_buffer(&self) -> &Buffer {
@@ -0,0 +1,324 @@
+use crate::{AttributeTrait, IndexTrait};
+use crate::bounds::{Aabb, BoundingSphere};
+use crate::geometry::{weld, face_normal};
+use crate::simplify::simplify;
+use crate::tangent::generate_tangents;
+use crate::optimize::{CacheStatistics, analyze_vertex_cache, optimize_vertex_cache, optimize_overdraw, optimize_vertex_fetch};
+use cgmath::{Matrix, Matrix3, Matrix4, SquareMatrix, Vector3, Vector4, InnerSpace, Zero};
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct MeshData<V: AttributeTrait, I: IndexTrait> {
+	pub vertices: Vec<V>,
+	pub indices: Vec<I>,
+}
+
+impl<V: AttributeTrait, I: IndexTrait> MeshData<V, I> {
+	pub fn new(vertices: Vec<V>, indices: Vec<I>) -> MeshData<V, I> {
+		MeshData {
+			vertices,
+			indices,
+		}
+	}
+
+	pub fn get_triangle_count(&self) -> usize {
+		self.indices.len() / 3
+	}
+
+	pub fn convert_indices<J: IndexTrait>(&self) -> Option<MeshData<V, J>> {
+		let mut indices = Vec::with_capacity(self.indices.len());
+		for index in &self.indices {
+			indices.push(J::try_from_u32(index.to_u32())?);
+		}
+		Some(MeshData::new(self.vertices.clone(), indices))
+	}
+
+	pub fn convert_vertices<W: AttributeTrait>(&self) -> MeshData<W, I> {
+		let vertices = self.vertices.iter().map(|vertex| {
+			let mut converted = W::zeroed();
+			converted.set_position(vertex.get_position());
+			if let Some(normal) = vertex.get_normal() {
+				converted.set_normal(normal);
+			}
+			if let Some(uv) = vertex.get_uv() {
+				converted.set_uv(uv);
+			}
+			converted.set_color(vertex.get_color().unwrap_or([1.0; 4]));
+			if let Some(tangent) = vertex.get_tangent() {
+				converted.set_tangent(tangent);
+			}
+			if let Some(joints) = vertex.get_joints() {
+				converted.set_joints(joints);
+			}
+			if let Some(weights) = vertex.get_weights() {
+				converted.set_weights(weights);
+			}
+			converted
+		}).collect();
+		MeshData::new(vertices, self.indices.clone())
+	}
+
+	pub fn unweld(&mut self) -> Option<()> {
+		let indices = (0..self.indices.len() as u32).map(I::try_from_u32).collect::<Option<Vec<I>>>()?;
+		self.vertices = self.indices.iter().map(|index| self.vertices[index.to_u32() as usize]).collect();
+		self.indices = indices;
+		Some(())
+	}
+
+	pub fn weld(&mut self, tolerance: f32) {
+		let mut indices: Vec<u32> = self.indices.iter().map(|index| index.to_u32()).collect();
+		self.vertices = weld(&self.vertices, &mut indices, tolerance);
+		self.indices = indices.into_iter().map(I::from_u32).collect();
+	}
+
+	pub fn compute_flat_normals(&mut self) -> Option<()> {
+		self.unweld()?;
+		for triangle in self.vertices.chunks_exact_mut(3) {
+			let normal = face_normal(triangle[0].get_position(), triangle[1].get_position(), triangle[2].get_position());
+			for vertex in triangle {
+				vertex.set_normal(normal);
+			}
+		}
+		Some(())
+	}
+
+	pub fn compute_smooth_normals(&mut self) {
+		let mut groups: HashMap<[u32; 3], Vector3<f32>> = HashMap::new();
+		for triangle in self.indices.chunks_exact(3) {
+			let positions = [
+				self.vertices[triangle[0].to_u32() as usize].get_position(),
+				self.vertices[triangle[1].to_u32() as usize].get_position(),
+				self.vertices[triangle[2].to_u32() as usize].get_position(),
+			];
+			let a = Vector3::from(positions[0]);
+			let weighted = (Vector3::from(positions[1]) - a).cross(Vector3::from(positions[2]) - a);
+			for position in &positions {
+				*groups.entry(position_key(*position)).or_insert_with(Vector3::zero) += weighted;
+			}
+		}
+		for vertex in &mut self.vertices {
+			let normal = groups.get(&position_key(vertex.get_position())).copied().unwrap_or_else(Vector3::zero);
+			if normal.magnitude2() > 0.0 {
+				vertex.set_normal(normal.normalize().into());
+			}
+		}
+	}
+
+	pub fn compute_tangents(&mut self) -> Option<()> {
+		let positions: Vec<[f32; 3]> = self.vertices.iter().map(|vertex| vertex.get_position()).collect();
+		let normals: Vec<[f32; 3]> = self.vertices.iter().map(|vertex| match vertex.get_normal() {
+			Some(normal) if Vector3::from(normal).magnitude2() > 0.0 => Vector3::from(normal).normalize().into(),
+			_ => [0.0; 3],
+		}).collect();
+		let uvs: Vec<[f32; 2]> = self.vertices.iter().map(|vertex| vertex.get_uv().unwrap_or([0.0; 2])).collect();
+		let indices: Vec<u32> = self.indices.iter().map(|index| index.to_u32()).collect();
+		let tangents = generate_tangents(&positions, &normals, &uvs, &indices);
+
+		let mut vertices = self.vertices.clone();
+		let mut corners = indices;
+		let mut assigned: Vec<Option<[u32; 4]>> = vec![None; self.vertices.len()];
+		let mut split: HashMap<(u32, [u32; 4]), u32> = HashMap::new();
+		for (corner, tangent) in corners.iter_mut().zip(tangents) {
+			let index = *corner;
+			let normal = Vector3::from(normals[index as usize]);
+			let direction = Vector3::new(tangent[0], tangent[1], tangent[2]);
+			let direction = if direction.magnitude2() > 0.0 { direction } else { any_perpendicular(normal) };
+			let tangent = [direction.x, direction.y, direction.z, tangent[3]];
+			let key = tangent.map(f32::to_bits);
+			match assigned[index as usize] {
+				None => {
+					assigned[index as usize] = Some(key);
+					vertices[index as usize].set_tangent(tangent);
+				}
+				Some(existing) if existing == key => (),
+				Some(_) => {
+					let duplicate = *split.entry((index, key)).or_insert_with(|| {
+						let mut vertex = vertices[index as usize];
+						vertex.set_tangent(tangent);
+						vertices.push(vertex);
+						(vertices.len() - 1) as u32
+					});
+					*corner = duplicate;
+				}
+			}
+		}
+		for (vertex, assigned) in vertices.iter_mut().zip(assigned) {
+			if assigned.is_none() {
+				let tangent = any_perpendicular(Vector3::from(vertex.get_normal().unwrap_or([0.0, 0.0, 1.0])));
+				vertex.set_tangent([tangent.x, tangent.y, tangent.z, 1.0]);
+			}
+		}
+		self.indices = corners.into_iter().map(I::try_from_u32).collect::<Option<Vec<I>>>()?;
+		self.vertices = vertices;
+		Some(())
+	}
+
+	pub fn compute_bounding_box(&self) -> Option<Aabb> {
+		Aabb::from_points(self.vertices.iter().map(|vertex| vertex.get_position()))
+	}
+
+	pub fn compute_bounding_sphere(&self) -> Option<BoundingSphere> {
+		let points: Vec<[f32; 3]> = self.vertices.iter().map(|vertex| vertex.get_position()).collect();
+		BoundingSphere::from_points(&points)
+	}
+
+	pub fn transform(&mut self, matrix: &Matrix4<f32>) {
+		let linear = Matrix3::from_cols(matrix.x.truncate(), matrix.y.truncate(), matrix.z.truncate());
+		let normal_matrix = linear.invert().map(|inverse| inverse.transpose()).unwrap_or(linear);
+		let handedness = if linear.determinant() < 0.0 { -1.0 } else { 1.0 };
+		for vertex in &mut self.vertices {
+			let position = matrix * Vector4::new(vertex.get_position()[0], vertex.get_position()[1], vertex.get_position()[2], 1.0);
+			vertex.set_position([position.x / position.w, position.y / position.w, position.z / position.w]);
+			if let Some(normal) = vertex.get_normal() {
+				let normal = normal_matrix * Vector3::from(normal);
+				if normal.magnitude2() > 0.0 {
+					vertex.set_normal(normal.normalize().into());
+				}
+			}
+			if let Some(tangent) = vertex.get_tangent() {
+				let direction = linear * Vector3::new(tangent[0], tangent[1], tangent[2]);
+				if direction.magnitude2() > 0.0 {
+					let direction = direction.normalize();
+					vertex.set_tangent([direction.x, direction.y, direction.z, tangent[3] * handedness]);
+				}
+			}
+		}
+		if handedness < 0.0 {
+			self.flip_winding();
+		}
+	}
+
+	pub fn simplify(&self, target_triangle_count: usize, target_error: f32) -> (MeshData<V, I>, f32) {
+		let (indices, error) = simplify(self, target_triangle_count, target_error);
+		(MeshData::new(self.vertices.clone(), indices), error)
+	}
+
+	pub fn optimize_vertex_cache(&mut self) {
+		optimize_vertex_cache(&mut self.indices, self.vertices.len());
+	}
+
+	pub fn optimize_overdraw(&mut self, threshold: f32) {
+		let positions: Vec<[f32; 3]> = self.vertices.iter().map(|vertex| vertex.get_position()).collect();
+		optimize_overdraw(&mut self.indices, &positions, threshold);
+	}
+
+	pub fn optimize_vertex_fetch(&mut self) {
+		optimize_vertex_fetch(&mut self.vertices, &mut self.indices);
+	}
+
+	pub fn optimize(&mut self) {
+		self.optimize_vertex_cache();
+		self.optimize_overdraw(1.05);
+		self.optimize_vertex_fetch();
+	}
+
+	pub fn analyze_vertex_cache(&self, cache_size: usize) -> CacheStatistics {
+		analyze_vertex_cache(&self.indices, self.vertices.len(), cache_size)
+	}
+
+	pub fn merge(&mut self, other: &MeshData<V, I>) -> Option<()> {
+		let offset = self.vertices.len() as u32;
+		let indices = other.indices.iter()
+			.map(|index| index.to_u32().checked_add(offset).and_then(I::try_from_u32))
+			.collect::<Option<Vec<I>>>()?;
+		self.vertices.extend_from_slice(&other.vertices);
+		self.indices.extend(indices);
+		Some(())
+	}
+
+	pub fn flip_winding(&mut self) {
+		for triangle in self.indices.chunks_exact_mut(3) {
+			triangle.swap(1, 2);
+		}
+	}
+}
+
+fn position_key(position: [f32; 3]) -> [u32; 3] {
+	[(position[0] + 0.0).to_bits(), (position[1] + 0.0).to_bits(), (position[2] + 0.0).to_bits()]
+}
+
+fn any_perpendicular(normal: Vector3<f32>) -> Vector3<f32> {
+	let axis = if normal.x.abs() < 0.9 { Vector3::unit_x() } else { Vector3::unit_y() };
+	let tangent = axis - normal * normal.dot(axis);
+	if tangent.magnitude2() > 0.0 { tangent.normalize() } else { Vector3::unit_x() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PbrVertex;
+	use crate::primitive::plane;
+
+	fn triangle(offset: f32) -> MeshData<PbrVertex, u16> {
+		let vertices = [[offset, 0.0, 0.0], [offset + 1.0, 0.0, 0.0], [offset, 1.0, 0.0]].iter()
+			.map(|position| PbrVertex::new(*position, [0.0; 3], [0.0; 2], [0.0; 4]))
+			.collect();
+		MeshData::new(vertices, vec![0, 1, 2])
+	}
+
+	#[test]
+	fn computes_flat_normals() {
+		let mut data: MeshData<PbrVertex, u32> = plane(1.0, 1.0, 2, 2);
+		let triangle_count = data.get_triangle_count();
+		assert_eq!(data.compute_flat_normals(), Some(()));
+		assert_eq!(data.vertices.len(), triangle_count * 3);
+		assert!(data.vertices.iter().all(|vertex| vertex.get_normal() == Some([0.0, 1.0, 0.0])));
+	}
+
+	#[test]
+	fn flat_normals_report_u16_overflow() {
+		let mut data = plane::<PbrVertex>(1.0, 1.0, 105, 105).convert_indices::<u16>().expect("plane_exceeds_u16");
+		let vertex_count = data.vertices.len();
+		assert_eq!(data.compute_flat_normals(), None);
+		assert_eq!(data.vertices.len(), vertex_count);
+	}
+
+	#[test]
+	fn merges_with_offset_indices() {
+		let mut data = triangle(0.0);
+		assert_eq!(data.merge(&triangle(2.0)), Some(()));
+		assert_eq!(data.indices, vec![0, 1, 2, 3, 4, 5]);
+		assert_eq!(data.vertices[3].get_position(), [2.0, 0.0, 0.0]);
+	}
+
+	#[test]
+	fn merge_reports_u16_overflow() {
+		let mut data = triangle(0.0);
+		data.vertices.resize(40000, data.vertices[0]);
+		let mut other = data.clone();
+		other.indices = vec![0, 1, 39999];
+		assert_eq!(data.merge(&other), None);
+		assert_eq!(data.vertices.len(), 40000);
+		assert_eq!(data.indices, vec![0, 1, 2]);
+	}
+
+	#[test]
+	fn welds_duplicate_vertices() {
+		let mut data = triangle(0.0);
+		data.merge(&triangle(0.0)).expect("merge_failed");
+		data.weld(1e-5);
+		assert_eq!(data.vertices.len(), 3);
+		assert_eq!(data.indices, vec![0, 1, 2, 0, 1, 2]);
+		data.unweld().expect("unweld_failed");
+		assert_eq!(data.vertices.len(), 6);
+	}
+
+	#[test]
+	fn computes_plane_tangents() {
+		let mut data: MeshData<PbrVertex, u32> = plane(2.0, 2.0, 3, 3);
+		data.compute_tangents().expect("tangent_generation_failed");
+		for vertex in &data.vertices {
+			let tangent = vertex.get_tangent().expect("tangent_missing");
+			assert!((tangent[0] - 1.0).abs() < 1e-5 && tangent[1].abs() < 1e-5 && tangent[2].abs() < 1e-5);
+			assert_eq!(tangent[3], 1.0);
+		}
+	}
+
+	#[test]
+	fn converts_indices_only_when_they_fit() {
+		let mut data: MeshData<PbrVertex, u32> = MeshData::new(Vec::new(), vec![0, 1, 70000]);
+		assert!(data.convert_indices::<u16>().is_none());
+		data.indices[2] = 2;
+		assert_eq!(data.convert_indices::<u16>().expect("conversion_failed").indices, vec![0u16, 1, 2]);
+	}
+}
@@ -0,0 +1,177 @@
+use crate::quantize::{from_unorm8, to_unorm8};
+use std::f32::consts::PI;
+
+const LANCZOS_LOBES: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MipFilter {
+	Box,
+	Lanczos,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MipLevel {
+	pub width: u32,
+	pub height: u32,
+	pub pixels: Vec<[f32; 4]>,
+}
+
+pub fn get_mip_level_count(width: u32, height: u32) -> u32 {
+	32 - width.max(height).max(1).leading_zeros()
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+	if value <= 0.003_130_8 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+pub fn decode_rgba8(rgba: &[u8], srgb: bool) -> Vec<[f32; 4]> {
+	rgba.chunks_exact(4).map(|pixel| {
+		let mut decoded = [from_unorm8(pixel[0]), from_unorm8(pixel[1]), from_unorm8(pixel[2]), from_unorm8(pixel[3])];
+		if srgb {
+			for channel in &mut decoded[..3] {
+				*channel = srgb_to_linear(*channel);
+			}
+		}
+		decoded
+	}).collect()
+}
+
+pub fn encode_rgba8(pixels: &[[f32; 4]], srgb: bool) -> Vec<u8> {
+	let mut rgba = Vec::with_capacity(pixels.len() * 4);
+	for pixel in pixels {
+		for (index, &channel) in pixel.iter().enumerate() {
+			let channel = if srgb && index < 3 { linear_to_srgb(channel.max(0.0)) } else { channel };
+			rgba.push(to_unorm8(channel));
+		}
+	}
+	rgba
+}
+
+pub fn generate_mip_chain(width: u32, height: u32, pixels: Vec<[f32; 4]>, filter: MipFilter) -> Vec<MipLevel> {
+	if pixels.len() != width as usize * height as usize {
+		panic!("mip_size_mismatch");
+	}
+	let level_count = get_mip_level_count(width, height);
+	let mut levels = Vec::with_capacity(level_count as usize);
+	levels.push(MipLevel {
+		width,
+		height,
+		pixels,
+	});
+	for _ in 1..level_count {
+		let previous = levels.last().expect("mip_level_missing");
+		let (next_width, next_height) = ((previous.width / 2).max(1), (previous.height / 2).max(1));
+		let next = match filter {
+			MipFilter::Box => downsample_box(previous),
+			MipFilter::Lanczos => resample_lanczos(previous, next_width, next_height),
+		};
+		levels.push(next);
+	}
+	levels
+}
+
+fn downsample_box(source: &MipLevel) -> MipLevel {
+	let width = (source.width / 2).max(1);
+	let height = (source.height / 2).max(1);
+	let mut pixels = Vec::with_capacity((width * height) as usize);
+	for y in 0..height {
+		for x in 0..width {
+			let mut sum = [0.0; 4];
+			let mut count = 0.0;
+			for source_y in y * 2..(y * 2 + 2).min(source.height) {
+				for source_x in x * 2..(x * 2 + 2).min(source.width) {
+					let pixel = source.pixels[(source_y * source.width + source_x) as usize];
+					for (total, channel) in sum.iter_mut().zip(pixel.iter()) {
+						*total += channel;
+					}
+					count += 1.0;
+				}
+			}
+			pixels.push([sum[0] / count, sum[1] / count, sum[2] / count, sum[3] / count]);
+		}
+	}
+
+	MipLevel {
+		width,
+		height,
+		pixels,
+	}
+}
+
+fn resample_lanczos(source: &MipLevel, width: u32, height: u32) -> MipLevel {
+	let horizontal = get_lanczos_weights(source.width, width);
+	let vertical = get_lanczos_weights(source.height, height);
+	let mut rows = Vec::with_capacity((width * source.height) as usize);
+	for y in 0..source.height {
+		for (start, weights) in &horizontal {
+			let mut sum = [0.0; 4];
+			for (offset, weight) in weights.iter().enumerate() {
+				let pixel = source.pixels[(y * source.width) as usize + start + offset];
+				for (total, channel) in sum.iter_mut().zip(pixel.iter()) {
+					*total += channel * weight;
+				}
+			}
+			rows.push(sum);
+		}
+	}
+	let mut pixels = Vec::with_capacity((width * height) as usize);
+	for (start, weights) in &vertical {
+		for x in 0..width as usize {
+			let mut sum = [0.0; 4];
+			for (offset, weight) in weights.iter().enumerate() {
+				let pixel = rows[(start + offset) * width as usize + x];
+				for (total, channel) in sum.iter_mut().zip(pixel.iter()) {
+					*total += channel * weight;
+				}
+			}
+			pixels.push([sum[0].max(0.0), sum[1].max(0.0), sum[2].max(0.0), sum[3].clamp(0.0, 1.0)]);
+		}
+	}
+
+	MipLevel {
+		width,
+		height,
+		pixels,
+	}
+}
+
+fn get_lanczos_weights(source_size: u32, target_size: u32) -> Vec<(usize, Vec<f32>)> {
+	let scale = (source_size as f32 / target_size as f32).max(1.0);
+	let support = LANCZOS_LOBES * scale;
+	(0..target_size).map(|index| {
+		let center = (index as f32 + 0.5) * source_size as f32 / target_size as f32;
+		let start = (center - support).floor().max(0.0) as usize;
+		let end = ((center + support).ceil() as usize).min(source_size as usize);
+		let mut weights: Vec<f32> = (start..end).map(|source| lanczos((source as f32 + 0.5 - center) / scale)).collect();
+		let total: f32 = weights.iter().sum();
+		if total.abs() > f32::EPSILON {
+			for weight in &mut weights {
+				*weight /= total;
+			}
+		}
+		(start, weights)
+	}).collect()
+}
+
+fn lanczos(x: f32) -> f32 {
+	if x.abs() < f32::EPSILON {
+		1.0
+	} else if x.abs() >= LANCZOS_LOBES {
+		0.0
+	} else {
+		let px = PI * x;
+		LANCZOS_LOBES * px.sin() * (px / LANCZOS_LOBES).sin() / (px * px)
+	}
+}
\ No newline at end of file
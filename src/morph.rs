@@ -0,0 +1,240 @@
+use crate::{AttributeTrait, IndexTrait, Mesh, MeshTrait, Renderer, Bind};
+use bytemuck::{bytes_of, cast_slice, Pod, Zeroable};
+use wgpu::*;
+
+pub const MAX_MORPH_TARGETS: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct MorphTarget {
+	pub name: Option<String>,
+	pub positions: Vec<[f32; 3]>,
+	pub normals: Option<Vec<[f32; 3]>>,
+}
+
+impl MorphTarget {
+	pub fn new(name: Option<String>, positions: Vec<[f32; 3]>, normals: Option<Vec<[f32; 3]>>) -> MorphTarget {
+		MorphTarget {
+			name,
+			positions,
+			normals,
+		}
+	}
+}
+
+pub fn apply_morph_targets<V: AttributeTrait>(base: &[V], targets: &[MorphTarget], weights: &[f32]) -> Vec<V> {
+	let mut vertices = base.to_vec();
+	for (target, weight) in targets.iter().zip(weights) {
+		if *weight == 0.0 {
+			continue;
+		}
+		for (vertex, delta) in vertices.iter_mut().zip(&target.positions) {
+			let position = vertex.get_position();
+			vertex.set_position([position[0] + delta[0] * weight, position[1] + delta[1] * weight, position[2] + delta[2] * weight]);
+		}
+		if let Some(normals) = &target.normals {
+			for (vertex, delta) in vertices.iter_mut().zip(normals) {
+				if let Some(normal) = vertex.get_normal() {
+					vertex.set_normal([normal[0] + delta[0] * weight, normal[1] + delta[1] * weight, normal[2] + delta[2] * weight]);
+				}
+			}
+		}
+	}
+	if targets.iter().any(|target| target.normals.is_some()) {
+		for vertex in &mut vertices {
+			if let Some(normal) = vertex.get_normal() {
+				let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+				if length > 0.0 {
+					vertex.set_normal([normal[0] / length, normal[1] / length, normal[2] / length]);
+				}
+			}
+		}
+	}
+	vertices
+}
+
+pub struct MorphMesh<V: AttributeTrait, I: IndexTrait> {
+	mesh: Mesh<V, I>,
+	base: Vec<V>,
+	targets: Vec<MorphTarget>,
+	weights: Vec<f32>,
+}
+
+impl<V: AttributeTrait, I: IndexTrait> MorphMesh<V, I> {
+	pub fn new(renderer: &Renderer, vertices: &[V], indices: &[I], targets: Vec<MorphTarget>) -> MorphMesh<V, I> {
+		check_targets(vertices.len(), &targets);
+
+		MorphMesh {
+			mesh: Mesh::new(renderer, vertices, indices),
+			base: vertices.to_vec(),
+			weights: vec![0.0; targets.len()],
+			targets,
+		}
+	}
+
+	pub fn set_weights(&mut self, renderer: &Renderer, weights: &[f32]) {
+		self.weights = weights.iter().copied().chain(std::iter::repeat(0.0)).take(self.targets.len()).collect();
+		let vertices = apply_morph_targets(&self.base, &self.targets, &self.weights);
+		self.mesh.update_vertex(renderer, &vertices);
+	}
+
+	pub fn get_weights(&self) -> &[f32] {
+		&self.weights
+	}
+
+	pub fn get_targets(&self) -> &[MorphTarget] {
+		&self.targets
+	}
+
+	pub fn get_mesh(&self) -> &Mesh<V, I> {
+		&self.mesh
+	}
+}
+
+impl<V: AttributeTrait, I: IndexTrait> MeshTrait for MorphMesh<V, I> {
+	fn get_vertex_buffers(&self) -> Vec<&Buffer> {
+		self.mesh.get_vertex_buffers()
+	}
+
+	fn get_index_buffer(&self) -> &Buffer {
+		self.mesh.get_index_buffer()
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct MorphWeights {
+	counts: [u32; 4],
+	weights: [[f32; 4]; MAX_MORPH_TARGETS / 4],
+}
+
+unsafe impl Pod for MorphWeights {}
+
+unsafe impl Zeroable for MorphWeights {}
+
+pub struct MorphTargetBuffer {
+	weight_buffer: Buffer,
+	_delta_buffer: Buffer,
+	bind_group: BindGroup,
+	vertex_count: u32,
+	target_count: u32,
+}
+
+impl MorphTargetBuffer {
+	pub fn new(renderer: &Renderer, vertex_count: usize, targets: &[MorphTarget]) -> MorphTargetBuffer {
+		check_targets(vertex_count, targets);
+		if targets.len() > MAX_MORPH_TARGETS {
+			panic!("morph_target_count_exceeded");
+		}
+		let mut deltas: Vec<[f32; 4]> = Vec::with_capacity((targets.len() * vertex_count * 2).max(2));
+		for target in targets {
+			for vertex in 0..vertex_count {
+				let position = target.positions[vertex];
+				let normal = target.normals.as_ref().map_or([0.0; 3], |normals| normals[vertex]);
+				deltas.push([position[0], position[1], position[2], 0.0]);
+				deltas.push([normal[0], normal[1], normal[2], 0.0]);
+			}
+		}
+		if deltas.is_empty() {
+			deltas.resize(2, [0.0; 4]);
+		}
+		let weights = MorphWeights {
+			counts: [vertex_count as u32, targets.len() as u32, 0, 0],
+			weights: [[0.0; 4]; MAX_MORPH_TARGETS / 4],
+		};
+		let weight_buffer = renderer
+			.get_device()
+			.create_buffer_with_data(bytes_of(&weights), BufferUsage::UNIFORM | BufferUsage::COPY_DST);
+		let delta_data: &[u8] = cast_slice(&deltas);
+		let _delta_buffer = renderer
+			.get_device()
+			.create_buffer_with_data(delta_data, BufferUsage::STORAGE_READ);
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("morph_target_buffer_type_not_registered"),
+			bindings: &[
+				Binding {
+					binding: 0,
+					resource: BindingResource::Buffer {
+						buffer: &weight_buffer,
+						range: 0..std::mem::size_of::<MorphWeights>() as BufferAddress,
+					},
+				},
+				Binding {
+					binding: 1,
+					resource: BindingResource::Buffer {
+						buffer: &_delta_buffer,
+						range: 0..delta_data.len() as BufferAddress,
+					},
+				},
+			],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		MorphTargetBuffer {
+			weight_buffer,
+			_delta_buffer,
+			bind_group,
+			vertex_count: vertex_count as u32,
+			target_count: targets.len() as u32,
+		}
+	}
+
+	pub fn update_weights(&self, renderer: &Renderer, weights: &[f32]) {
+		let mut data = MorphWeights {
+			counts: [self.vertex_count, self.target_count, 0, 0],
+			weights: [[0.0; 4]; MAX_MORPH_TARGETS / 4],
+		};
+		for (index, weight) in weights.iter().take(self.target_count as usize).enumerate() {
+			data.weights[index / 4][index % 4] = *weight;
+		}
+		let data = bytes_of(&data);
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("morph_weight_update_encoder"),
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		let staging_buffer = renderer.get_device().create_buffer_with_data(data, BufferUsage::COPY_SRC);
+		encoder.copy_buffer_to_buffer(&staging_buffer, 0, &self.weight_buffer, 0, data.len() as u64);
+		renderer.get_queue().submit(&[encoder.finish()]);
+	}
+
+	pub fn get_target_count(&self) -> u32 {
+		self.target_count
+	}
+}
+
+impl Bind for MorphTargetBuffer {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[
+				BindGroupLayoutEntry {
+					binding: 0,
+					visibility: ShaderStage::VERTEX,
+					ty: BindingType::UniformBuffer { dynamic: false },
+				},
+				BindGroupLayoutEntry {
+					binding: 1,
+					visibility: ShaderStage::VERTEX,
+					ty: BindingType::StorageBuffer { dynamic: false, readonly: true },
+				},
+			],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+fn check_targets(vertex_count: usize, targets: &[MorphTarget]) {
+	for target in targets {
+		let normals_match = match &target.normals {
+			Some(normals) => normals.len() == vertex_count,
+			None => true,
+		};
+		if target.positions.len() != vertex_count || !normals_match {
+			panic!("morph_target_size_mismatch");
+		}
+	}
+}
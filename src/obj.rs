@@ -0,0 +1,483 @@
+use crate::{AttributeTrait, MeshData, LoadError};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::Path;
+use std::fs;
+
+pub struct ObjModel<V: AttributeTrait> {
+	pub meshes: Vec<ObjMesh<V>>,
+	pub material_libraries: Vec<String>,
+}
+
+pub struct ObjMesh<V: AttributeTrait> {
+	pub object: String,
+	pub group: String,
+	pub material: Option<String>,
+	pub data: MeshData<V, u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjMaterial {
+	pub name: String,
+	pub ambient: [f32; 3],
+	pub diffuse: [f32; 3],
+	pub specular: [f32; 3],
+	pub emissive: [f32; 3],
+	pub shininess: f32,
+	pub optical_density: f32,
+	pub dissolve: f32,
+	pub illumination: Option<u32>,
+	pub roughness: Option<f32>,
+	pub metallic: Option<f32>,
+	pub ambient_texture: Option<String>,
+	pub diffuse_texture: Option<String>,
+	pub specular_texture: Option<String>,
+	pub emissive_texture: Option<String>,
+	pub shininess_texture: Option<String>,
+	pub dissolve_texture: Option<String>,
+	pub normal_texture: Option<String>,
+	pub roughness_texture: Option<String>,
+	pub metallic_texture: Option<String>,
+}
+
+impl ObjMaterial {
+	fn new(name: &str) -> ObjMaterial {
+		ObjMaterial {
+			name: String::from(name),
+			ambient: [0.0; 3],
+			diffuse: [1.0; 3],
+			specular: [0.0; 3],
+			emissive: [0.0; 3],
+			shininess: 0.0,
+			optical_density: 1.0,
+			dissolve: 1.0,
+			illumination: None,
+			roughness: None,
+			metallic: None,
+			ambient_texture: None,
+			diffuse_texture: None,
+			specular_texture: None,
+			emissive_texture: None,
+			shininess_texture: None,
+			dissolve_texture: None,
+			normal_texture: None,
+			roughness_texture: None,
+			metallic_texture: None,
+		}
+	}
+}
+
+pub fn load_obj<V: AttributeTrait, P: AsRef<Path>>(path: P) -> Result<(ObjModel<V>, Vec<ObjMaterial>), LoadError> {
+	let path = path.as_ref();
+	let model: ObjModel<V> = parse_obj(&fs::read_to_string(path)?)?;
+	let directory = path.parent().unwrap_or_else(|| Path::new(""));
+	let mut materials = Vec::new();
+	for library in &model.material_libraries {
+		materials.append(&mut parse_mtl(&fs::read_to_string(directory.join(library))?)?);
+	}
+	Ok((model, materials))
+}
+
+pub fn parse_obj<V: AttributeTrait>(source: &str) -> Result<ObjModel<V>, LoadError> {
+	let mut positions: Vec<[f32; 3]> = Vec::new();
+	let mut colors: Vec<Option<[f32; 3]>> = Vec::new();
+	let mut uvs: Vec<[f32; 2]> = Vec::new();
+	let mut normals: Vec<[f32; 3]> = Vec::new();
+	let mut model = ObjModel {
+		meshes: Vec::new(),
+		material_libraries: Vec::new(),
+	};
+	let mut builder = MeshBuilder::new(String::new(), String::new(), None);
+	let mut face: Vec<(usize, Option<usize>, Option<usize>)> = Vec::new();
+
+	for (number, line) in source.lines().enumerate() {
+		let number = number + 1;
+		let line = match line.find('#') {
+			Some(position) => &line[..position],
+			None => line,
+		};
+		let mut tokens = line.split_whitespace();
+		let keyword = match tokens.next() {
+			Some(keyword) => keyword,
+			None => continue,
+		};
+		match keyword {
+			"v" => {
+				let values = parse_floats(number, tokens)?;
+				match values.len() {
+					3 | 4 => {
+						positions.push([values[0], values[1], values[2]]);
+						colors.push(None);
+					}
+					6 | 7 => {
+						positions.push([values[0], values[1], values[2]]);
+						let offset = values.len() - 3;
+						colors.push(Some([values[offset], values[offset + 1], values[offset + 2]]));
+					}
+					_ => return Err(LoadError::parse(number, "invalid_vertex")),
+				}
+			}
+			"vt" => {
+				let values = parse_floats(number, tokens)?;
+				match values.len() {
+					1 => uvs.push([values[0], 0.0]),
+					2 | 3 => uvs.push([values[0], values[1]]),
+					_ => return Err(LoadError::parse(number, "invalid_texture_coordinate")),
+				}
+			}
+			"vn" => {
+				let values = parse_floats(number, tokens)?;
+				if values.len() != 3 {
+					return Err(LoadError::parse(number, "invalid_normal"));
+				}
+				normals.push([values[0], values[1], values[2]]);
+			}
+			"f" => {
+				face.clear();
+				for token in tokens {
+					let mut parts = token.split('/');
+					let position = resolve_index(number, parts.next(), positions.len())?
+						.ok_or_else(|| LoadError::parse(number, "missing_position_index"))?;
+					let uv = resolve_index(number, parts.next(), uvs.len())?;
+					let normal = resolve_index(number, parts.next(), normals.len())?;
+					if parts.next().is_some() {
+						return Err(LoadError::parse(number, "invalid_face_vertex"));
+					}
+					face.push((position, uv, normal));
+				}
+				if face.len() < 3 {
+					return Err(LoadError::parse(number, "face_with_less_than_three_vertices"));
+				}
+				let mut corners = Vec::with_capacity(face.len());
+				for (position, uv, normal) in &face {
+					corners.push(builder.add_vertex((*position, *uv, *normal), || {
+						let mut vertex = V::zeroed();
+						vertex.set_position(positions[*position]);
+						match colors[*position] {
+							Some(color) => vertex.set_color([color[0], color[1], color[2], 1.0]),
+							None => vertex.set_color([1.0; 4]),
+						}
+						if let Some(uv) = uv {
+							vertex.set_uv(uvs[*uv]);
+						}
+						if let Some(normal) = normal {
+							vertex.set_normal(normals[*normal]);
+						}
+						vertex
+					}));
+				}
+				let polygon: Vec<[f32; 3]> = face.iter().map(|(position, _, _)| positions[*position]).collect();
+				for triangle in triangulate(&polygon) {
+					builder.mesh.data.indices.push(corners[triangle[0]]);
+					builder.mesh.data.indices.push(corners[triangle[1]]);
+					builder.mesh.data.indices.push(corners[triangle[2]]);
+				}
+			}
+			"o" => {
+				let name = join_tokens(tokens);
+				let next = MeshBuilder::new(name, String::new(), builder.mesh.material.clone());
+				builder.finish(&mut model.meshes, next);
+			}
+			"g" => {
+				let name = join_tokens(tokens);
+				let next = MeshBuilder::new(builder.mesh.object.clone(), name, builder.mesh.material.clone());
+				builder.finish(&mut model.meshes, next);
+			}
+			"usemtl" => {
+				let name = join_tokens(tokens);
+				let next = MeshBuilder::new(builder.mesh.object.clone(), builder.mesh.group.clone(), Some(name));
+				builder.finish(&mut model.meshes, next);
+			}
+			"mtllib" => {
+				model.material_libraries.extend(tokens.map(String::from));
+			}
+			_ => (),
+		}
+	}
+	let next = MeshBuilder::new(String::new(), String::new(), None);
+	builder.finish(&mut model.meshes, next);
+	Ok(model)
+}
+
+pub fn parse_mtl(source: &str) -> Result<Vec<ObjMaterial>, LoadError> {
+	let mut materials: Vec<ObjMaterial> = Vec::new();
+	for (number, line) in source.lines().enumerate() {
+		let number = number + 1;
+		let line = match line.find('#') {
+			Some(position) => &line[..position],
+			None => line,
+		};
+		let mut tokens = line.split_whitespace();
+		let keyword = match tokens.next() {
+			Some(keyword) => keyword,
+			None => continue,
+		};
+		if keyword == "newmtl" {
+			materials.push(ObjMaterial::new(&join_tokens(tokens)));
+			continue;
+		}
+		let material = materials.last_mut().ok_or_else(|| LoadError::parse(number, "statement_before_newmtl"))?;
+		match keyword {
+			"Ka" => material.ambient = parse_color(number, tokens)?,
+			"Kd" => material.diffuse = parse_color(number, tokens)?,
+			"Ks" => material.specular = parse_color(number, tokens)?,
+			"Ke" => material.emissive = parse_color(number, tokens)?,
+			"Ns" => material.shininess = parse_float(number, tokens)?,
+			"Ni" => material.optical_density = parse_float(number, tokens)?,
+			"d" => material.dissolve = parse_float(number, tokens)?,
+			"Tr" => material.dissolve = 1.0 - parse_float(number, tokens)?,
+			"Pr" => material.roughness = Some(parse_float(number, tokens)?),
+			"Pm" => material.metallic = Some(parse_float(number, tokens)?),
+			"illum" => {
+				let value = parse_float(number, tokens)?;
+				material.illumination = Some(value as u32);
+			}
+			"map_Ka" => material.ambient_texture = Some(parse_texture(number, tokens)?),
+			"map_Kd" => material.diffuse_texture = Some(parse_texture(number, tokens)?),
+			"map_Ks" => material.specular_texture = Some(parse_texture(number, tokens)?),
+			"map_Ke" => material.emissive_texture = Some(parse_texture(number, tokens)?),
+			"map_Ns" => material.shininess_texture = Some(parse_texture(number, tokens)?),
+			"map_d" => material.dissolve_texture = Some(parse_texture(number, tokens)?),
+			"map_Bump" | "map_bump" | "bump" | "norm" => material.normal_texture = Some(parse_texture(number, tokens)?),
+			"map_Pr" => material.roughness_texture = Some(parse_texture(number, tokens)?),
+			"map_Pm" => material.metallic_texture = Some(parse_texture(number, tokens)?),
+			_ => (),
+		}
+	}
+	Ok(materials)
+}
+
+struct MeshBuilder<V: AttributeTrait> {
+	mesh: ObjMesh<V>,
+	lookup: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+}
+
+impl<V: AttributeTrait> MeshBuilder<V> {
+	fn new(object: String, group: String, material: Option<String>) -> MeshBuilder<V> {
+		MeshBuilder {
+			mesh: ObjMesh {
+				object,
+				group,
+				material,
+				data: MeshData::new(Vec::new(), Vec::new()),
+			},
+			lookup: HashMap::new(),
+		}
+	}
+
+	fn add_vertex<F: FnOnce() -> V>(&mut self, key: (usize, Option<usize>, Option<usize>), create: F) -> u32 {
+		match self.lookup.entry(key) {
+			Entry::Occupied(o) => *o.get(),
+			Entry::Vacant(v) => {
+				let index = self.mesh.data.vertices.len() as u32;
+				self.mesh.data.vertices.push(create());
+				*v.insert(index)
+			}
+		}
+	}
+
+	fn finish(&mut self, meshes: &mut Vec<ObjMesh<V>>, next: MeshBuilder<V>) {
+		let builder = std::mem::replace(self, next);
+		if !builder.mesh.data.indices.is_empty() {
+			meshes.push(builder.mesh);
+		}
+	}
+}
+
+fn join_tokens<'a, T: Iterator<Item = &'a str>>(tokens: T) -> String {
+	tokens.collect::<Vec<&str>>().join(" ")
+}
+
+fn parse_floats<'a, T: Iterator<Item = &'a str>>(number: usize, tokens: T) -> Result<Vec<f32>, LoadError> {
+	tokens.map(|token| token.parse::<f32>().map_err(|_| LoadError::parse(number, "invalid_number"))).collect()
+}
+
+fn parse_float<'a, T: Iterator<Item = &'a str>>(number: usize, tokens: T) -> Result<f32, LoadError> {
+	let values = parse_floats(number, tokens)?;
+	if values.len() != 1 {
+		return Err(LoadError::parse(number, "expected_single_value"));
+	}
+	Ok(values[0])
+}
+
+fn parse_color<'a, T: Iterator<Item = &'a str>>(number: usize, mut tokens: T) -> Result<[f32; 3], LoadError> {
+	let first = tokens.next().ok_or_else(|| LoadError::parse(number, "missing_color"))?;
+	if first == "spectral" || first == "xyz" {
+		return Err(LoadError::parse(number, "unsupported_color_space"));
+	}
+	let values = parse_floats(number, std::iter::once(first).chain(tokens))?;
+	match values.len() {
+		1 => Ok([values[0]; 3]),
+		3 => Ok([values[0], values[1], values[2]]),
+		_ => Err(LoadError::parse(number, "invalid_color")),
+	}
+}
+
+fn parse_texture<'a, T: Iterator<Item = &'a str>>(number: usize, tokens: T) -> Result<String, LoadError> {
+	let tokens: Vec<&str> = tokens.collect();
+	let mut position = 0;
+	while position < tokens.len() && tokens[position].starts_with('-') {
+		let arguments = match tokens[position] {
+			"-blendu" | "-blendv" | "-cc" | "-clamp" | "-texres" | "-imfchan" | "-bm" | "-boost" | "-type" => 1,
+			"-mm" => 2,
+			"-o" | "-s" | "-t" => {
+				let mut count = 0;
+				while count < 3 && position + count + 1 < tokens.len() - 1 && tokens[position + count + 1].parse::<f32>().is_ok() {
+					count += 1;
+				}
+				count
+			}
+			_ => return Err(LoadError::parse(number, "unknown_texture_option")),
+		};
+		position += arguments + 1;
+	}
+	if position >= tokens.len() {
+		return Err(LoadError::parse(number, "missing_texture_path"));
+	}
+	Ok(tokens[position..].join(" "))
+}
+
+fn resolve_index(number: usize, token: Option<&str>, count: usize) -> Result<Option<usize>, LoadError> {
+	let token = match token {
+		Some(token) if !token.is_empty() => token,
+		_ => return Ok(None),
+	};
+	let index = token.parse::<i64>().map_err(|_| LoadError::parse(number, "invalid_index"))?;
+	let resolved = if index > 0 {
+		index - 1
+	} else if index < 0 {
+		count as i64 + index
+	} else {
+		return Err(LoadError::parse(number, "invalid_index"));
+	};
+	if resolved < 0 || resolved >= count as i64 {
+		return Err(LoadError::parse(number, "index_out_of_range"));
+	}
+	Ok(Some(resolved as usize))
+}
+
+fn triangulate(polygon: &[[f32; 3]]) -> Vec<[usize; 3]> {
+	let count = polygon.len();
+	if count == 3 {
+		return vec![[0, 1, 2]];
+	}
+	let mut normal = [0.0f32; 3];
+	for i in 0..count {
+		let a = polygon[i];
+		let b = polygon[(i + 1) % count];
+		normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+		normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+		normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+	}
+	let axis = if normal[0].abs() > normal[1].abs() && normal[0].abs() > normal[2].abs() {
+		0
+	} else if normal[1].abs() > normal[2].abs() {
+		1
+	} else {
+		2
+	};
+	let (u, v) = match axis {
+		0 => (1, 2),
+		1 => (2, 0),
+		_ => (0, 1),
+	};
+	let sign = if normal[axis] < 0.0 { -1.0 } else { 1.0 };
+	let points: Vec<[f32; 2]> = polygon.iter().map(|p| [p[u], p[v] * sign]).collect();
+
+	let mut remaining: Vec<usize> = (0..count).collect();
+	let mut triangles = Vec::with_capacity(count - 2);
+	while remaining.len() > 3 {
+		let length = remaining.len();
+		let ear = (0..length).find(|i| {
+			is_ear(&points, &remaining, remaining[(i + length - 1) % length], remaining[*i], remaining[(i + 1) % length])
+		});
+		match ear {
+			Some(i) => {
+				triangles.push([remaining[(i + length - 1) % length], remaining[i], remaining[(i + 1) % length]]);
+				remaining.remove(i);
+			}
+			None => break,
+		}
+	}
+	if remaining.len() > 3 {
+		for i in 1..remaining.len() - 1 {
+			triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+		}
+	} else {
+		triangles.push([remaining[0], remaining[1], remaining[2]]);
+	}
+	triangles
+}
+
+fn is_ear(points: &[[f32; 2]], remaining: &[usize], previous: usize, current: usize, next: usize) -> bool {
+	let a = points[previous];
+	let b = points[current];
+	let c = points[next];
+	if cross(a, b, c) <= 0.0 {
+		return false;
+	}
+	for &index in remaining {
+		if index == previous || index == current || index == next {
+			continue;
+		}
+		let p = points[index];
+		if cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0 {
+			return false;
+		}
+	}
+	true
+}
+
+fn cross(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+	(b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PbrVertex;
+
+	#[test]
+	fn parses_quad_into_two_triangles() {
+		let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//1 4//1\n";
+		let model: ObjModel<PbrVertex> = parse_obj(source).expect("obj_parsing_failed");
+		assert_eq!(model.meshes.len(), 1);
+		let data = &model.meshes[0].data;
+		assert_eq!(data.vertices.len(), 4);
+		assert_eq!(data.get_triangle_count(), 2);
+		assert!(data.vertices.iter().all(|vertex| vertex.get_normal() == Some([0.0, 0.0, 1.0])));
+	}
+
+	#[test]
+	fn resolves_negative_indices_and_splits_groups() {
+		let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\ng first\nusemtl red\nf -3 -2 -1\ng second\nf 1 2 3\n";
+		let model: ObjModel<PbrVertex> = parse_obj(source).expect("obj_parsing_failed");
+		assert_eq!(model.meshes.len(), 2);
+		assert_eq!(model.meshes[0].group, "first");
+		assert_eq!(model.meshes[0].material.as_deref(), Some("red"));
+		assert_eq!(model.meshes[1].group, "second");
+		assert_eq!(model.meshes[1].material.as_deref(), Some("red"));
+		assert_eq!(model.meshes[0].data.indices, model.meshes[1].data.indices);
+	}
+
+	#[test]
+	fn rejects_out_of_range_and_short_faces() {
+		assert!(parse_obj::<PbrVertex>("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").is_err());
+		assert!(parse_obj::<PbrVertex>("v 0 0 0\nv 1 0 0\nf 1 2\n").is_err());
+		assert!(parse_obj::<PbrVertex>("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 0\n").is_err());
+		assert!(parse_obj::<PbrVertex>("v 0 0 x\n").is_err());
+	}
+
+	#[test]
+	fn parses_materials() {
+		let source = "newmtl metal\nKd 0.5 0.25 1\nd 0.5\nPm 1\nmap_Kd -s 2 2 1 textures/albedo.png\n";
+		let materials = parse_mtl(source).expect("mtl_parsing_failed");
+		assert_eq!(materials.len(), 1);
+		assert_eq!(materials[0].name, "metal");
+		assert_eq!(materials[0].diffuse, [0.5, 0.25, 1.0]);
+		assert_eq!(materials[0].dissolve, 0.5);
+		assert_eq!(materials[0].metallic, Some(1.0));
+		assert_eq!(materials[0].diffuse_texture.as_deref(), Some("textures/albedo.png"));
+		assert!(parse_mtl("Kd 1 1 1\n").is_err());
+	}
+}
@@ -0,0 +1,349 @@
+use crate::{VertexTrait, IndexTrait};
+use cgmath::{Vector3, InnerSpace, Zero};
+use std::collections::VecDeque;
+
+const CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+const OVERDRAW_CACHE_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CacheStatistics {
+	pub misses: u32,
+	pub acmr: f32,
+	pub atvr: f32,
+}
+
+pub fn analyze_vertex_cache<I: IndexTrait>(indices: &[I], vertex_count: usize, cache_size: usize) -> CacheStatistics {
+	let mut cache: VecDeque<u32> = VecDeque::with_capacity(cache_size + 1);
+	let mut used = vec![false; vertex_count];
+	let mut misses = 0;
+	for index in indices {
+		let index = index.to_u32();
+		used[index as usize] = true;
+		if !cache.contains(&index) {
+			misses += 1;
+			cache.push_back(index);
+			if cache.len() > cache_size {
+				cache.pop_front();
+			}
+		}
+	}
+	let triangle_count = indices.len() / 3;
+	let unique = used.iter().filter(|used| **used).count();
+	CacheStatistics {
+		misses,
+		acmr: if triangle_count > 0 { misses as f32 / triangle_count as f32 } else { 0.0 },
+		atvr: if unique > 0 { misses as f32 / unique as f32 } else { 0.0 },
+	}
+}
+
+pub fn optimize_vertex_cache<I: IndexTrait>(indices: &mut [I], vertex_count: usize) {
+	let triangle_count = indices.len() / 3;
+	let triangles: Vec<[u32; 3]> = indices
+		.chunks_exact(3)
+		.map(|triangle| [triangle[0].to_u32(), triangle[1].to_u32(), triangle[2].to_u32()])
+		.collect();
+
+	let mut offsets = vec![0usize; vertex_count + 1];
+	for triangle in &triangles {
+		for vertex in triangle {
+			offsets[*vertex as usize + 1] += 1;
+		}
+	}
+	for i in 0..vertex_count {
+		offsets[i + 1] += offsets[i];
+	}
+	let mut remaining: Vec<u32> = (0..vertex_count).map(|vertex| (offsets[vertex + 1] - offsets[vertex]) as u32).collect();
+	let mut adjacency = vec![0u32; offsets[vertex_count]];
+	let mut fill = offsets.clone();
+	for (index, triangle) in triangles.iter().enumerate() {
+		for vertex in triangle {
+			adjacency[fill[*vertex as usize]] = index as u32;
+			fill[*vertex as usize] += 1;
+		}
+	}
+
+	let mut cache_positions: Vec<i32> = vec![-1; vertex_count];
+	let mut vertex_scores: Vec<f32> = remaining.iter().map(|remaining| vertex_score(-1, *remaining)).collect();
+	let mut triangle_scores: Vec<f32> = triangles.iter()
+		.map(|triangle| triangle.iter().map(|vertex| vertex_scores[*vertex as usize]).sum())
+		.collect();
+	let mut emitted = vec![false; triangle_count];
+	let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+	let mut output: Vec<u32> = Vec::with_capacity(indices.len());
+	let mut cursor = 0;
+	let mut best: Option<usize> = None;
+
+	for _ in 0..triangle_count {
+		let triangle = match best {
+			Some(triangle) => triangle,
+			None => {
+				while emitted[cursor] {
+					cursor += 1;
+				}
+				let mut chosen = cursor;
+				for candidate in cursor..triangle_count {
+					if !emitted[candidate] && triangle_scores[candidate] > triangle_scores[chosen] {
+						chosen = candidate;
+					}
+				}
+				chosen
+			}
+		};
+		emitted[triangle] = true;
+		output.extend_from_slice(&triangles[triangle]);
+
+		let mut next_cache: Vec<u32> = triangles[triangle].to_vec();
+		next_cache.extend(cache.iter().filter(|vertex| !triangles[triangle].contains(vertex)));
+		for vertex in &triangles[triangle] {
+			let vertex = *vertex as usize;
+			remaining[vertex] -= 1;
+			let start = offsets[vertex];
+			let count = remaining[vertex] as usize;
+			if let Some(position) = adjacency[start..=start + count].iter().position(|adjacent| *adjacent == triangle as u32) {
+				adjacency.swap(start + position, start + count);
+			}
+		}
+
+		for (position, vertex) in next_cache.iter().enumerate() {
+			let vertex = *vertex as usize;
+			cache_positions[vertex] = if position < CACHE_SIZE { position as i32 } else { -1 };
+		}
+		for vertex in &next_cache {
+			let vertex = *vertex as usize;
+			let score = vertex_score(cache_positions[vertex], remaining[vertex]);
+			let delta = score - vertex_scores[vertex];
+			vertex_scores[vertex] = score;
+			let start = offsets[vertex];
+			for adjacent in &adjacency[start..start + remaining[vertex] as usize] {
+				triangle_scores[*adjacent as usize] += delta;
+			}
+		}
+		best = None;
+		let mut best_score = f32::MIN;
+		for vertex in next_cache.iter().take(CACHE_SIZE) {
+			let start = offsets[*vertex as usize];
+			for adjacent in &adjacency[start..start + remaining[*vertex as usize] as usize] {
+				if triangle_scores[*adjacent as usize] > best_score {
+					best_score = triangle_scores[*adjacent as usize];
+					best = Some(*adjacent as usize);
+				}
+			}
+		}
+		next_cache.truncate(CACHE_SIZE);
+		cache = next_cache;
+	}
+
+	for (index, value) in indices.iter_mut().zip(output) {
+		*index = I::from_u32(value);
+	}
+}
+
+pub fn optimize_overdraw<I: IndexTrait>(indices: &mut [I], positions: &[[f32; 3]], threshold: f32) {
+	let triangle_count = indices.len() / 3;
+	if triangle_count == 0 {
+		return;
+	}
+	let triangles: Vec<[u32; 3]> = indices
+		.chunks_exact(3)
+		.map(|triangle| [triangle[0].to_u32(), triangle[1].to_u32(), triangle[2].to_u32()])
+		.collect();
+
+	let mut clusters: Vec<usize> = Vec::new();
+	let mut cache: VecDeque<u32> = VecDeque::with_capacity(OVERDRAW_CACHE_SIZE + 1);
+	for (index, triangle) in triangles.iter().enumerate() {
+		if simulate_triangle(&mut cache, triangle) == 3 {
+			clusters.push(index);
+		}
+	}
+	if clusters.first() != Some(&0) {
+		clusters.insert(0, 0);
+	}
+
+	let mut soft_clusters: Vec<usize> = Vec::with_capacity(clusters.len());
+	for (k, start) in clusters.iter().enumerate() {
+		let end = clusters.get(k + 1).copied().unwrap_or(triangle_count);
+		cache.clear();
+		let misses: usize = triangles[*start..end].iter().map(|triangle| simulate_triangle(&mut cache, triangle)).sum();
+		let cluster_acmr = misses as f32 / (end - start) as f32;
+		soft_clusters.push(*start);
+		cache.clear();
+		let mut running = 0;
+		let mut begin = *start;
+		for (index, triangle) in triangles.iter().enumerate().take(end).skip(*start) {
+			running += simulate_triangle(&mut cache, triangle);
+			let count = index + 1 - begin;
+			if index + 1 < end && count > 1 && (running as f32 / count as f32) <= cluster_acmr * threshold {
+				soft_clusters.push(index + 1);
+				begin = index + 1;
+				running = 0;
+				cache.clear();
+			}
+		}
+	}
+
+	let mut centroid = Vector3::zero();
+	let mut total_area = 0.0;
+	for triangle in &triangles {
+		let (center, normal) = triangle_center_normal(positions, triangle);
+		let area = normal.magnitude();
+		centroid += center * area;
+		total_area += area;
+	}
+	if total_area > 0.0 {
+		centroid /= total_area;
+	}
+
+	let mut keyed: Vec<(f32, usize, usize)> = soft_clusters.iter().enumerate().map(|(k, start)| {
+		let end = soft_clusters.get(k + 1).copied().unwrap_or(triangle_count);
+		let mut center = Vector3::zero();
+		let mut normal = Vector3::zero();
+		let mut area = 0.0;
+		for triangle in &triangles[*start..end] {
+			let (triangle_center, triangle_normal) = triangle_center_normal(positions, triangle);
+			let triangle_area = triangle_normal.magnitude();
+			center += triangle_center * triangle_area;
+			normal += triangle_normal;
+			area += triangle_area;
+		}
+		if area > 0.0 {
+			center /= area;
+		}
+		let normal = if normal.magnitude2() > 0.0 { normal.normalize() } else { normal };
+		((center - centroid).dot(normal), *start, end)
+	}).collect();
+	keyed.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+	let output = keyed.iter().flat_map(|(_, start, end)| triangles[*start..*end].iter().flatten());
+	for (index, value) in indices.iter_mut().zip(output) {
+		*index = I::from_u32(*value);
+	}
+}
+
+pub fn optimize_vertex_fetch<V: VertexTrait, I: IndexTrait>(vertices: &mut Vec<V>, indices: &mut [I]) {
+	let mut remap: Vec<Option<u32>> = vec![None; vertices.len()];
+	let mut reordered: Vec<V> = Vec::with_capacity(vertices.len());
+	for index in indices.iter_mut() {
+		let old = index.to_u32() as usize;
+		let new = *remap[old].get_or_insert_with(|| {
+			reordered.push(vertices[old]);
+			(reordered.len() - 1) as u32
+		});
+		*index = I::from_u32(new);
+	}
+	*vertices = reordered;
+}
+
+fn vertex_score(cache_position: i32, remaining: u32) -> f32 {
+	if remaining == 0 {
+		return -1.0;
+	}
+	let mut score = 0.0;
+	if cache_position >= 0 {
+		score = if cache_position < 3 {
+			LAST_TRIANGLE_SCORE
+		} else {
+			(1.0 - (cache_position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(CACHE_DECAY_POWER)
+		};
+	}
+	score + VALENCE_BOOST_SCALE * (remaining as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+fn simulate_triangle(cache: &mut VecDeque<u32>, triangle: &[u32; 3]) -> usize {
+	let mut misses = 0;
+	for vertex in triangle {
+		if !cache.contains(vertex) {
+			misses += 1;
+			cache.push_back(*vertex);
+			if cache.len() > OVERDRAW_CACHE_SIZE {
+				cache.pop_front();
+			}
+		}
+	}
+	misses
+}
+
+fn triangle_center_normal(positions: &[[f32; 3]], triangle: &[u32; 3]) -> (Vector3<f32>, Vector3<f32>) {
+	let a = Vector3::from(positions[triangle[0] as usize]);
+	let b = Vector3::from(positions[triangle[1] as usize]);
+	let c = Vector3::from(positions[triangle[2] as usize]);
+	((a + b + c) / 3.0, (b - a).cross(c - a))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{AttributeTrait, MeshData, PbrVertex};
+	use crate::primitive::plane;
+
+	fn shuffled_grid() -> MeshData<PbrVertex, u32> {
+		let mut data: MeshData<PbrVertex, u32> = plane(1.0, 1.0, 16, 16);
+		let mut triangles: Vec<[u32; 3]> = data.indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]]).collect();
+		let mut state = 12345u32;
+		for i in (1..triangles.len()).rev() {
+			state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+			triangles.swap(i, (state >> 8) as usize % (i + 1));
+		}
+		data.indices = triangles.into_iter().flatten().collect();
+		data
+	}
+
+	fn canonical_triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+		let mut triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|triangle| {
+			let start = (0..3).min_by_key(|corner| triangle[*corner]).expect("triangle_empty");
+			[triangle[start], triangle[(start + 1) % 3], triangle[(start + 2) % 3]]
+		}).collect();
+		triangles.sort_unstable();
+		triangles
+	}
+
+	#[test]
+	fn analyzes_cache_misses() {
+		let statistics = analyze_vertex_cache(&[0u16, 1, 2, 0, 2, 3], 4, 32);
+		assert_eq!(statistics.misses, 4);
+		assert_eq!(statistics.acmr, 2.0);
+		assert_eq!(statistics.atvr, 1.0);
+	}
+
+	#[test]
+	fn vertex_cache_keeps_triangles_and_lowers_acmr() {
+		let mut data = shuffled_grid();
+		let before = data.analyze_vertex_cache(CACHE_SIZE);
+		let triangles = canonical_triangles(&data.indices);
+		data.optimize_vertex_cache();
+		let after = data.analyze_vertex_cache(CACHE_SIZE);
+		assert_eq!(canonical_triangles(&data.indices), triangles);
+		assert!(after.acmr < before.acmr);
+	}
+
+	#[test]
+	fn overdraw_keeps_triangles_within_threshold() {
+		let mut data = shuffled_grid();
+		data.optimize_vertex_cache();
+		let before = data.analyze_vertex_cache(CACHE_SIZE);
+		let triangles = canonical_triangles(&data.indices);
+		data.optimize_overdraw(1.05);
+		let after = data.analyze_vertex_cache(CACHE_SIZE);
+		assert_eq!(canonical_triangles(&data.indices), triangles);
+		assert!(after.acmr <= before.acmr * 1.05 + 1e-4);
+	}
+
+	#[test]
+	fn vertex_fetch_orders_by_first_use_and_drops_unused() {
+		let mut data = shuffled_grid();
+		data.vertices.push(PbrVertex::new([9.0; 3], [0.0; 3], [0.0; 2], [0.0; 4]));
+		let corners: Vec<[f32; 3]> = data.indices.iter().map(|index| data.vertices[*index as usize].get_position()).collect();
+		data.optimize_vertex_fetch();
+		assert_eq!(data.vertices.len(), 17 * 17);
+		let mut next = 0;
+		for index in &data.indices {
+			assert!(*index <= next);
+			next = next.max(*index + 1);
+		}
+		let reordered: Vec<[f32; 3]> = data.indices.iter().map(|index| data.vertices[*index as usize].get_position()).collect();
+		assert_eq!(reordered, corners);
+	}
+}
@@ -0,0 +1,371 @@
+use crate::{Bind, Material, Pipeline, PipelineTrait, Renderer, Texture, Uniform, VertexLayoutTrait, IndexTrait, CameraUniform, LightSet, ShadowMap, CubeMap, Environment};
+use crate::light::LightsUniform;
+use crate::shadow::{register_shadows, ShadowUniform};
+use crate::environment::register_environment;
+use crate::pipeline::{BlendMode, PipelineOptions};
+use crate::scene::ModelUniform;
+use crate::gltf::{GltfMaterial, GltfAlphaMode};
+use bytemuck::{Pod, Zeroable};
+use shaderc::Compiler;
+use std::any::TypeId;
+use std::mem::size_of;
+use std::rc::Rc;
+use wgpu::*;
+
+pub const PBR_FRAME_SLOT: u32 = 0;
+pub const PBR_MODEL_SLOT: u32 = 1;
+pub const PBR_TEXTURE_SLOT: u32 = 2;
+pub const PBR_PARAMETER_SLOT: u32 = 3;
+
+const PBR_VERTEX_SHADER: &str = include_str!("shaders/pbr_vertex.glsl");
+const PBR_FRAGMENT_SHADER: &str = include_str!("shaders/pbr_fragment.glsl");
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PbrParameters {
+	pub base_color_factor: [f32; 4],
+	pub emissive_factor: [f32; 4],
+	pub metallic_factor: f32,
+	pub roughness_factor: f32,
+	pub normal_scale: f32,
+	pub occlusion_strength: f32,
+	pub alpha_cutoff: f32,
+	pub _padding: [f32; 3],
+}
+
+unsafe impl Pod for PbrParameters {}
+
+unsafe impl Zeroable for PbrParameters {}
+
+impl Default for PbrParameters {
+	fn default() -> PbrParameters {
+		PbrParameters {
+			base_color_factor: [1.0; 4],
+			emissive_factor: [0.0; 4],
+			metallic_factor: 1.0,
+			roughness_factor: 1.0,
+			normal_scale: 1.0,
+			occlusion_strength: 1.0,
+			alpha_cutoff: 0.0,
+			_padding: [0.0; 3],
+		}
+	}
+}
+
+impl PbrParameters {
+	pub fn from_gltf(material: &GltfMaterial) -> PbrParameters {
+		let emissive = material.emissive_factor;
+		PbrParameters {
+			base_color_factor: material.base_color_factor,
+			emissive_factor: [emissive[0], emissive[1], emissive[2], 0.0],
+			metallic_factor: material.metallic_factor,
+			roughness_factor: material.roughness_factor,
+			normal_scale: material.normal_scale,
+			occlusion_strength: material.occlusion_strength,
+			alpha_cutoff: if material.alpha_mode == GltfAlphaMode::Mask { material.alpha_cutoff } else { 0.0 },
+			_padding: [0.0; 3],
+		}
+	}
+}
+
+pub struct PbrDefaults {
+	white: Texture,
+	white_linear: Texture,
+	flat_normal: Texture,
+	black_cube: CubeMap,
+}
+
+impl PbrDefaults {
+	pub fn new(renderer: &Renderer) -> PbrDefaults {
+		let black: &[u8] = &[0, 0, 0, 255];
+		PbrDefaults {
+			white: Texture::from_rgba(renderer, 1, 1, &[255, 255, 255, 255], true),
+			white_linear: Texture::from_rgba(renderer, 1, 1, &[255, 255, 255, 255], false),
+			flat_normal: Texture::from_rgba(renderer, 1, 1, &[128, 128, 255, 255], false),
+			black_cube: CubeMap::from_rgba(renderer, 1, &[black; 6], false),
+		}
+	}
+}
+
+pub struct PbrTextures {
+	bind_group: BindGroup,
+	_defaults: Rc<PbrDefaults>,
+}
+
+impl PbrTextures {
+	pub fn new(
+		renderer: &Renderer,
+		defaults: &Rc<PbrDefaults>,
+		base_color: Option<&Texture>,
+		metallic_roughness: Option<&Texture>,
+		normal: Option<&Texture>,
+		occlusion: Option<&Texture>,
+		emissive: Option<&Texture>,
+	) -> PbrTextures {
+		let textures = [
+			base_color.unwrap_or(&defaults.white),
+			metallic_roughness.unwrap_or(&defaults.white_linear),
+			normal.unwrap_or(&defaults.flat_normal),
+			occlusion.unwrap_or(&defaults.white_linear),
+			emissive.unwrap_or(&defaults.white),
+		];
+		let mut bindings = Vec::with_capacity(textures.len() * 2);
+		for (index, texture) in textures.iter().enumerate() {
+			bindings.push(Binding {
+				binding: index as u32 * 2,
+				resource: BindingResource::TextureView(texture.get_view()),
+			});
+			bindings.push(Binding {
+				binding: index as u32 * 2 + 1,
+				resource: BindingResource::Sampler(texture.get_sampler()),
+			});
+		}
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("pbr_textures_type_not_registered"),
+			bindings: &bindings,
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		PbrTextures {
+			bind_group,
+			_defaults: defaults.clone(),
+		}
+	}
+
+	pub fn from_gltf(renderer: &Renderer, defaults: &Rc<PbrDefaults>, material: &GltfMaterial, textures: &[Texture]) -> PbrTextures {
+		let get = |index: Option<usize>| index.map(|index| textures.get(index).expect("gltf_texture_not_found"));
+		PbrTextures::new(
+			renderer,
+			defaults,
+			get(material.base_color_texture),
+			get(material.metallic_roughness_texture),
+			get(material.normal_texture),
+			get(material.occlusion_texture),
+			get(material.emissive_texture),
+		)
+	}
+}
+
+impl Bind for PbrTextures {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let mut bindings = Vec::with_capacity(10);
+		for index in 0..5 {
+			bindings.push(BindGroupLayoutEntry {
+				binding: index * 2,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::SampledTexture {
+					multisampled: false,
+					dimension: TextureViewDimension::D2,
+					component_type: TextureComponentType::Float,
+				},
+			});
+			bindings.push(BindGroupLayoutEntry {
+				binding: index * 2 + 1,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::Sampler {
+					comparison: false,
+				},
+			});
+		}
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &bindings,
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+pub struct PbrFrame {
+	bind_group: BindGroup,
+	_defaults: Rc<PbrDefaults>,
+}
+
+impl PbrFrame {
+	pub fn new(
+		renderer: &Renderer,
+		defaults: &Rc<PbrDefaults>,
+		camera: &Uniform<CameraUniform>,
+		lights: &LightSet,
+		shadows: &ShadowMap,
+		environment: Option<&Environment>,
+	) -> PbrFrame {
+		let irradiance = environment.map_or(&defaults.black_cube, |environment| environment.get_irradiance());
+		let specular = environment.map_or(&defaults.black_cube, |environment| environment.get_specular());
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("pbr_frame_type_not_registered"),
+			bindings: &[
+				Binding {
+					binding: 0,
+					resource: BindingResource::Buffer {
+						buffer: camera.get_buffer(),
+						range: 0..size_of::<CameraUniform>() as BufferAddress,
+					},
+				},
+				Binding {
+					binding: 1,
+					resource: BindingResource::Buffer {
+						buffer: lights.get_buffer(),
+						range: 0..size_of::<LightsUniform>() as BufferAddress,
+					},
+				},
+				Binding {
+					binding: 2,
+					resource: BindingResource::TextureView(shadows.get_view()),
+				},
+				Binding {
+					binding: 3,
+					resource: BindingResource::Sampler(shadows.get_sampler()),
+				},
+				Binding {
+					binding: 4,
+					resource: BindingResource::Buffer {
+						buffer: shadows.get_buffer(),
+						range: 0..size_of::<ShadowUniform>() as BufferAddress,
+					},
+				},
+				Binding {
+					binding: 5,
+					resource: BindingResource::TextureView(irradiance.get_view()),
+				},
+				Binding {
+					binding: 6,
+					resource: BindingResource::Sampler(irradiance.get_sampler()),
+				},
+				Binding {
+					binding: 7,
+					resource: BindingResource::TextureView(specular.get_view()),
+				},
+				Binding {
+					binding: 8,
+					resource: BindingResource::Sampler(specular.get_sampler()),
+				},
+			],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		PbrFrame {
+			bind_group,
+			_defaults: defaults.clone(),
+		}
+	}
+}
+
+impl Bind for PbrFrame {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let mut bindings = vec![
+			BindGroupLayoutEntry {
+				binding: 0,
+				visibility: ShaderStage::VERTEX | ShaderStage::FRAGMENT,
+				ty: BindingType::UniformBuffer { dynamic: false },
+			},
+			BindGroupLayoutEntry {
+				binding: 1,
+				visibility: ShaderStage::VERTEX | ShaderStage::FRAGMENT,
+				ty: BindingType::UniformBuffer { dynamic: false },
+			},
+			BindGroupLayoutEntry {
+				binding: 2,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::SampledTexture {
+					multisampled: false,
+					dimension: TextureViewDimension::D2Array,
+					component_type: TextureComponentType::Float,
+				},
+			},
+			BindGroupLayoutEntry {
+				binding: 3,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::Sampler {
+					comparison: true,
+				},
+			},
+			BindGroupLayoutEntry {
+				binding: 4,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::UniformBuffer { dynamic: false },
+			},
+		];
+		for index in 0..2 {
+			bindings.push(BindGroupLayoutEntry {
+				binding: 5 + index * 2,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::SampledTexture {
+					multisampled: false,
+					dimension: TextureViewDimension::Cube,
+					component_type: TextureComponentType::Float,
+				},
+			});
+			bindings.push(BindGroupLayoutEntry {
+				binding: 5 + index * 2 + 1,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::Sampler {
+					comparison: false,
+				},
+			});
+		}
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &bindings,
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+pub fn get_pbr_binds() -> [TypeId; 4] {
+	[
+		TypeId::of::<PbrFrame>(),
+		TypeId::of::<Uniform<ModelUniform>>(),
+		TypeId::of::<PbrTextures>(),
+		TypeId::of::<Uniform<PbrParameters>>(),
+	]
+}
+
+pub fn register_pbr(renderer: &mut Renderer) {
+	register_shadows(renderer);
+	register_environment(renderer);
+	if renderer.get_bind_group_layout::<PbrFrame>().is_none() {
+		renderer.register_bind_group_layout::<PbrFrame>();
+	}
+	if renderer.get_bind_group_layout::<PbrTextures>().is_none() {
+		renderer.register_bind_group_layout::<PbrTextures>();
+	}
+	if renderer.get_bind_group_layout::<Uniform<PbrParameters>>().is_none() {
+		renderer.register_bind_group_layout::<Uniform<PbrParameters>>();
+	}
+	if renderer.get_bind_group_layout::<LightSet>().is_none() {
+		renderer.register_bind_group_layout::<LightSet>();
+	}
+	let binds = get_pbr_binds();
+	if renderer.get_pipeline_layout(&binds).is_none() {
+		renderer.register_pipeline_layout(&binds);
+	}
+}
+
+pub fn get_pbr_options(material: &GltfMaterial) -> PipelineOptions {
+	PipelineOptions {
+		cull_mode: if material.double_sided { CullMode::None } else { CullMode::Back },
+		depth_write: material.alpha_mode != GltfAlphaMode::Blend,
+		blend: if material.alpha_mode == GltfAlphaMode::Blend { BlendMode::Alpha } else { BlendMode::Replace },
+		..PipelineOptions::with_depth()
+	}
+}
+
+pub fn create_pbr_pipeline<V: VertexLayoutTrait, I: IndexTrait>(renderer: &Renderer, compiler: &mut Compiler, options: &PipelineOptions) -> Pipeline<V, I> {
+	Pipeline::new_with_options(renderer, compiler, PBR_VERTEX_SHADER, PBR_FRAGMENT_SHADER, &get_pbr_binds(), options)
+}
+
+pub fn create_pbr_material(renderer: &Renderer, pipeline: Rc<dyn PipelineTrait>, textures: Rc<PbrTextures>, parameters: PbrParameters) -> Material<PbrParameters> {
+	let mut material = Material::new(renderer, pipeline, parameters, PBR_PARAMETER_SLOT);
+	material.set_bind(textures, PBR_TEXTURE_SLOT);
+	material
+}
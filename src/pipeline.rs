@@ -1,24 +1,112 @@
-use crate::{Renderer, VertexTrait, IndexTrait};
+use crate::{Renderer, VertexLayoutTrait, IndexTrait};
+use crate::renderer::DEPTH_FORMAT;
+use crate::shader::resolve_includes;
 use shaderc::{Compiler, ShaderKind};
 use wgpu::*;
 use std::io::Cursor;
 use std::marker::PhantomData;
 use std::any::TypeId;
+use std::collections::HashSet;
 
 pub trait PipelineTrait {
 	fn get_render_pipeline(&self) -> &RenderPipeline;
 }
 
-pub struct Pipeline<V: VertexTrait, I: IndexTrait> {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+	Replace,
+	Alpha,
+	Additive,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PipelineOptions {
+	pub cull_mode: CullMode,
+	pub depth_test: bool,
+	pub depth_write: bool,
+	pub blend: BlendMode,
+	pub color_format: Option<TextureFormat>,
+	pub depth_bias: i32,
+	pub depth_bias_slope_scale: f32,
+}
+
+impl Default for PipelineOptions {
+	fn default() -> PipelineOptions {
+		PipelineOptions {
+			cull_mode: CullMode::Back,
+			depth_test: false,
+			depth_write: false,
+			blend: BlendMode::Alpha,
+			color_format: Some(TextureFormat::Bgra8UnormSrgb),
+			depth_bias: 0,
+			depth_bias_slope_scale: 0.0,
+		}
+	}
+}
+
+impl PipelineOptions {
+	pub fn with_depth() -> PipelineOptions {
+		PipelineOptions {
+			depth_test: true,
+			depth_write: true,
+			..PipelineOptions::default()
+		}
+	}
+
+	pub fn depth_only(depth_bias: i32, depth_bias_slope_scale: f32) -> PipelineOptions {
+		PipelineOptions {
+			depth_test: true,
+			depth_write: true,
+			blend: BlendMode::Replace,
+			color_format: None,
+			depth_bias,
+			depth_bias_slope_scale,
+			..PipelineOptions::default()
+		}
+	}
+}
+
+pub struct Pipeline<V: VertexLayoutTrait, I: IndexTrait> {
 	render_pipeline: RenderPipeline,
 	vertex_marker: PhantomData<V>,
 	index_marker: PhantomData<I>,
 }
 
-impl<V: VertexTrait, I: IndexTrait> Pipeline<V, I> {
+impl<V: VertexLayoutTrait, I: IndexTrait> Pipeline<V, I> {
 	pub fn new(renderer: &Renderer, compiler: &mut Compiler, vertex_code: &str, fragment_code: &str, binds: &[TypeId]) -> Pipeline<V, I> {
+		Pipeline::new_with_options(renderer, compiler, vertex_code, fragment_code, binds, &PipelineOptions {
+			color_format: Some(renderer.get_format()),
+			..PipelineOptions::default()
+		})
+	}
+
+	pub fn new_with_options(renderer: &Renderer, compiler: &mut Compiler, vertex_code: &str, fragment_code: &str, binds: &[TypeId], options: &PipelineOptions) -> Pipeline<V, I> {
 		let vertex_mod = compile(renderer, compiler, vertex_code, ShaderKind::Vertex);
 		let fragment_mod = compile(renderer, compiler, fragment_code, ShaderKind::Fragment);
+		let vertex_buffers = V::descriptors();
+		check_shader_locations(&vertex_buffers);
+		let color_states: Vec<ColorStateDescriptor> = options.color_format.iter().map(|&format| ColorStateDescriptor {
+			format,
+			color_blend: match options.blend {
+				BlendMode::Replace => BlendDescriptor::REPLACE,
+				BlendMode::Alpha => BlendDescriptor {
+					src_factor: BlendFactor::SrcAlpha,
+					dst_factor: BlendFactor::OneMinusSrcAlpha,
+					operation: BlendOperation::Add,
+				},
+				BlendMode::Additive => BlendDescriptor {
+					src_factor: BlendFactor::One,
+					dst_factor: BlendFactor::One,
+					operation: BlendOperation::Add,
+				},
+			},
+			alpha_blend: BlendDescriptor {
+				src_factor: BlendFactor::One,
+				dst_factor: BlendFactor::Zero,
+				operation: BlendOperation::Add,
+			},
+			write_mask: ColorWrite::ALL,
+		}).collect();
 		let pipeline_desc = RenderPipelineDescriptor {
 			layout: renderer.get_pipeline_layout(binds).expect("pipeline_layout_not_registered"),
 			vertex_stage: ProgrammableStageDescriptor {
@@ -30,33 +118,30 @@ impl<V: VertexTrait, I: IndexTrait> Pipeline<V, I> {
 				entry_point: "main",
 			}),
 			rasterization_state: Some(RasterizationStateDescriptor {
-				cull_mode: CullMode::Back,
+				cull_mode: options.cull_mode,
 				front_face: FrontFace::Ccw,
-				depth_bias: 0,
-				depth_bias_slope_scale: 0.0,
+				depth_bias: options.depth_bias,
+				depth_bias_slope_scale: options.depth_bias_slope_scale,
 				depth_bias_clamp: 0.0,
 			}),
-			color_states: &[
-				ColorStateDescriptor {
-					format: TextureFormat::Bgra8UnormSrgb,
-					color_blend: BlendDescriptor {
-						src_factor: BlendFactor::SrcAlpha,
-						dst_factor: BlendFactor::OneMinusSrcAlpha,
-						operation: BlendOperation::Add,
-					},
-					alpha_blend: BlendDescriptor {
-						src_factor: BlendFactor::One,
-						dst_factor: BlendFactor::Zero,
-						operation: BlendOperation::Add,
-					},
-					write_mask: ColorWrite::ALL,
-				}
-			],
+			color_states: &color_states,
 			primitive_topology: PrimitiveTopology::TriangleList,
-			depth_stencil_state: None,
+			depth_stencil_state: if options.depth_test {
+				Some(DepthStencilStateDescriptor {
+					format: DEPTH_FORMAT,
+					depth_write_enabled: options.depth_write,
+					depth_compare: CompareFunction::LessEqual,
+					stencil_front: StencilStateFaceDescriptor::IGNORE,
+					stencil_back: StencilStateFaceDescriptor::IGNORE,
+					stencil_read_mask: 0,
+					stencil_write_mask: 0,
+				})
+			} else {
+				None
+			},
 			vertex_state: VertexStateDescriptor {
 				index_format: I::index_format(),
-				vertex_buffers: &[V::descriptor()],
+				vertex_buffers: vertex_buffers.as_slice(),
 			},
 			sample_count: 1,
 			sample_mask: !0,
@@ -71,14 +156,26 @@ impl<V: VertexTrait, I: IndexTrait> Pipeline<V, I> {
 	}
 }
 
-impl<V: VertexTrait, I: IndexTrait> PipelineTrait for Pipeline<V, I> {
+impl<V: VertexLayoutTrait, I: IndexTrait> PipelineTrait for Pipeline<V, I> {
 	fn get_render_pipeline(&self) -> &RenderPipeline {
 		&self.render_pipeline
 	}
 }
 
 fn compile(renderer: &Renderer, compiler: &mut Compiler, code: &str, kind: ShaderKind) -> ShaderModule {
-	let output = compiler.compile_into_spirv(code, kind, "", "main", None).expect("shader_compilation_failed");
+	let code = resolve_includes(code);
+	let output = compiler.compile_into_spirv(&code, kind, "", "main", None).expect("shader_compilation_failed");
 	let data = read_spirv(Cursor::new(output.as_binary_u8())).expect("shader_compilation_failed");
 	renderer.get_device().create_shader_module(data.as_slice())
+}
+
+fn check_shader_locations(vertex_buffers: &[VertexBufferDescriptor]) {
+	let mut locations = HashSet::new();
+	for vertex_buffer in vertex_buffers {
+		for attribute in vertex_buffer.attributes {
+			if !locations.insert(attribute.shader_location) {
+				panic!("vertex_shader_location_collision");
+			}
+		}
+	}
 }
\ No newline at end of file
@@ -0,0 +1,446 @@
+use crate::{AttributeTrait, IndexTrait, MeshData, LoadError};
+use std::convert::TryInto;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlyFormat {
+	Ascii,
+	BinaryLittleEndian,
+	BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ScalarType {
+	Char,
+	UChar,
+	Short,
+	UShort,
+	Int,
+	UInt,
+	Float,
+	Double,
+}
+
+impl ScalarType {
+	fn parse(name: &str) -> Option<ScalarType> {
+		match name {
+			"char" | "int8" => Some(ScalarType::Char),
+			"uchar" | "uint8" => Some(ScalarType::UChar),
+			"short" | "int16" => Some(ScalarType::Short),
+			"ushort" | "uint16" => Some(ScalarType::UShort),
+			"int" | "int32" => Some(ScalarType::Int),
+			"uint" | "uint32" => Some(ScalarType::UInt),
+			"float" | "float32" => Some(ScalarType::Float),
+			"double" | "float64" => Some(ScalarType::Double),
+			_ => None,
+		}
+	}
+
+	fn size(self) -> usize {
+		match self {
+			ScalarType::Char | ScalarType::UChar => 1,
+			ScalarType::Short | ScalarType::UShort => 2,
+			ScalarType::Int | ScalarType::UInt | ScalarType::Float => 4,
+			ScalarType::Double => 8,
+		}
+	}
+
+	fn normalize(self, value: f64) -> f32 {
+		match self {
+			ScalarType::UChar => (value / 255.0) as f32,
+			ScalarType::UShort => (value / 65535.0) as f32,
+			_ => value as f32,
+		}
+	}
+}
+
+enum PropertyType {
+	Scalar(ScalarType),
+	List(ScalarType, ScalarType),
+}
+
+struct Property {
+	name: String,
+	kind: PropertyType,
+}
+
+struct Element {
+	name: String,
+	count: usize,
+	properties: Vec<Property>,
+}
+
+enum Body<'a> {
+	Ascii { lines: std::iter::Enumerate<std::str::Lines<'a>>, offset: usize },
+	Binary { data: &'a [u8], position: usize, big_endian: bool },
+}
+
+impl<'a> Body<'a> {
+	fn read_element(&mut self, element: &Element) -> Result<Vec<Vec<f64>>, LoadError> {
+		match self {
+			Body::Ascii { lines, offset } => {
+				let (number, line) = loop {
+					match lines.next() {
+						Some((_, line)) if line.trim().is_empty() => continue,
+						Some((number, line)) => break (number + *offset + 1, line),
+						None => return Err(LoadError::invalid("unexpected_end_of_data")),
+					}
+				};
+				let values = line
+					.split_whitespace()
+					.map(|token| token.parse::<f64>().map_err(|_| LoadError::parse(number, "invalid_number")))
+					.collect::<Result<Vec<f64>, LoadError>>()?;
+				let mut values = values.into_iter();
+				let mut properties = Vec::with_capacity(element.properties.len());
+				for property in &element.properties {
+					match property.kind {
+						PropertyType::Scalar(_) => {
+							properties.push(vec![values.next().ok_or_else(|| LoadError::parse(number, "missing_property_value"))?]);
+						}
+						PropertyType::List(_, _) => {
+							let count = values.next().ok_or_else(|| LoadError::parse(number, "missing_list_count"))? as usize;
+							let list: Vec<f64> = values.by_ref().take(count).collect();
+							if list.len() != count {
+								return Err(LoadError::parse(number, "missing_list_value"));
+							}
+							properties.push(list);
+						}
+					}
+				}
+				if values.next().is_some() {
+					return Err(LoadError::parse(number, "unexpected_property_value"));
+				}
+				Ok(properties)
+			}
+			Body::Binary { data, position, big_endian } => {
+				let mut properties = Vec::with_capacity(element.properties.len());
+				for property in &element.properties {
+					match property.kind {
+						PropertyType::Scalar(kind) => {
+							properties.push(vec![read_scalar(data, position, kind, *big_endian)?]);
+						}
+						PropertyType::List(count_kind, item_kind) => {
+							let count = read_scalar(data, position, count_kind, *big_endian)? as usize;
+							let mut list = Vec::with_capacity(count.min((data.len() - *position) / item_kind.size()));
+							for _ in 0..count {
+								list.push(read_scalar(data, position, item_kind, *big_endian)?);
+							}
+							properties.push(list);
+						}
+					}
+				}
+				Ok(properties)
+			}
+		}
+	}
+}
+
+pub fn read_ply<V: AttributeTrait>(data: &[u8], tolerance: Option<f32>) -> Result<MeshData<V, u32>, LoadError> {
+	let header_end = find_header_end(data).ok_or_else(|| LoadError::invalid("missing_end_header"))?;
+	let header = std::str::from_utf8(&data[..header_end]).map_err(|_| LoadError::invalid("invalid_header_encoding"))?;
+	let (format, elements, header_lines) = parse_header(header)?;
+	let mut body = match format {
+		PlyFormat::Ascii => Body::Ascii {
+			lines: std::str::from_utf8(&data[header_end..]).map_err(|_| LoadError::invalid("invalid_utf8"))?.lines().enumerate(),
+			offset: header_lines,
+		},
+		PlyFormat::BinaryLittleEndian => Body::Binary { data: &data[header_end..], position: 0, big_endian: false },
+		PlyFormat::BinaryBigEndian => Body::Binary { data: &data[header_end..], position: 0, big_endian: true },
+	};
+
+	let mut vertices: Vec<V> = Vec::new();
+	let mut indices: Vec<u32> = Vec::new();
+	for element in &elements {
+		let find = |names: &[&str]| element.properties.iter().position(|property| names.contains(&property.name.as_str()));
+		let scalar_type = |index: usize| match element.properties[index].kind {
+			PropertyType::Scalar(kind) => kind,
+			PropertyType::List(_, kind) => kind,
+		};
+		match element.name.as_str() {
+			"vertex" => {
+				let (x, y, z) = match (find(&["x"]), find(&["y"]), find(&["z"])) {
+					(Some(x), Some(y), Some(z)) => (x, y, z),
+					_ => return Err(LoadError::invalid("missing_vertex_position")),
+				};
+				let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+				let uv = [find(&["s", "u", "texture_u", "texture_s"]), find(&["t", "v", "texture_v", "texture_t"])];
+				let color = [find(&["red", "diffuse_red"]), find(&["green", "diffuse_green"]), find(&["blue", "diffuse_blue"]), find(&["alpha"])];
+				for _ in 0..element.count {
+					let values = body.read_element(element)?;
+					let mut vertex = V::zeroed();
+					vertex.set_position([values[x][0] as f32, values[y][0] as f32, values[z][0] as f32]);
+					if let [Some(nx), Some(ny), Some(nz)] = normal {
+						vertex.set_normal([values[nx][0] as f32, values[ny][0] as f32, values[nz][0] as f32]);
+					}
+					if let [Some(u), Some(v)] = uv {
+						vertex.set_uv([values[u][0] as f32, values[v][0] as f32]);
+					}
+					match color {
+						[Some(r), Some(g), Some(b), alpha] => {
+							let alpha = alpha.map_or(1.0, |a| scalar_type(a).normalize(values[a][0]));
+							vertex.set_color([scalar_type(r).normalize(values[r][0]), scalar_type(g).normalize(values[g][0]), scalar_type(b).normalize(values[b][0]), alpha]);
+						}
+						_ => vertex.set_color([1.0; 4]),
+					}
+					vertices.push(vertex);
+				}
+			}
+			"face" => {
+				let list = find(&["vertex_indices", "vertex_index"]).ok_or_else(|| LoadError::invalid("missing_vertex_indices"))?;
+				for _ in 0..element.count {
+					let values = body.read_element(element)?;
+					let face = values[list].iter().map(|value| to_index(*value)).collect::<Result<Vec<u32>, LoadError>>()?;
+					for i in 2..face.len() {
+						indices.extend_from_slice(&[face[0], face[i - 1], face[i]]);
+					}
+				}
+			}
+			_ => {
+				for _ in 0..element.count {
+					body.read_element(element)?;
+				}
+			}
+		}
+	}
+	if indices.iter().any(|index| *index as usize >= vertices.len()) {
+		return Err(LoadError::invalid("index_out_of_range"));
+	}
+	let mut data = MeshData::new(vertices, indices);
+	if let Some(tolerance) = tolerance {
+		data.weld(tolerance);
+	}
+	Ok(data)
+}
+
+pub fn write_ply<V: AttributeTrait, I: IndexTrait>(vertices: &[V], indices: &[I], format: PlyFormat) -> Vec<u8> {
+	let has_normal = vertices.first().and_then(|vertex| vertex.get_normal()).is_some();
+	let has_uv = vertices.first().and_then(|vertex| vertex.get_uv()).is_some();
+	let has_color = vertices.first().and_then(|vertex| vertex.get_color()).is_some();
+	let mut header = String::from("ply\n");
+	header.push_str(match format {
+		PlyFormat::Ascii => "format ascii 1.0\n",
+		PlyFormat::BinaryLittleEndian => "format binary_little_endian 1.0\n",
+		PlyFormat::BinaryBigEndian => "format binary_big_endian 1.0\n",
+	});
+	header.push_str("comment meshed\n");
+	header.push_str(&format!("element vertex {}\n", vertices.len()));
+	header.push_str("property float x\nproperty float y\nproperty float z\n");
+	if has_normal {
+		header.push_str("property float nx\nproperty float ny\nproperty float nz\n");
+	}
+	if has_uv {
+		header.push_str("property float s\nproperty float t\n");
+	}
+	if has_color {
+		header.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\nproperty uchar alpha\n");
+	}
+	header.push_str(&format!("element face {}\n", indices.len() / 3));
+	header.push_str("property list uchar uint vertex_indices\n");
+	header.push_str("end_header\n");
+
+	let mut data = header.into_bytes();
+	for vertex in vertices {
+		let mut floats: Vec<f32> = vertex.get_position().to_vec();
+		if has_normal {
+			floats.extend_from_slice(&vertex.get_normal().unwrap_or([0.0; 3]));
+		}
+		if has_uv {
+			floats.extend_from_slice(&vertex.get_uv().unwrap_or([0.0; 2]));
+		}
+		let color: Vec<u8> = if has_color {
+			vertex.get_color().unwrap_or([1.0; 4]).iter().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8).collect()
+		} else {
+			Vec::new()
+		};
+		match format {
+			PlyFormat::Ascii => {
+				let mut line: Vec<String> = floats.iter().map(|value| value.to_string()).collect();
+				line.extend(color.iter().map(|value| value.to_string()));
+				data.extend_from_slice(line.join(" ").as_bytes());
+				data.push(b'\n');
+			}
+			PlyFormat::BinaryLittleEndian => {
+				floats.iter().for_each(|value| data.extend_from_slice(&value.to_le_bytes()));
+				data.extend_from_slice(&color);
+			}
+			PlyFormat::BinaryBigEndian => {
+				floats.iter().for_each(|value| data.extend_from_slice(&value.to_be_bytes()));
+				data.extend_from_slice(&color);
+			}
+		}
+	}
+	for triangle in indices.chunks_exact(3) {
+		let triangle = [triangle[0].to_u32(), triangle[1].to_u32(), triangle[2].to_u32()];
+		match format {
+			PlyFormat::Ascii => {
+				data.extend_from_slice(format!("3 {} {} {}\n", triangle[0], triangle[1], triangle[2]).as_bytes());
+			}
+			PlyFormat::BinaryLittleEndian => {
+				data.push(3);
+				triangle.iter().for_each(|index| data.extend_from_slice(&index.to_le_bytes()));
+			}
+			PlyFormat::BinaryBigEndian => {
+				data.push(3);
+				triangle.iter().for_each(|index| data.extend_from_slice(&index.to_be_bytes()));
+			}
+		}
+	}
+	data
+}
+
+fn find_header_end(data: &[u8]) -> Option<usize> {
+	let mut start = 0;
+	while start < data.len() {
+		let end = start + data[start..].iter().position(|byte| *byte == b'\n')?;
+		let line = &data[start..end];
+		if line.strip_suffix(b"\r").unwrap_or(line) == b"end_header" {
+			return Some(end + 1);
+		}
+		start = end + 1;
+	}
+	None
+}
+
+fn to_index(value: f64) -> Result<u32, LoadError> {
+	if value >= 0.0 && value <= u32::MAX as f64 && value.fract() == 0.0 {
+		Ok(value as u32)
+	} else {
+		Err(LoadError::invalid("invalid_face_index"))
+	}
+}
+
+fn parse_header(header: &str) -> Result<(PlyFormat, Vec<Element>, usize), LoadError> {
+	let mut format = None;
+	let mut elements: Vec<Element> = Vec::new();
+	let mut line_count = 0;
+	for (number, line) in header.lines().enumerate() {
+		let number = number + 1;
+		line_count = number;
+		let tokens: Vec<&str> = line.split_whitespace().collect();
+		match tokens.first().copied() {
+			None => (),
+			Some("ply") if number == 1 => (),
+			Some(_) if number == 1 => return Err(LoadError::parse(number, "missing_ply_magic")),
+			Some("format") => {
+				format = match tokens.get(1).copied() {
+					Some("ascii") => Some(PlyFormat::Ascii),
+					Some("binary_little_endian") => Some(PlyFormat::BinaryLittleEndian),
+					Some("binary_big_endian") => Some(PlyFormat::BinaryBigEndian),
+					_ => return Err(LoadError::parse(number, "unknown_format")),
+				};
+			}
+			Some("comment") | Some("obj_info") | Some("end_header") => (),
+			Some("element") => {
+				if tokens.len() != 3 {
+					return Err(LoadError::parse(number, "invalid_element"));
+				}
+				elements.push(Element {
+					name: String::from(tokens[1]),
+					count: tokens[2].parse().map_err(|_| LoadError::parse(number, "invalid_element_count"))?,
+					properties: Vec::new(),
+				});
+			}
+			Some("property") => {
+				let element = elements.last_mut().ok_or_else(|| LoadError::parse(number, "property_before_element"))?;
+				let kind = if tokens.get(1) == Some(&"list") {
+					if tokens.len() != 5 {
+						return Err(LoadError::parse(number, "invalid_property"));
+					}
+					PropertyType::List(
+						ScalarType::parse(tokens[2]).ok_or_else(|| LoadError::parse(number, "unknown_property_type"))?,
+						ScalarType::parse(tokens[3]).ok_or_else(|| LoadError::parse(number, "unknown_property_type"))?,
+					)
+				} else {
+					if tokens.len() != 3 {
+						return Err(LoadError::parse(number, "invalid_property"));
+					}
+					PropertyType::Scalar(ScalarType::parse(tokens[1]).ok_or_else(|| LoadError::parse(number, "unknown_property_type"))?)
+				};
+				element.properties.push(Property {
+					name: String::from(tokens[tokens.len() - 1]),
+					kind,
+				});
+			}
+			Some(_) => return Err(LoadError::parse(number, "unknown_keyword")),
+		}
+	}
+	let format = format.ok_or_else(|| LoadError::invalid("missing_format"))?;
+	Ok((format, elements, line_count))
+}
+
+fn read_scalar(data: &[u8], position: &mut usize, kind: ScalarType, big_endian: bool) -> Result<f64, LoadError> {
+	let size = kind.size();
+	if *position + size > data.len() {
+		return Err(LoadError::invalid("unexpected_end_of_data"));
+	}
+	let bytes = &data[*position..*position + size];
+	*position += size;
+	macro_rules! read {
+		($type:ty) => {{
+			let bytes = bytes.try_into().expect("slice_size_mismatch");
+			if big_endian { <$type>::from_be_bytes(bytes) } else { <$type>::from_le_bytes(bytes) }
+		}};
+	}
+	Ok(match kind {
+		ScalarType::Char => bytes[0] as i8 as f64,
+		ScalarType::UChar => bytes[0] as f64,
+		ScalarType::Short => read!(i16) as f64,
+		ScalarType::UShort => read!(u16) as f64,
+		ScalarType::Int => read!(i32) as f64,
+		ScalarType::UInt => read!(u32) as f64,
+		ScalarType::Float => read!(f32) as f64,
+		ScalarType::Double => read!(f64),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PbrVertex;
+
+	fn quad() -> (Vec<PbrVertex>, Vec<u32>) {
+		let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]].iter()
+			.map(|position| PbrVertex::new(*position, [0.0, 0.0, 1.0], [position[0], position[1]], [1.0, 0.0, 0.0, 1.0]))
+			.collect();
+		(vertices, vec![0, 1, 2, 0, 2, 3])
+	}
+
+	#[test]
+	fn round_trips_every_format() {
+		let (vertices, indices) = quad();
+		for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian, PlyFormat::BinaryBigEndian].iter() {
+			let data: MeshData<PbrVertex, u32> = read_ply(&write_ply(&vertices, &indices, *format), None).expect("ply_reading_failed");
+			assert_eq!(data.indices, indices);
+			for (read, written) in data.vertices.iter().zip(&vertices) {
+				assert_eq!(read.get_position(), written.get_position());
+				assert_eq!(read.get_normal(), written.get_normal());
+				assert_eq!(read.get_uv(), written.get_uv());
+			}
+		}
+	}
+
+	#[test]
+	fn ignores_end_header_inside_comments() {
+		let source = "ply\nformat ascii 1.0\ncomment see end_header\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+		let data: MeshData<PbrVertex, u32> = read_ply(source.as_bytes(), None).expect("ply_reading_failed");
+		assert_eq!(data.vertices.len(), 3);
+		assert_eq!(data.indices, vec![0, 1, 2]);
+		assert!(read_ply::<PbrVertex>(b"ply\nformat ascii 1.0\ncomment end_header\n", None).is_err());
+	}
+
+	#[test]
+	fn rejects_invalid_face_indices() {
+		let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n";
+		for face in ["3 0 1 -1\n", "3 0 1 1.5\n", "3 0 1 3\n"].iter() {
+			let source = format!("{}{}", header, face);
+			assert!(read_ply::<PbrVertex>(source.as_bytes(), None).is_err());
+		}
+	}
+
+	#[test]
+	fn rejects_hostile_list_counts() {
+		let mut data = b"ply\nformat binary_little_endian 1.0\nelement face 1\nproperty list uint uint vertex_indices\nend_header\n".to_vec();
+		data.extend_from_slice(&u32::MAX.to_le_bytes());
+		data.extend_from_slice(&0u32.to_le_bytes());
+		assert!(read_ply::<PbrVertex>(&data, None).is_err());
+	}
+}
@@ -0,0 +1,400 @@
+use crate::{Bind, Bloom, Command, Mesh, Pipeline, PositionVertex, Renderer, Uniform};
+use crate::bloom::get_bloom_binds;
+use crate::cubemap::create_fullscreen_triangle;
+use crate::pipeline::{BlendMode, PipelineOptions};
+use bytemuck::{Pod, Zeroable};
+use shaderc::Compiler;
+use std::any::TypeId;
+use wgpu::*;
+
+pub const HDR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+const POST_VERTEX_SHADER: &str = include_str!("shaders/post_vertex.glsl");
+const POST_COPY_SHADER: &str = include_str!("shaders/post_copy.glsl");
+const POST_TONEMAP_SHADER: &str = include_str!("shaders/post_tonemap.glsl");
+const POST_GAMMA_SHADER: &str = include_str!("shaders/post_gamma.glsl");
+const POST_VIGNETTE_SHADER: &str = include_str!("shaders/post_vignette.glsl");
+const POST_FXAA_SHADER: &str = include_str!("shaders/post_fxaa.glsl");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tonemapper {
+	Reinhard,
+	AcesFilmic,
+	Uncharted2,
+}
+
+impl Tonemapper {
+	fn get_index(self) -> f32 {
+		match self {
+			Tonemapper::Reinhard => 0.0,
+			Tonemapper::AcesFilmic => 1.0,
+			Tonemapper::Uncharted2 => 2.0,
+		}
+	}
+}
+
+pub struct RenderTarget {
+	_texture: wgpu::Texture,
+	view: TextureView,
+	_sampler: Sampler,
+	bind_group: BindGroup,
+	width: u32,
+	height: u32,
+	format: TextureFormat,
+}
+
+impl RenderTarget {
+	pub fn new(renderer: &Renderer, width: u32, height: u32, format: TextureFormat) -> RenderTarget {
+		let texture_desc = TextureDescriptor {
+			size: Extent3d {
+				width: width.max(1),
+				height: height.max(1),
+				depth: 1,
+			},
+			array_layer_count: 1,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: TextureDimension::D2,
+			format,
+			usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+			label: Some("render_target"),
+		};
+		let _texture = renderer.get_device().create_texture(&texture_desc);
+		let view = _texture.create_default_view();
+		let sampler_desc = SamplerDescriptor {
+			address_mode_u: AddressMode::ClampToEdge,
+			address_mode_v: AddressMode::ClampToEdge,
+			address_mode_w: AddressMode::ClampToEdge,
+			mag_filter: FilterMode::Linear,
+			min_filter: FilterMode::Linear,
+			mipmap_filter: FilterMode::Nearest,
+			lod_min_clamp: -100.0,
+			lod_max_clamp: 100.0,
+			compare: CompareFunction::Always,
+		};
+		let _sampler = renderer.get_device().create_sampler(&sampler_desc);
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("render_target_type_not_registered"),
+			bindings: &[
+				Binding {
+					binding: 0,
+					resource: BindingResource::TextureView(&view),
+				},
+				Binding {
+					binding: 1,
+					resource: BindingResource::Sampler(&_sampler),
+				},
+			],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		RenderTarget {
+			_texture,
+			view,
+			_sampler,
+			bind_group,
+			width,
+			height,
+			format,
+		}
+	}
+
+	pub fn get_view(&self) -> &TextureView {
+		&self.view
+	}
+
+	pub fn get_width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn get_height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn get_format(&self) -> TextureFormat {
+		self.format
+	}
+}
+
+impl Bind for RenderTarget {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[
+				BindGroupLayoutEntry {
+					binding: 0,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::SampledTexture {
+						multisampled: false,
+						dimension: TextureViewDimension::D2,
+						component_type: TextureComponentType::Float,
+					},
+				},
+				BindGroupLayoutEntry {
+					binding: 1,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::Sampler {
+						comparison: false,
+					},
+				},
+			],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PostUniform {
+	pub resolution: [f32; 4],
+	pub parameters: [f32; 4],
+}
+
+unsafe impl Pod for PostUniform {}
+
+unsafe impl Zeroable for PostUniform {}
+
+impl PostUniform {
+	pub fn new(width: u32, height: u32, parameters: [f32; 4]) -> PostUniform {
+		let width = width.max(1) as f32;
+		let height = height.max(1) as f32;
+		PostUniform {
+			resolution: [width, height, 1.0 / width, 1.0 / height],
+			parameters,
+		}
+	}
+}
+
+pub struct PostEffect {
+	intermediate: Pipeline<PositionVertex, u32>,
+	output: Pipeline<PositionVertex, u32>,
+	uniform: Uniform<PostUniform>,
+	parameters: [f32; 4],
+	enabled: bool,
+}
+
+impl PostEffect {
+	pub fn new(renderer: &Renderer, compiler: &mut Compiler, fragment_code: &str, parameters: [f32; 4]) -> PostEffect {
+		let binds = get_post_binds();
+		let options = PipelineOptions {
+			cull_mode: CullMode::None,
+			blend: BlendMode::Replace,
+			color_format: Some(HDR_FORMAT),
+			..PipelineOptions::default()
+		};
+		let intermediate = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, fragment_code, &binds, &options);
+		let options = PipelineOptions {
+			color_format: Some(renderer.get_format()),
+			..options
+		};
+		let output = Pipeline::new_with_options(renderer, compiler, POST_VERTEX_SHADER, fragment_code, &binds, &options);
+
+		PostEffect {
+			intermediate,
+			output,
+			uniform: Uniform::new(renderer, &PostUniform::new(renderer.get_width(), renderer.get_height(), parameters)),
+			parameters,
+			enabled: true,
+		}
+	}
+
+	pub fn copy(renderer: &Renderer, compiler: &mut Compiler) -> PostEffect {
+		PostEffect::new(renderer, compiler, POST_COPY_SHADER, [0.0; 4])
+	}
+
+	pub fn tonemap(renderer: &Renderer, compiler: &mut Compiler, tonemapper: Tonemapper, exposure: f32) -> PostEffect {
+		PostEffect::new(renderer, compiler, POST_TONEMAP_SHADER, [exposure, tonemapper.get_index(), 11.2, 0.0])
+	}
+
+	pub fn gamma(renderer: &Renderer, compiler: &mut Compiler, gamma: f32) -> PostEffect {
+		if matches!(renderer.get_format(), TextureFormat::Bgra8UnormSrgb | TextureFormat::Rgba8UnormSrgb) {
+			panic!("gamma_on_srgb_output");
+		}
+		PostEffect::new(renderer, compiler, POST_GAMMA_SHADER, [gamma, 0.0, 0.0, 0.0])
+	}
+
+	pub fn vignette(renderer: &Renderer, compiler: &mut Compiler, intensity: f32, radius: f32, softness: f32) -> PostEffect {
+		PostEffect::new(renderer, compiler, POST_VIGNETTE_SHADER, [intensity, radius, softness, 0.0])
+	}
+
+	pub fn fxaa(renderer: &Renderer, compiler: &mut Compiler) -> PostEffect {
+		PostEffect::new(renderer, compiler, POST_FXAA_SHADER, [1.0 / 8.0, 8.0, 0.0, 0.0])
+	}
+
+	pub fn get_parameters(&self) -> [f32; 4] {
+		self.parameters
+	}
+
+	pub fn set_parameters(&mut self, renderer: &Renderer, parameters: [f32; 4]) {
+		self.parameters = parameters;
+		self.uniform.update(renderer, &PostUniform::new(renderer.get_width(), renderer.get_height(), parameters));
+	}
+
+	pub fn set_exposure(&mut self, renderer: &Renderer, exposure: f32) {
+		let parameters = self.parameters;
+		self.set_parameters(renderer, [exposure, parameters[1], parameters[2], parameters[3]]);
+	}
+
+	pub fn set_tonemapper(&mut self, renderer: &Renderer, tonemapper: Tonemapper) {
+		let parameters = self.parameters;
+		self.set_parameters(renderer, [parameters[0], tonemapper.get_index(), parameters[2], parameters[3]]);
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+	}
+
+	fn resize(&self, renderer: &Renderer, width: u32, height: u32) {
+		self.uniform.update(renderer, &PostUniform::new(width, height, self.parameters));
+	}
+}
+
+pub struct PostProcess {
+	targets: [RenderTarget; 2],
+	triangle: Mesh<PositionVertex, u32>,
+	effects: Vec<PostEffect>,
+	copy: PostEffect,
+	bloom: Option<Bloom>,
+}
+
+impl PostProcess {
+	pub fn new(renderer: &Renderer, compiler: &mut Compiler) -> PostProcess {
+		let (width, height) = (renderer.get_width(), renderer.get_height());
+		let triangle = create_fullscreen_triangle(renderer);
+
+		PostProcess {
+			targets: [
+				RenderTarget::new(renderer, width, height, HDR_FORMAT),
+				RenderTarget::new(renderer, width, height, HDR_FORMAT),
+			],
+			triangle,
+			effects: Vec::new(),
+			copy: PostEffect::copy(renderer, compiler),
+			bloom: None,
+		}
+	}
+
+	pub fn add_effect(&mut self, effect: PostEffect) -> usize {
+		self.effects.push(effect);
+		self.effects.len() - 1
+	}
+
+	pub fn remove_effect(&mut self, index: usize) -> PostEffect {
+		self.effects.remove(index)
+	}
+
+	pub fn get_effect(&self, index: usize) -> &PostEffect {
+		&self.effects[index]
+	}
+
+	pub fn get_effect_mut(&mut self, index: usize) -> &mut PostEffect {
+		&mut self.effects[index]
+	}
+
+	pub fn get_effect_count(&self) -> usize {
+		self.effects.len()
+	}
+
+	pub fn get_bloom(&self) -> Option<&Bloom> {
+		self.bloom.as_ref()
+	}
+
+	pub fn get_bloom_mut(&mut self) -> Option<&mut Bloom> {
+		self.bloom.as_mut()
+	}
+
+	pub fn set_bloom(&mut self, renderer: &Renderer, bloom: Option<Bloom>) {
+		self.bloom = bloom;
+		if let Some(bloom) = &mut self.bloom {
+			bloom.resize(renderer, self.targets[0].get_width(), self.targets[0].get_height());
+		}
+	}
+
+	pub fn get_scene_target(&self) -> &RenderTarget {
+		&self.targets[0]
+	}
+
+	pub fn resize(&mut self, renderer: &Renderer) {
+		let (width, height) = (renderer.get_width(), renderer.get_height());
+		self.targets = [
+			RenderTarget::new(renderer, width, height, HDR_FORMAT),
+			RenderTarget::new(renderer, width, height, HDR_FORMAT),
+		];
+		for effect in self.effects.iter().chain(std::iter::once(&self.copy)) {
+			effect.resize(renderer, width, height);
+		}
+		if let Some(bloom) = &mut self.bloom {
+			bloom.resize(renderer, width, height);
+		}
+	}
+
+	pub fn execute(&mut self, renderer: &mut Renderer, clear_color: &[f64; 4], commands: &[Command]) {
+		if self.targets[0].get_width() != renderer.get_width() || self.targets[0].get_height() != renderer.get_height() {
+			self.resize(renderer);
+		}
+		Command::execute_to_view(renderer, self.targets[0].get_view(), Some(clear_color), true, commands);
+		let mut first = 0;
+		if let Some(bloom) = &self.bloom {
+			if bloom.is_enabled() && bloom.get_level_count() > 0 {
+				bloom.execute(renderer, &self.triangle, &self.targets[0], self.targets[1].get_view());
+				first = 1;
+			}
+		}
+
+		let frame = renderer.get_swap_chain_mut().get_next_texture().expect("texture_finding_failed");
+		let mut effects: Vec<&PostEffect> = self.effects.iter().filter(|effect| effect.enabled).collect();
+		if effects.is_empty() {
+			effects.push(&self.copy);
+		}
+		let last = effects.len() - 1;
+		for (index, effect) in effects.into_iter().enumerate() {
+			let input = &self.targets[(first + index) % 2];
+			let (pipeline, view) = if index == last {
+				(&effect.output, &frame.view)
+			} else {
+				(&effect.intermediate, self.targets[(first + index + 1) % 2].get_view())
+			};
+			let pass = [
+				Command::SetPipeline(pipeline),
+				Command::SetBind(input, 0),
+				Command::SetBind(&effect.uniform, 1),
+				Command::SetMesh(&self.triangle),
+				Command::Draw(0..3),
+			];
+			Command::execute_to_view(renderer, view, None, false, &pass);
+		}
+	}
+}
+
+pub fn get_post_binds() -> [TypeId; 2] {
+	[
+		TypeId::of::<RenderTarget>(),
+		TypeId::of::<Uniform<PostUniform>>(),
+	]
+}
+
+pub fn register_post_process(renderer: &mut Renderer) {
+	if renderer.get_bind_group_layout::<RenderTarget>().is_none() {
+		renderer.register_bind_group_layout::<RenderTarget>();
+	}
+	if renderer.get_bind_group_layout::<Uniform<PostUniform>>().is_none() {
+		renderer.register_bind_group_layout::<Uniform<PostUniform>>();
+	}
+	let binds = get_post_binds();
+	if renderer.get_pipeline_layout(&binds).is_none() {
+		renderer.register_pipeline_layout(&binds);
+	}
+	let binds = get_bloom_binds();
+	if renderer.get_pipeline_layout(&binds).is_none() {
+		renderer.register_pipeline_layout(&binds);
+	}
+}
@@ -0,0 +1,242 @@
+use crate::{AttributeTrait, MeshData};
+use cgmath::{Vector3, InnerSpace};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+pub fn plane<V: AttributeTrait>(width: f32, depth: f32, columns: u32, rows: u32) -> MeshData<V, u32> {
+	let columns = columns.max(1);
+	let rows = rows.max(1);
+	finish(surface(columns, rows, |i, j| {
+		let u = i as f32 / columns as f32;
+		let v = j as f32 / rows as f32;
+		([width * (u - 0.5), 0.0, depth * (0.5 - v)], [0.0, 1.0, 0.0], [u, v])
+	}))
+}
+
+pub fn cube<V: AttributeTrait>(size: f32, subdivisions: u32) -> MeshData<V, u32> {
+	let subdivisions = subdivisions.max(1);
+	let half = size * 0.5;
+	let faces = [
+		([half, -half, half], [0.0, 0.0, -size], [0.0, size, 0.0], [1.0, 0.0, 0.0]),
+		([-half, -half, -half], [0.0, 0.0, size], [0.0, size, 0.0], [-1.0, 0.0, 0.0]),
+		([-half, half, half], [size, 0.0, 0.0], [0.0, 0.0, -size], [0.0, 1.0, 0.0]),
+		([-half, -half, -half], [size, 0.0, 0.0], [0.0, 0.0, size], [0.0, -1.0, 0.0]),
+		([-half, -half, half], [size, 0.0, 0.0], [0.0, size, 0.0], [0.0, 0.0, 1.0]),
+		([half, -half, -half], [-size, 0.0, 0.0], [0.0, size, 0.0], [0.0, 0.0, -1.0]),
+	];
+	let mut data = MeshData::new(Vec::new(), Vec::new());
+	for (origin, u_axis, v_axis, normal) in faces.iter() {
+		data.merge(&surface(subdivisions, subdivisions, |i, j| {
+			let u = i as f32 / subdivisions as f32;
+			let v = j as f32 / subdivisions as f32;
+			let position = [
+				origin[0] + u_axis[0] * u + v_axis[0] * v,
+				origin[1] + u_axis[1] * u + v_axis[1] * v,
+				origin[2] + u_axis[2] * u + v_axis[2] * v,
+			];
+			(position, *normal, [u, v])
+		})).expect("index_out_of_range");
+	}
+	finish(data)
+}
+
+pub fn uv_sphere<V: AttributeTrait>(radius: f32, segments: u32, rings: u32) -> MeshData<V, u32> {
+	let segments = segments.max(3);
+	let rings = rings.max(2);
+	finish(surface(segments, rings, |i, j| {
+		let u = i as f32 / segments as f32;
+		let v = j as f32 / rings as f32;
+		let normal = spherical(2.0 * PI * u, PI * (1.0 - v));
+		([normal[0] * radius, normal[1] * radius, normal[2] * radius], normal, [u, v])
+	}))
+}
+
+pub fn icosphere<V: AttributeTrait>(radius: f32, subdivisions: u32) -> MeshData<V, u32> {
+	let t = (1.0 + 5.0f32.sqrt()) * 0.5;
+	let mut points: Vec<Vector3<f32>> = [
+		[-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+		[0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+		[t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+	].iter().map(|point| Vector3::from(*point).normalize()).collect();
+	let mut triangles: Vec<[u32; 3]> = vec![
+		[0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+		[1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+		[3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+		[4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+	];
+	for _ in 0..subdivisions {
+		let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+		let mut midpoint = |a: u32, b: u32| {
+			*midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+				points.push(((points[a as usize] + points[b as usize]) * 0.5).normalize());
+				(points.len() - 1) as u32
+			})
+		};
+		triangles = triangles.iter().flat_map(|[a, b, c]| {
+			let ab = midpoint(*a, *b);
+			let bc = midpoint(*b, *c);
+			let ca = midpoint(*c, *a);
+			vec![[*a, ab, ca], [*b, bc, ab], [*c, ca, bc], [ab, bc, ca]]
+		}).collect();
+	}
+
+	let mut lookup: HashMap<(u32, u32), u32> = HashMap::new();
+	let mut data = MeshData::new(Vec::new(), Vec::new());
+	for triangle in &triangles {
+		let mut us: Vec<f32> = triangle.iter().map(|index| {
+			let point = points[*index as usize];
+			let u = (-point.z).atan2(point.x) / (2.0 * PI);
+			if u < 0.0 { u + 1.0 } else { u }
+		}).collect();
+		if us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min) > 0.5 {
+			us.iter_mut().filter(|u| **u < 0.5).for_each(|u| *u += 1.0);
+		}
+		for k in 0..3 {
+			let point = points[triangle[k] as usize];
+			if point.y.abs() > 0.999_999 {
+				us[k] = (us[(k + 1) % 3] + us[(k + 2) % 3]) * 0.5;
+			}
+		}
+		for k in 0..3 {
+			let point = points[triangle[k] as usize];
+			let index = *lookup.entry((triangle[k], us[k].to_bits())).or_insert_with(|| {
+				let v = 0.5 + point.y.clamp(-1.0, 1.0).asin() / PI;
+				data.vertices.push(vertex((point * radius).into(), point.into(), [us[k], v]));
+				(data.vertices.len() - 1) as u32
+			});
+			data.indices.push(index);
+		}
+	}
+	finish(data)
+}
+
+pub fn cylinder<V: AttributeTrait>(radius: f32, height: f32, segments: u32, height_segments: u32, capped: bool) -> MeshData<V, u32> {
+	truncated_cone(radius, radius, height, segments, height_segments, capped)
+}
+
+pub fn cone<V: AttributeTrait>(radius: f32, height: f32, segments: u32, height_segments: u32, capped: bool) -> MeshData<V, u32> {
+	truncated_cone(radius, 0.0, height, segments, height_segments, capped)
+}
+
+pub fn capsule<V: AttributeTrait>(radius: f32, height: f32, segments: u32, rings: u32) -> MeshData<V, u32> {
+	let segments = segments.max(3);
+	let rings = rings.max(1);
+	let total = height + 2.0 * radius;
+	finish(surface(segments, 2 * rings + 1, |i, j| {
+		let u = i as f32 / segments as f32;
+		let (phi, offset) = if j <= rings {
+			(PI - PI * 0.5 * j as f32 / rings as f32, -height * 0.5)
+		} else {
+			(PI * 0.5 - PI * 0.5 * (j - rings - 1) as f32 / rings as f32, height * 0.5)
+		};
+		let normal = spherical(2.0 * PI * u, phi);
+		let position = [normal[0] * radius, normal[1] * radius + offset, normal[2] * radius];
+		(position, normal, [u, (position[1] + total * 0.5) / total])
+	}))
+}
+
+pub fn torus<V: AttributeTrait>(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> MeshData<V, u32> {
+	let major_segments = major_segments.max(3);
+	let minor_segments = minor_segments.max(3);
+	finish(surface(major_segments, minor_segments, |i, j| {
+		let u = i as f32 / major_segments as f32;
+		let v = j as f32 / minor_segments as f32;
+		let theta = 2.0 * PI * u;
+		let phi = 2.0 * PI * v;
+		let radial = [theta.cos(), 0.0, -theta.sin()];
+		let normal = [radial[0] * phi.cos(), phi.sin(), radial[2] * phi.cos()];
+		let position = [
+			radial[0] * major_radius + normal[0] * minor_radius,
+			normal[1] * minor_radius,
+			radial[2] * major_radius + normal[2] * minor_radius,
+		];
+		(position, normal, [u, v])
+	}))
+}
+
+pub fn disc<V: AttributeTrait>(radius: f32, segments: u32, rings: u32) -> MeshData<V, u32> {
+	finish(disc_surface(radius, 0.0, segments, rings, true))
+}
+
+fn truncated_cone<V: AttributeTrait>(bottom_radius: f32, top_radius: f32, height: f32, segments: u32, height_segments: u32, capped: bool) -> MeshData<V, u32> {
+	let segments = segments.max(3);
+	let height_segments = height_segments.max(1);
+	let mut data = surface(segments, height_segments, |i, j| {
+		let u = i as f32 / segments as f32;
+		let v = j as f32 / height_segments as f32;
+		let theta = 2.0 * PI * u;
+		let radius = bottom_radius + (top_radius - bottom_radius) * v;
+		let normal = if height != 0.0 {
+			Vector3::new(theta.cos(), (bottom_radius - top_radius) / height, -theta.sin()).normalize()
+		} else {
+			Vector3::new(0.0, (bottom_radius - top_radius).signum(), 0.0)
+		};
+		([radius * theta.cos(), height * (v - 0.5), -radius * theta.sin()], normal.into(), [u, v])
+	});
+	if capped {
+		data.merge(&disc_surface(bottom_radius, -height * 0.5, segments, 1, false)).expect("index_out_of_range");
+		if top_radius > 0.0 {
+			data.merge(&disc_surface(top_radius, height * 0.5, segments, 1, true)).expect("index_out_of_range");
+		}
+	}
+	finish(data)
+}
+
+fn disc_surface<V: AttributeTrait>(radius: f32, offset: f32, segments: u32, rings: u32, up: bool) -> MeshData<V, u32> {
+	let segments = segments.max(3);
+	let rings = rings.max(1);
+	let side = if up { -1.0 } else { 1.0 };
+	surface(segments, rings, |i, j| {
+		let theta = 2.0 * PI * i as f32 / segments as f32;
+		let distance = radius * (1.0 - j as f32 / rings as f32);
+		let x = distance * theta.cos();
+		let z = side * distance * theta.sin();
+		let uv = if radius > 0.0 { [0.5 - side * x / (2.0 * radius), 0.5 - z / (2.0 * radius)] } else { [0.5, 0.5] };
+		([x, offset, z], [0.0, -side, 0.0], uv)
+	})
+}
+
+fn surface<V: AttributeTrait, F: Fn(u32, u32) -> ([f32; 3], [f32; 3], [f32; 2])>(columns: u32, rows: u32, f: F) -> MeshData<V, u32> {
+	let mut data: MeshData<V, u32> = MeshData::new(Vec::with_capacity(((columns + 1) * (rows + 1)) as usize), Vec::new());
+	for j in 0..=rows {
+		for i in 0..=columns {
+			let (position, normal, uv) = f(i, j);
+			data.vertices.push(vertex(position, normal, uv));
+		}
+	}
+	for j in 0..rows {
+		for i in 0..columns {
+			let a = j * (columns + 1) + i;
+			let b = a + 1;
+			let c = a + columns + 1;
+			let d = c + 1;
+			for triangle in &[[a, b, c], [b, d, c]] {
+				let positions: Vec<Vector3<f32>> = triangle.iter().map(|index| Vector3::from(data.vertices[*index as usize].get_position())).collect();
+				if (positions[1] - positions[0]).cross(positions[2] - positions[0]).magnitude2() > 1e-12 {
+					data.indices.extend_from_slice(triangle);
+				}
+			}
+		}
+	}
+	data
+}
+
+fn spherical(theta: f32, phi: f32) -> [f32; 3] {
+	[phi.sin() * theta.cos(), phi.cos(), -phi.sin() * theta.sin()]
+}
+
+fn vertex<V: AttributeTrait>(position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> V {
+	let mut vertex = V::zeroed();
+	vertex.set_position(position);
+	vertex.set_normal(normal);
+	vertex.set_uv(uv);
+	vertex.set_color([1.0; 4]);
+	vertex
+}
+
+fn finish<V: AttributeTrait>(mut data: MeshData<V, u32>) -> MeshData<V, u32> {
+	if data.vertices.first().and_then(|vertex| vertex.get_tangent()).is_some() {
+		data.compute_tangents().expect("index_out_of_range");
+	}
+	data
+}
@@ -0,0 +1,109 @@
+pub fn f32_to_half(value: f32) -> u16 {
+	let bits = value.to_bits();
+	let sign = ((bits >> 16) & 0x8000) as u16;
+	let exponent = ((bits >> 23) & 0xff) as i32;
+	let mantissa = bits & 0x7f_ffff;
+	if exponent == 0xff {
+		return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+	}
+	let exponent = exponent - 127 + 15;
+	if exponent >= 0x1f {
+		return sign | 0x7c00;
+	}
+	if exponent <= 0 {
+		if exponent < -10 {
+			return sign;
+		}
+		let mantissa = mantissa | 0x80_0000;
+		let shift = (14 - exponent) as u32;
+		let half = mantissa >> shift;
+		let rest = mantissa & ((1 << shift) - 1);
+		let midpoint = 1 << (shift - 1);
+		let half = if rest > midpoint || (rest == midpoint && half & 1 == 1) { half + 1 } else { half };
+		return sign | half as u16;
+	}
+	let half = ((exponent as u32) << 10) | (mantissa >> 13);
+	let rest = mantissa & 0x1fff;
+	let half = if rest > 0x1000 || (rest == 0x1000 && half & 1 == 1) { half + 1 } else { half };
+	sign | half as u16
+}
+
+pub fn half_to_f32(value: u16) -> f32 {
+	let sign = ((value & 0x8000) as u32) << 16;
+	let exponent = ((value >> 10) & 0x1f) as u32;
+	let mantissa = (value & 0x3ff) as u32;
+	match exponent {
+		0 => {
+			let magnitude = mantissa as f32 / 16_777_216.0;
+			if sign != 0 { -magnitude } else { magnitude }
+		}
+		0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+		_ => f32::from_bits(sign | ((exponent + 112) << 23) | (mantissa << 13)),
+	}
+}
+
+pub fn to_unorm8(value: f32) -> u8 {
+	(value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+pub fn from_unorm8(value: u8) -> f32 {
+	value as f32 / 255.0
+}
+
+pub fn to_unorm16(value: f32) -> u16 {
+	(value.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+pub fn from_unorm16(value: u16) -> f32 {
+	value as f32 / 65535.0
+}
+
+pub fn to_snorm16(value: f32) -> i16 {
+	(value.clamp(-1.0, 1.0) * 32767.0).round() as i16
+}
+
+pub fn from_snorm16(value: i16) -> f32 {
+	(value as f32 / 32767.0).max(-1.0)
+}
+
+pub fn encode_octahedral(normal: [f32; 3]) -> [f32; 2] {
+	let length = normal[0].abs() + normal[1].abs() + normal[2].abs();
+	if length <= 0.0 {
+		return [0.0, 0.0];
+	}
+	let (x, y, z) = (normal[0] / length, normal[1] / length, normal[2] / length);
+	if z < 0.0 {
+		[(1.0 - y.abs()) * sign_not_zero(x), (1.0 - x.abs()) * sign_not_zero(y)]
+	} else {
+		[x, y]
+	}
+}
+
+pub fn decode_octahedral(encoded: [f32; 2]) -> [f32; 3] {
+	let (mut x, mut y) = (encoded[0], encoded[1]);
+	let z = 1.0 - x.abs() - y.abs();
+	if z < 0.0 {
+		let (folded_x, folded_y) = ((1.0 - y.abs()) * sign_not_zero(x), (1.0 - x.abs()) * sign_not_zero(y));
+		x = folded_x;
+		y = folded_y;
+	}
+	let length = (x * x + y * y + z * z).sqrt();
+	if length > 0.0 {
+		[x / length, y / length, z / length]
+	} else {
+		[0.0, 0.0, 1.0]
+	}
+}
+
+pub fn to_octahedral_snorm16(normal: [f32; 3]) -> [i16; 2] {
+	let encoded = encode_octahedral(normal);
+	[to_snorm16(encoded[0]), to_snorm16(encoded[1])]
+}
+
+pub fn from_octahedral_snorm16(encoded: [i16; 2]) -> [f32; 3] {
+	decode_octahedral([from_snorm16(encoded[0]), from_snorm16(encoded[1])])
+}
+
+fn sign_not_zero(value: f32) -> f32 {
+	if value >= 0.0 { 1.0 } else { -1.0 }
+}
@@ -1,10 +1,15 @@
-use crate::Bind;
+use crate::{Bind, Camera};
 use raw_window_handle::HasRawWindowHandle;
 use wgpu::*;
 use futures::executor::block_on;
 use std::collections::HashMap;
 use std::any::TypeId;
 use std::collections::hash_map::Entry;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+pub const MAX_BIND_GROUPS: u32 = wgpu::MAX_BIND_GROUPS as u32;
 
 pub struct Renderer {
 	surface: Surface,
@@ -12,12 +17,19 @@ pub struct Renderer {
 	queue: Queue,
 	swap_chain: SwapChain,
 	swap_chain_desc: SwapChainDescriptor,
+	_depth_texture: Texture,
+	depth_view: TextureView,
 	bind_group_layout: HashMap<TypeId, BindGroupLayout>,
 	pipeline_layouts: HashMap<Vec<TypeId>, PipelineLayout>,
+	cameras: Vec<Weak<RefCell<Camera>>>,
 }
 
 impl Renderer {
 	pub fn new<T: HasRawWindowHandle>(window: &T, width: u32, height: u32) -> Renderer {
+		Renderer::new_with_format(window, width, height, TextureFormat::Bgra8UnormSrgb)
+	}
+
+	pub fn new_with_format<T: HasRawWindowHandle>(window: &T, width: u32, height: u32, format: TextureFormat) -> Renderer {
 		let surface = Surface::create(window);
 		let adapter_option = RequestAdapterOptions {
 			power_preference: PowerPreference::Default,
@@ -28,17 +40,20 @@ impl Renderer {
 			extensions: Extensions {
 				anisotropic_filtering: false
 			},
-			limits: Default::default(),
+			limits: Limits {
+				max_bind_groups: MAX_BIND_GROUPS,
+			},
 		};
 		let (device, queue) = block_on(adapter.request_device(&device_desc));
 		let swap_chain_desc = SwapChainDescriptor {
 			usage: TextureUsage::OUTPUT_ATTACHMENT,
-			format: TextureFormat::Bgra8UnormSrgb,
+			format,
 			present_mode: PresentMode::Fifo,
 			width,
 			height,
 		};
 		let swap_chain = device.create_swap_chain(&surface, &swap_chain_desc);
+		let (_depth_texture, depth_view) = create_depth_texture(&device, width, height);
 
 		Renderer {
 			surface,
@@ -46,8 +61,11 @@ impl Renderer {
 			queue,
 			swap_chain,
 			swap_chain_desc,
+			_depth_texture,
+			depth_view,
 			bind_group_layout: HashMap::new(),
 			pipeline_layouts: HashMap::new(),
+			cameras: Vec::new(),
 		}
 	}
 
@@ -55,6 +73,37 @@ impl Renderer {
 		self.swap_chain_desc.width = width;
 		self.swap_chain_desc.height = height;
 		self.swap_chain = self.device.create_swap_chain(&self.surface, &self.swap_chain_desc);
+		let (depth_texture, depth_view) = create_depth_texture(&self.device, width, height);
+		self._depth_texture = depth_texture;
+		self.depth_view = depth_view;
+		self.cameras.retain(|camera| match camera.upgrade() {
+			Some(camera) => {
+				camera.borrow_mut().set_viewport(width, height);
+				true
+			}
+			None => false,
+		});
+	}
+
+	pub fn register_camera(&mut self, camera: &Rc<RefCell<Camera>>) {
+		camera.borrow_mut().set_viewport(self.get_width(), self.get_height());
+		self.cameras.push(Rc::downgrade(camera));
+	}
+
+	pub fn get_width(&self) -> u32 {
+		self.swap_chain_desc.width
+	}
+
+	pub fn get_height(&self) -> u32 {
+		self.swap_chain_desc.height
+	}
+
+	pub fn get_format(&self) -> TextureFormat {
+		self.swap_chain_desc.format
+	}
+
+	pub fn get_aspect_ratio(&self) -> f32 {
+		self.swap_chain_desc.width as f32 / self.swap_chain_desc.height.max(1) as f32
 	}
 
 	pub fn get_device(&self) -> &Device {
@@ -81,6 +130,10 @@ impl Renderer {
 		&mut self.swap_chain
 	}
 
+	pub fn get_depth_view(&self) -> &TextureView {
+		&self.depth_view
+	}
+
 	pub fn register_bind_group_layout<T: 'static + Bind>(&mut self) {
 		let id = TypeId::of::<T>();
 		match self.bind_group_layout.entry(id) {
@@ -118,4 +171,24 @@ impl Renderer {
 	pub fn get_pipeline_layout(&self, binds: &[TypeId]) -> Option<&PipelineLayout> {
 		self.pipeline_layouts.get(binds)
 	}
+}
+
+fn create_depth_texture(device: &Device, width: u32, height: u32) -> (Texture, TextureView) {
+	let texture_desc = TextureDescriptor {
+		size: Extent3d {
+			width: width.max(1),
+			height: height.max(1),
+			depth: 1,
+		},
+		array_layer_count: 1,
+		mip_level_count: 1,
+		sample_count: 1,
+		dimension: TextureDimension::D2,
+		format: DEPTH_FORMAT,
+		usage: TextureUsage::OUTPUT_ATTACHMENT,
+		label: Some("depth_texture"),
+	};
+	let texture = device.create_texture(&texture_desc);
+	let view = texture.create_default_view();
+	(texture, view)
 }
\ No newline at end of file
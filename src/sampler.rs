@@ -0,0 +1,128 @@
+use crate::{Bind, Renderer};
+use wgpu::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerOptions {
+	pub address_mode_u: AddressMode,
+	pub address_mode_v: AddressMode,
+	pub address_mode_w: AddressMode,
+	pub mag_filter: FilterMode,
+	pub min_filter: FilterMode,
+	pub mipmap_filter: FilterMode,
+	pub lod_min_clamp: f32,
+	pub lod_max_clamp: f32,
+}
+
+impl Default for SamplerOptions {
+	fn default() -> SamplerOptions {
+		SamplerOptions {
+			address_mode_u: AddressMode::ClampToEdge,
+			address_mode_v: AddressMode::ClampToEdge,
+			address_mode_w: AddressMode::ClampToEdge,
+			mag_filter: FilterMode::Linear,
+			min_filter: FilterMode::Linear,
+			mipmap_filter: FilterMode::Linear,
+			lod_min_clamp: -100.0,
+			lod_max_clamp: 100.0,
+		}
+	}
+}
+
+impl SamplerOptions {
+	pub fn with_address_mode(address_mode: AddressMode) -> SamplerOptions {
+		SamplerOptions {
+			address_mode_u: address_mode,
+			address_mode_v: address_mode,
+			address_mode_w: address_mode,
+			..SamplerOptions::default()
+		}
+	}
+
+	pub fn repeat() -> SamplerOptions {
+		SamplerOptions::with_address_mode(AddressMode::Repeat)
+	}
+
+	pub fn mirror() -> SamplerOptions {
+		SamplerOptions::with_address_mode(AddressMode::MirrorRepeat)
+	}
+
+	pub fn nearest() -> SamplerOptions {
+		SamplerOptions {
+			mag_filter: FilterMode::Nearest,
+			min_filter: FilterMode::Nearest,
+			mipmap_filter: FilterMode::Nearest,
+			..SamplerOptions::default()
+		}
+	}
+
+	pub fn create_sampler(&self, device: &Device) -> wgpu::Sampler {
+		let sampler_desc = SamplerDescriptor {
+			address_mode_u: self.address_mode_u,
+			address_mode_v: self.address_mode_v,
+			address_mode_w: self.address_mode_w,
+			mag_filter: self.mag_filter,
+			min_filter: self.min_filter,
+			mipmap_filter: self.mipmap_filter,
+			lod_min_clamp: self.lod_min_clamp,
+			lod_max_clamp: self.lod_max_clamp,
+			compare: CompareFunction::Always,
+		};
+		device.create_sampler(&sampler_desc)
+	}
+}
+
+#[derive(Debug)]
+pub struct Sampler {
+	sampler: wgpu::Sampler,
+	bind_group: BindGroup,
+	options: SamplerOptions,
+}
+
+impl Sampler {
+	pub fn new(renderer: &Renderer, options: &SamplerOptions) -> Sampler {
+		let sampler = options.create_sampler(renderer.get_device());
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("sampler_type_not_registered"),
+			bindings: &[Binding {
+				binding: 0,
+				resource: BindingResource::Sampler(&sampler),
+			}],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		Sampler {
+			sampler,
+			bind_group,
+			options: *options,
+		}
+	}
+
+	pub fn get_sampler(&self) -> &wgpu::Sampler {
+		&self.sampler
+	}
+
+	pub fn get_options(&self) -> &SamplerOptions {
+		&self.options
+	}
+}
+
+impl Bind for Sampler {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[BindGroupLayoutEntry {
+				binding: 0,
+				visibility: ShaderStage::FRAGMENT,
+				ty: BindingType::Sampler {
+					comparison: false,
+				},
+			}],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
\ No newline at end of file
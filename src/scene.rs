@@ -0,0 +1,332 @@
+use crate::{Aabb, Bind, Command, Frustum, MaterialTrait, MeshTrait, PipelineTrait, Renderer, Transform, Uniform};
+use cgmath::{Matrix, Matrix4, SquareMatrix};
+use bytemuck::{Pod, Zeroable};
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize, u32);
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ModelUniform {
+	pub model: [[f32; 4]; 4],
+	pub normal: [[f32; 4]; 4],
+}
+
+unsafe impl Pod for ModelUniform {}
+
+unsafe impl Zeroable for ModelUniform {}
+
+impl ModelUniform {
+	pub fn new(model: &Matrix4<f32>) -> ModelUniform {
+		let normal = model.invert().map(|inverse| inverse.transpose()).unwrap_or(*model);
+		ModelUniform {
+			model: (*model).into(),
+			normal: normal.into(),
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct Renderable {
+	pub mesh: Rc<dyn MeshTrait>,
+	pub material: Rc<dyn MaterialTrait>,
+	pub range: Range<u32>,
+	pub model_slot: Option<u32>,
+	pub bounding_box: Option<Aabb>,
+	pub cast_shadows: bool,
+}
+
+impl Renderable {
+	pub fn new(mesh: Rc<dyn MeshTrait>, material: Rc<dyn MaterialTrait>, range: Range<u32>) -> Renderable {
+		Renderable {
+			mesh,
+			material,
+			range,
+			model_slot: None,
+			bounding_box: None,
+			cast_shadows: true,
+		}
+	}
+}
+
+struct Node {
+	name: Option<String>,
+	transform: Transform,
+	world: Matrix4<f32>,
+	parent: Option<NodeId>,
+	children: Vec<NodeId>,
+	renderable: Option<Renderable>,
+	model: Option<Uniform<ModelUniform>>,
+	visible: bool,
+	dirty: bool,
+	uploaded: bool,
+}
+
+#[derive(Default)]
+pub struct Scene {
+	nodes: Vec<Option<Node>>,
+	generations: Vec<u32>,
+	free: Vec<usize>,
+	roots: Vec<NodeId>,
+}
+
+impl Scene {
+	pub fn new() -> Scene {
+		Scene::default()
+	}
+
+	pub fn add_node(&mut self, parent: Option<NodeId>, transform: Transform) -> NodeId {
+		let node = Node {
+			name: None,
+			transform,
+			world: Matrix4::identity(),
+			parent,
+			children: Vec::new(),
+			renderable: None,
+			model: None,
+			visible: true,
+			dirty: true,
+			uploaded: false,
+		};
+		let id = match self.free.pop() {
+			Some(index) => {
+				self.nodes[index] = Some(node);
+				NodeId(index, self.generations[index])
+			}
+			None => {
+				self.nodes.push(Some(node));
+				self.generations.push(0);
+				NodeId(self.nodes.len() - 1, 0)
+			}
+		};
+		match parent {
+			Some(parent) => self.get_node_mut(parent).children.push(id),
+			None => self.roots.push(id),
+		}
+		id
+	}
+
+	pub fn remove_node(&mut self, id: NodeId) {
+		let parent = self.get_node(id).parent;
+		self.detach(id, parent);
+		let mut stack = vec![id];
+		while let Some(current) = stack.pop() {
+			let node = self.nodes[current.0].take().expect("scene_node_not_found");
+			stack.extend(node.children);
+			self.generations[current.0] = self.generations[current.0].wrapping_add(1);
+			self.free.push(current.0);
+		}
+	}
+
+	pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+		let mut ancestor = parent;
+		while let Some(current) = ancestor {
+			if current == id {
+				panic!("scene_graph_cycle");
+			}
+			ancestor = self.get_node(current).parent;
+		}
+		let previous = self.get_node(id).parent;
+		self.detach(id, previous);
+		match parent {
+			Some(parent) => self.get_node_mut(parent).children.push(id),
+			None => self.roots.push(id),
+		}
+		let node = self.get_node_mut(id);
+		node.parent = parent;
+		node.dirty = true;
+	}
+
+	pub fn get_parent(&self, id: NodeId) -> Option<NodeId> {
+		self.get_node(id).parent
+	}
+
+	pub fn get_children(&self, id: NodeId) -> &[NodeId] {
+		&self.get_node(id).children
+	}
+
+	pub fn get_roots(&self) -> &[NodeId] {
+		&self.roots
+	}
+
+	pub fn contains(&self, id: NodeId) -> bool {
+		self.generations.get(id.0) == Some(&id.1) && self.nodes[id.0].is_some()
+	}
+
+	pub fn get_name(&self, id: NodeId) -> Option<&str> {
+		self.get_node(id).name.as_deref()
+	}
+
+	pub fn set_name(&mut self, id: NodeId, name: Option<String>) {
+		self.get_node_mut(id).name = name;
+	}
+
+	pub fn find_node(&self, name: &str) -> Option<NodeId> {
+		self.nodes.iter()
+			.position(|node| node.as_ref().and_then(|node| node.name.as_deref()) == Some(name))
+			.map(|index| NodeId(index, self.generations[index]))
+	}
+
+	pub fn get_transform(&self, id: NodeId) -> &Transform {
+		&self.get_node(id).transform
+	}
+
+	pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+		let node = self.get_node_mut(id);
+		node.transform = transform;
+		node.dirty = true;
+	}
+
+	pub fn get_world_matrix(&self, id: NodeId) -> Matrix4<f32> {
+		self.get_node(id).world
+	}
+
+	pub fn get_renderable(&self, id: NodeId) -> Option<&Renderable> {
+		self.get_node(id).renderable.as_ref()
+	}
+
+	pub fn set_renderable(&mut self, id: NodeId, renderable: Option<Renderable>) {
+		let node = self.get_node_mut(id);
+		node.renderable = renderable;
+		node.uploaded = false;
+	}
+
+	pub fn is_visible(&self, id: NodeId) -> bool {
+		self.get_node(id).visible
+	}
+
+	pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+		self.get_node_mut(id).visible = visible;
+	}
+
+	pub fn update(&mut self, renderer: &Renderer) {
+		let mut stack: Vec<(NodeId, Matrix4<f32>, bool)> = self.roots.iter().rev().map(|root| (*root, Matrix4::identity(), false)).collect();
+		while let Some((id, parent_world, parent_changed)) = stack.pop() {
+			let node = self.nodes[id.0].as_mut().expect("scene_node_not_found");
+			let changed = node.dirty || parent_changed;
+			if changed {
+				node.world = parent_world * node.transform.to_matrix();
+				node.dirty = false;
+				node.uploaded = false;
+			}
+			if !node.uploaded {
+				if let Some(renderable) = &node.renderable {
+					if renderable.model_slot.is_some() {
+						let data = ModelUniform::new(&node.world);
+						match &node.model {
+							Some(model) => model.update(renderer, &data),
+							None => node.model = Some(Uniform::new(renderer, &data)),
+						}
+					}
+				}
+				node.uploaded = true;
+			}
+			let world = node.world;
+			stack.extend(node.children.iter().rev().map(|child| (*child, world, changed)));
+		}
+	}
+
+	pub fn get_commands(&self, frustum: Option<&Frustum>) -> Vec<Command<'_>> {
+		let mut visible = self.collect_visible(frustum);
+		visible.sort_by_key(|(_, renderable)| get_sort_key(renderable));
+
+		let mut commands = Vec::with_capacity(visible.len() * 4);
+		let mut material: Option<*const u8> = None;
+		let mut mesh: Option<*const u8> = None;
+		for (node, renderable) in visible {
+			let material_address = Rc::as_ptr(&renderable.material) as *const u8;
+			if material != Some(material_address) {
+				commands.push(Command::SetMaterial(renderable.material.as_ref()));
+				material = Some(material_address);
+			}
+			if let (Some(slot), Some(model)) = (renderable.model_slot, &node.model) {
+				commands.push(Command::SetBind(model, slot));
+			}
+			let mesh_address = Rc::as_ptr(&renderable.mesh) as *const u8;
+			if mesh != Some(mesh_address) {
+				commands.push(Command::SetMesh(renderable.mesh.as_ref()));
+				mesh = Some(mesh_address);
+			}
+			commands.push(Command::Draw(renderable.range.clone()));
+		}
+		commands
+	}
+
+	pub fn get_shadow_commands<'a>(&'a self, pipeline: &'a dyn PipelineTrait, camera: &'a dyn Bind, frustum: Option<&Frustum>) -> Vec<Command<'a>> {
+		let mut casters: Vec<(&Node, &Renderable)> = self.collect_visible(frustum)
+			.into_iter()
+			.filter(|(_, renderable)| renderable.cast_shadows)
+			.collect();
+		casters.sort_by_key(|(_, renderable)| Rc::as_ptr(&renderable.mesh) as *const u8 as usize);
+
+		let mut commands = vec![Command::SetPipeline(pipeline), Command::SetBind(camera, 0)];
+		let mut mesh: Option<*const u8> = None;
+		for (node, renderable) in casters {
+			let model = match &node.model {
+				Some(model) => model,
+				None => continue,
+			};
+			commands.push(Command::SetBind(model, 1));
+			let mesh_address = Rc::as_ptr(&renderable.mesh) as *const u8;
+			if mesh != Some(mesh_address) {
+				commands.push(Command::SetMesh(renderable.mesh.as_ref()));
+				mesh = Some(mesh_address);
+			}
+			commands.push(Command::Draw(renderable.range.clone()));
+		}
+		commands
+	}
+
+	fn collect_visible(&self, frustum: Option<&Frustum>) -> Vec<(&Node, &Renderable)> {
+		let mut visible = Vec::new();
+		let mut stack: Vec<&NodeId> = self.roots.iter().rev().collect();
+		while let Some(id) = stack.pop() {
+			let node = self.get_node(*id);
+			if !node.visible {
+				continue;
+			}
+			if let Some(renderable) = &node.renderable {
+				let inside = match (frustum, &renderable.bounding_box) {
+					(Some(frustum), Some(bounding_box)) => frustum.intersects_aabb(&bounding_box.transform(&node.world)),
+					_ => true,
+				};
+				if inside {
+					visible.push((node, renderable));
+				}
+			}
+			stack.extend(node.children.iter().rev());
+		}
+		visible
+	}
+
+	fn detach(&mut self, id: NodeId, parent: Option<NodeId>) {
+		match parent {
+			Some(parent) => self.get_node_mut(parent).children.retain(|child| *child != id),
+			None => self.roots.retain(|root| *root != id),
+		}
+	}
+
+	fn get_node(&self, id: NodeId) -> &Node {
+		if !self.contains(id) {
+			panic!("scene_node_not_found");
+		}
+		self.nodes[id.0].as_ref().expect("scene_node_not_found")
+	}
+
+	fn get_node_mut(&mut self, id: NodeId) -> &mut Node {
+		if !self.contains(id) {
+			panic!("scene_node_not_found");
+		}
+		self.nodes[id.0].as_mut().expect("scene_node_not_found")
+	}
+}
+
+fn get_sort_key(renderable: &Renderable) -> (usize, usize, usize) {
+	(
+		renderable.material.get_pipeline() as *const dyn PipelineTrait as *const u8 as usize,
+		Rc::as_ptr(&renderable.material) as *const u8 as usize,
+		Rc::as_ptr(&renderable.mesh) as *const u8 as usize,
+	)
+}
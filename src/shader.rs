@@ -0,0 +1,33 @@
+pub const LIGHTS_CHUNK: &str = include_str!("shaders/lights.glsl");
+pub const SHADOWS_CHUNK: &str = include_str!("shaders/shadows.glsl");
+pub const POST_CHUNK: &str = include_str!("shaders/post.glsl");
+pub const CUBE_CHUNK: &str = include_str!("shaders/cube.glsl");
+pub const IBL_CHUNK: &str = include_str!("shaders/ibl.glsl");
+pub const MORPH_CHUNK: &str = include_str!("shaders/morph.glsl");
+
+pub fn get_chunk(name: &str) -> Option<&'static str> {
+	match name {
+		"lights.glsl" => Some(LIGHTS_CHUNK),
+		"shadows.glsl" => Some(SHADOWS_CHUNK),
+		"post.glsl" => Some(POST_CHUNK),
+		"cube.glsl" => Some(CUBE_CHUNK),
+		"ibl.glsl" => Some(IBL_CHUNK),
+		"morph.glsl" => Some(MORPH_CHUNK),
+		_ => None,
+	}
+}
+
+pub fn resolve_includes(code: &str) -> String {
+	let mut resolved = String::with_capacity(code.len());
+	for line in code.lines() {
+		match line.trim().strip_prefix("#include") {
+			Some(include) => {
+				let name = include.trim().trim_matches(|c| c == '<' || c == '>' || c == '"');
+				resolved.push_str(get_chunk(name).expect("shader_chunk_not_found"));
+			}
+			None => resolved.push_str(line),
+		}
+		resolved.push('\n');
+	}
+	resolved
+}
@@ -0,0 +1,22 @@
+#version 450
+
+#include <post.glsl>
+
+vec3 apply_threshold(vec3 color) {
+	float brightness = max(color.r, max(color.g, color.b));
+	float knee = max(parameters.y, 1e-4);
+	float soft = clamp(brightness - parameters.x + knee, 0.0, 2.0 * knee);
+	soft = soft * soft / (4.0 * knee);
+	float contribution = max(soft, brightness - parameters.x) / max(brightness, 1e-4);
+	return color * contribution;
+}
+
+void main() {
+	vec2 texel = resolution.zw;
+	vec3 color = sample_input(v_uv + texel * vec2(-1.0, -1.0)).rgb;
+	color += sample_input(v_uv + texel * vec2(1.0, -1.0)).rgb;
+	color += sample_input(v_uv + texel * vec2(-1.0, 1.0)).rgb;
+	color += sample_input(v_uv + texel * vec2(1.0, 1.0)).rgb;
+	color = max(color * 0.25, vec3(0.0));
+	f_color = vec4(apply_threshold(color), 1.0);
+}
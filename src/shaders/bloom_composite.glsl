@@ -0,0 +1,12 @@
+#version 450
+
+#include <post.glsl>
+
+layout(set=2, binding=0) uniform texture2D t_bloom;
+layout(set=2, binding=1) uniform sampler s_bloom;
+
+void main() {
+	vec4 color = sample_input(v_uv);
+	vec3 bloom = texture(sampler2D(t_bloom, s_bloom), v_uv).rgb;
+	f_color = vec4(color.rgb + bloom * parameters.x, color.a);
+}
@@ -0,0 +1,13 @@
+#version 450
+
+#include <post.glsl>
+
+void main() {
+	vec2 texel = resolution.zw;
+	vec3 color = sample_input(v_uv).rgb * 4.0;
+	color += sample_input(v_uv + texel * vec2(-1.0, -1.0)).rgb;
+	color += sample_input(v_uv + texel * vec2(1.0, -1.0)).rgb;
+	color += sample_input(v_uv + texel * vec2(-1.0, 1.0)).rgb;
+	color += sample_input(v_uv + texel * vec2(1.0, 1.0)).rgb;
+	f_color = vec4(color / 8.0, 1.0);
+}
@@ -0,0 +1,18 @@
+vec3 get_cube_direction(int face, vec2 uv) {
+	vec2 st = uv * 2.0 - 1.0;
+	vec3 direction;
+	if (face == 0) {
+		direction = vec3(1.0, -st.y, -st.x);
+	} else if (face == 1) {
+		direction = vec3(-1.0, -st.y, st.x);
+	} else if (face == 2) {
+		direction = vec3(st.x, 1.0, st.y);
+	} else if (face == 3) {
+		direction = vec3(st.x, -1.0, -st.y);
+	} else if (face == 4) {
+		direction = vec3(st.x, -st.y, 1.0);
+	} else {
+		direction = vec3(-st.x, -st.y, -1.0);
+	}
+	return normalize(direction);
+}
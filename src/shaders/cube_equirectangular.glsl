@@ -0,0 +1,12 @@
+#version 450
+
+#include <post.glsl>
+#include <cube.glsl>
+
+const float PI = 3.14159265359;
+
+void main() {
+	vec3 direction = get_cube_direction(int(parameters.x + 0.5), v_uv);
+	vec2 uv = vec2(atan(direction.z, direction.x) / (2.0 * PI) + 0.5, asin(clamp(direction.y, -1.0, 1.0)) / PI + 0.5);
+	f_color = vec4(textureLod(sampler2D(t_input, s_input), uv, 0.0).rgb, 1.0);
+}
@@ -0,0 +1,37 @@
+#version 450
+
+#include <cube.glsl>
+
+layout(location=0) in vec2 v_uv;
+layout(location=0) out vec4 f_color;
+
+layout(set=0, binding=0) uniform textureCube t_source;
+layout(set=0, binding=1) uniform sampler s_source;
+
+layout(set=1, binding=0)
+uniform Post {
+	vec4 resolution;
+	vec4 parameters;
+};
+
+const float PI = 3.14159265359;
+const float SAMPLE_DELTA = 0.05;
+
+void main() {
+	vec3 normal = get_cube_direction(int(parameters.x + 0.5), v_uv);
+	vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+	vec3 right = normalize(cross(up, normal));
+	up = cross(normal, right);
+
+	vec3 irradiance = vec3(0.0);
+	float count = 0.0;
+	for (float phi = 0.0; phi < 2.0 * PI; phi += SAMPLE_DELTA) {
+		for (float theta = 0.0; theta < 0.5 * PI; theta += SAMPLE_DELTA) {
+			vec3 local = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+			vec3 direction = local.x * right + local.y * up + local.z * normal;
+			irradiance += textureLod(samplerCube(t_source, s_source), direction, 0.0).rgb * cos(theta) * sin(theta);
+			count += 1.0;
+		}
+	}
+	f_color = vec4(PI * irradiance / max(count, 1.0), 1.0);
+}
@@ -0,0 +1,67 @@
+#version 450
+
+#include <cube.glsl>
+
+layout(location=0) in vec2 v_uv;
+layout(location=0) out vec4 f_color;
+
+layout(set=0, binding=0) uniform textureCube t_source;
+layout(set=0, binding=1) uniform sampler s_source;
+
+layout(set=1, binding=0)
+uniform Post {
+	vec4 resolution;
+	vec4 parameters;
+};
+
+const float PI = 3.14159265359;
+
+vec2 hammersley(uint index, uint count) {
+	return vec2(float(index) / float(count), float(bitfieldReverse(index)) * 2.3283064365386963e-10);
+}
+
+vec3 importance_sample_ggx(vec2 xi, vec3 normal, float roughness) {
+	float a = roughness * roughness;
+	float phi = 2.0 * PI * xi.x;
+	float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+	float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+	vec3 up = abs(normal.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+	vec3 tangent = normalize(cross(up, normal));
+	vec3 bitangent = cross(normal, tangent);
+	return normalize(tangent * cos(phi) * sin_theta + bitangent * sin(phi) * sin_theta + normal * cos_theta);
+}
+
+float distribution_ggx(float n_dot_h, float roughness) {
+	float a = roughness * roughness;
+	float a2 = a * a;
+	float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+	return a2 / (PI * denominator * denominator);
+}
+
+void main() {
+	vec3 normal = get_cube_direction(int(parameters.x + 0.5), v_uv);
+	float roughness = parameters.y;
+	if (roughness <= 0.0) {
+		f_color = vec4(textureLod(samplerCube(t_source, s_source), normal, 0.0).rgb, 1.0);
+		return;
+	}
+
+	uint sample_count = max(uint(parameters.w), 1u);
+	float texel_angle = 4.0 * PI / (6.0 * parameters.z * parameters.z);
+	vec3 color = vec3(0.0);
+	float weight = 0.0;
+	for (uint index = 0u; index < sample_count; index++) {
+		vec3 half_vector = importance_sample_ggx(hammersley(index, sample_count), normal, roughness);
+		vec3 light = normalize(2.0 * dot(normal, half_vector) * half_vector - normal);
+		float n_dot_l = dot(normal, light);
+		if (n_dot_l > 0.0) {
+			float n_dot_h = max(dot(normal, half_vector), 0.0);
+			float pdf = distribution_ggx(n_dot_h, roughness) * 0.25 + 1e-4;
+			float sample_angle = 1.0 / (float(sample_count) * pdf + 1e-4);
+			float lod = max(0.5 * log2(sample_angle / texel_angle) + 1.0, 0.0);
+			color += textureLod(samplerCube(t_source, s_source), light, lod).rgb * n_dot_l;
+			weight += n_dot_l;
+		}
+	}
+	f_color = vec4(color / max(weight, 1e-4), 1.0);
+}
@@ -0,0 +1,32 @@
+#ifndef ENVIRONMENT_SET
+#define ENVIRONMENT_SET 0
+#endif
+
+#ifndef ENVIRONMENT_BINDING
+#define ENVIRONMENT_BINDING 0
+#endif
+
+layout(set=ENVIRONMENT_SET, binding=ENVIRONMENT_BINDING) uniform textureCube t_irradiance;
+layout(set=ENVIRONMENT_SET, binding=ENVIRONMENT_BINDING + 1) uniform sampler s_irradiance;
+layout(set=ENVIRONMENT_SET, binding=ENVIRONMENT_BINDING + 2) uniform textureCube t_specular;
+layout(set=ENVIRONMENT_SET, binding=ENVIRONMENT_BINDING + 3) uniform sampler s_specular;
+
+vec2 get_environment_brdf(float n_dot_v, float roughness) {
+	const vec4 c0 = vec4(-1.0, -0.0275, -0.572, 0.022);
+	const vec4 c1 = vec4(1.0, 0.0425, 1.04, -0.04);
+	vec4 r = roughness * c0 + c1;
+	float a004 = min(r.x * r.x, exp2(-9.28 * n_dot_v)) * r.x + r.y;
+	return vec2(-1.04, 1.04) * a004 + r.zw;
+}
+
+vec3 get_environment_lighting(vec3 n, vec3 v, vec3 albedo, float metallic, float roughness) {
+	vec3 f0 = mix(vec3(0.04), albedo, metallic);
+	float n_dot_v = max(dot(n, v), 1e-4);
+	float max_lod = float(textureQueryLevels(samplerCube(t_specular, s_specular)) - 1);
+	vec3 prefiltered = textureLod(samplerCube(t_specular, s_specular), reflect(-v, n), roughness * max_lod).rgb;
+	vec3 irradiance = texture(samplerCube(t_irradiance, s_irradiance), n).rgb;
+	vec2 brdf = get_environment_brdf(n_dot_v, roughness);
+	vec3 specular_weight = f0 * brdf.x + brdf.y;
+	vec3 diffuse_weight = (1.0 - specular_weight) * (1.0 - metallic);
+	return diffuse_weight * albedo * irradiance + specular_weight * prefiltered;
+}
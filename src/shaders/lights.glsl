@@ -0,0 +1,58 @@
+#ifndef LIGHTS_SET
+#define LIGHTS_SET 0
+#endif
+
+#ifndef LIGHTS_BINDING
+#define LIGHTS_BINDING 0
+#endif
+
+#define MAX_LIGHTS 64
+#define LIGHT_DIRECTIONAL 0
+#define LIGHT_POINT 1
+#define LIGHT_SPOT 2
+
+struct Light {
+	vec4 position_type;
+	vec4 direction_range;
+	vec4 color_intensity;
+	vec4 cone;
+};
+
+layout(set=LIGHTS_SET, binding=LIGHTS_BINDING)
+uniform Lights {
+	uvec4 light_counts;
+	vec4 ambient_light;
+	Light lights[MAX_LIGHTS];
+};
+
+float get_range_attenuation(float distance, float range) {
+	float inverse_square = 1.0 / max(distance * distance, 1e-4);
+	if (range <= 0.0) {
+		return inverse_square;
+	}
+	float ratio = distance / range;
+	float falloff = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+	return falloff * falloff * inverse_square;
+}
+
+float get_spot_attenuation(vec3 to_light, vec3 direction, float cos_inner, float cos_outer) {
+	return smoothstep(cos_outer, cos_inner, dot(direction, -to_light));
+}
+
+vec3 get_light_vector(Light light, vec3 position, out vec3 radiance) {
+	int kind = int(light.position_type.w + 0.5);
+	vec3 color = light.color_intensity.rgb * light.color_intensity.a;
+	if (kind == LIGHT_DIRECTIONAL) {
+		radiance = color;
+		return normalize(-light.direction_range.xyz);
+	}
+	vec3 offset = light.position_type.xyz - position;
+	float distance = length(offset);
+	vec3 to_light = offset / max(distance, 1e-4);
+	float attenuation = get_range_attenuation(distance, light.direction_range.w);
+	if (kind == LIGHT_SPOT) {
+		attenuation *= get_spot_attenuation(to_light, normalize(light.direction_range.xyz), light.cone.x, light.cone.y);
+	}
+	radiance = color * attenuation;
+	return to_light;
+}
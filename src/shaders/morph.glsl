@@ -0,0 +1,32 @@
+#ifndef MORPH_SET
+#define MORPH_SET 0
+#endif
+
+#define MAX_MORPH_TARGETS 64
+
+layout(set=MORPH_SET, binding=0)
+uniform MorphWeights {
+	uvec4 morph_counts;
+	vec4 morph_weights[MAX_MORPH_TARGETS / 4];
+};
+
+layout(set=MORPH_SET, binding=1)
+readonly buffer MorphDeltas {
+	vec4 morph_deltas[];
+};
+
+float get_morph_weight(uint index) {
+	return morph_weights[index / 4][index % 4];
+}
+
+void apply_morph_targets(uint vertex_index, inout vec3 position, inout vec3 normal) {
+	uint vertex_count = morph_counts.x;
+	for (uint index = 0; index < morph_counts.y; index++) {
+		float weight = get_morph_weight(index);
+		if (weight != 0.0) {
+			uint offset = (index * vertex_count + vertex_index) * 2;
+			position += morph_deltas[offset].xyz * weight;
+			normal += morph_deltas[offset + 1].xyz * weight;
+		}
+	}
+}
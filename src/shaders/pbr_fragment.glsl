@@ -0,0 +1,127 @@
+#version 450
+
+const float PI = 3.14159265359;
+
+layout(location=0) in vec3 v_position;
+layout(location=1) in vec4 v_color;
+layout(location=2) in vec2 v_uv;
+layout(location=3) in vec3 v_normal;
+layout(location=4) in vec4 v_tangent;
+
+layout(location=0) out vec4 f_color;
+
+layout(set=0, binding=0)
+uniform Camera {
+	mat4 view;
+	mat4 projection;
+	mat4 view_projection;
+	vec4 camera_position;
+};
+
+#define LIGHTS_SET 0
+#define LIGHTS_BINDING 1
+#include <lights.glsl>
+
+#define SHADOWS_SET 0
+#define SHADOWS_BINDING 2
+#include <shadows.glsl>
+
+#define ENVIRONMENT_SET 0
+#define ENVIRONMENT_BINDING 5
+#include <ibl.glsl>
+
+layout(set=2, binding=0) uniform texture2D t_base_color;
+layout(set=2, binding=1) uniform sampler s_base_color;
+layout(set=2, binding=2) uniform texture2D t_metallic_roughness;
+layout(set=2, binding=3) uniform sampler s_metallic_roughness;
+layout(set=2, binding=4) uniform texture2D t_normal;
+layout(set=2, binding=5) uniform sampler s_normal;
+layout(set=2, binding=6) uniform texture2D t_occlusion;
+layout(set=2, binding=7) uniform sampler s_occlusion;
+layout(set=2, binding=8) uniform texture2D t_emissive;
+layout(set=2, binding=9) uniform sampler s_emissive;
+
+layout(set=3, binding=0)
+uniform Material {
+	vec4 base_color_factor;
+	vec4 emissive_factor;
+	float metallic_factor;
+	float roughness_factor;
+	float normal_scale;
+	float occlusion_strength;
+	float alpha_cutoff;
+};
+
+float distribution_ggx(float n_dot_h, float roughness) {
+	float a = roughness * roughness;
+	float a2 = a * a;
+	float d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+	return a2 / max(PI * d * d, 1e-7);
+}
+
+float geometry_schlick_ggx(float n_dot_x, float roughness) {
+	float r = roughness + 1.0;
+	float k = r * r / 8.0;
+	return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+	return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+vec3 get_normal() {
+	vec3 n = normalize(v_normal);
+	if (!gl_FrontFacing) {
+		n = -n;
+	}
+	vec3 t = v_tangent.xyz - n * dot(n, v_tangent.xyz);
+	if (dot(t, t) < 1e-8) {
+		return n;
+	}
+	t = normalize(t);
+	vec3 b = cross(n, t) * (v_tangent.w < 0.0 ? -1.0 : 1.0);
+	vec3 sampled = texture(sampler2D(t_normal, s_normal), v_uv).xyz * 2.0 - 1.0;
+	sampled.xy *= normal_scale;
+	return normalize(mat3(t, b, n) * sampled);
+}
+
+vec3 shade(vec3 n, vec3 v, vec3 l, vec3 radiance, vec3 albedo, float metallic, float roughness) {
+	vec3 h = normalize(v + l);
+	float n_dot_l = max(dot(n, l), 0.0);
+	float n_dot_v = max(dot(n, v), 1e-4);
+	float n_dot_h = max(dot(n, h), 0.0);
+	vec3 f0 = mix(vec3(0.04), albedo, metallic);
+	vec3 f = fresnel_schlick(max(dot(h, v), 0.0), f0);
+	float d = distribution_ggx(n_dot_h, roughness);
+	float g = geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+	vec3 specular = d * g * f / max(4.0 * n_dot_v * n_dot_l, 1e-4);
+	vec3 diffuse = (1.0 - f) * (1.0 - metallic) * albedo / PI;
+	return (diffuse + specular) * radiance * n_dot_l;
+}
+
+void main() {
+	vec4 base_color = texture(sampler2D(t_base_color, s_base_color), v_uv) * base_color_factor * v_color;
+	if (base_color.a < alpha_cutoff) {
+		discard;
+	}
+	vec4 metallic_roughness = texture(sampler2D(t_metallic_roughness, s_metallic_roughness), v_uv);
+	float metallic = clamp(metallic_roughness.b * metallic_factor, 0.0, 1.0);
+	float roughness = clamp(metallic_roughness.g * roughness_factor, 0.04, 1.0);
+	float occlusion = mix(1.0, texture(sampler2D(t_occlusion, s_occlusion), v_uv).r, occlusion_strength);
+	vec3 emissive = texture(sampler2D(t_emissive, s_emissive), v_uv).rgb * emissive_factor.rgb;
+
+	vec3 n = get_normal();
+	vec3 v = normalize(camera_position.xyz - v_position);
+	vec3 geometric_normal = normalize(v_normal);
+	float view_depth = -(view * vec4(v_position, 1.0)).z;
+	vec3 color = ambient_light.rgb * base_color.rgb * occlusion;
+	color += get_environment_lighting(n, v, base_color.rgb, metallic, roughness) * occlusion;
+	for (uint i = 0u; i < min(light_counts.x, uint(MAX_LIGHTS)); i++) {
+		vec3 radiance;
+		vec3 l = get_light_vector(lights[i], v_position, radiance);
+		radiance *= get_shadow(lights[i].position_type, lights[i].cone, v_position, view_depth, geometric_normal, l);
+		color += shade(n, v, l, radiance, base_color.rgb, metallic, roughness);
+	}
+	color += emissive;
+	f_color = vec4(color, base_color.a);
+}
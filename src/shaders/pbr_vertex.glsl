@@ -0,0 +1,37 @@
+#version 450
+
+layout(location=0) in vec3 a_position;
+layout(location=1) in vec4 a_color;
+layout(location=2) in vec2 a_uv;
+layout(location=3) in vec3 a_normal;
+layout(location=4) in vec4 a_tangent;
+
+layout(location=0) out vec3 v_position;
+layout(location=1) out vec4 v_color;
+layout(location=2) out vec2 v_uv;
+layout(location=3) out vec3 v_normal;
+layout(location=4) out vec4 v_tangent;
+
+layout(set=0, binding=0)
+uniform Camera {
+	mat4 view;
+	mat4 projection;
+	mat4 view_projection;
+	vec4 camera_position;
+};
+
+layout(set=1, binding=0)
+uniform Model {
+	mat4 model;
+	mat4 normal_matrix;
+};
+
+void main() {
+	vec4 world_position = model * vec4(a_position, 1.0);
+	v_position = world_position.xyz;
+	v_color = a_color;
+	v_uv = a_uv;
+	v_normal = mat3(normal_matrix) * a_normal;
+	v_tangent = vec4(mat3(model) * a_tangent.xyz, a_tangent.w);
+	gl_Position = view_projection * world_position;
+}
@@ -0,0 +1,19 @@
+layout(location=0) in vec2 v_uv;
+layout(location=0) out vec4 f_color;
+
+layout(set=0, binding=0) uniform texture2D t_input;
+layout(set=0, binding=1) uniform sampler s_input;
+
+layout(set=1, binding=0)
+uniform Post {
+	vec4 resolution;
+	vec4 parameters;
+};
+
+vec4 sample_input(vec2 uv) {
+	return texture(sampler2D(t_input, s_input), uv);
+}
+
+float get_luma(vec3 color) {
+	return dot(color, vec3(0.299, 0.587, 0.114));
+}
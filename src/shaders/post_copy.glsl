@@ -0,0 +1,7 @@
+#version 450
+
+#include <post.glsl>
+
+void main() {
+	f_color = sample_input(v_uv);
+}
@@ -0,0 +1,46 @@
+#version 450
+
+#include <post.glsl>
+
+#define FXAA_REDUCE_MIN (1.0 / 128.0)
+
+void main() {
+	vec2 texel = resolution.zw;
+	float reduce_multiplier = parameters.x;
+	float span_max = parameters.y;
+	vec3 rgb_nw = sample_input(v_uv + vec2(-1.0, -1.0) * texel).rgb;
+	vec3 rgb_ne = sample_input(v_uv + vec2(1.0, -1.0) * texel).rgb;
+	vec3 rgb_sw = sample_input(v_uv + vec2(-1.0, 1.0) * texel).rgb;
+	vec3 rgb_se = sample_input(v_uv + vec2(1.0, 1.0) * texel).rgb;
+	vec4 rgba_m = sample_input(v_uv);
+	float luma_nw = get_luma(rgb_nw);
+	float luma_ne = get_luma(rgb_ne);
+	float luma_sw = get_luma(rgb_sw);
+	float luma_se = get_luma(rgb_se);
+	float luma_m = get_luma(rgba_m.rgb);
+	float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+	float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+	vec2 direction = vec2(
+		-((luma_nw + luma_ne) - (luma_sw + luma_se)),
+		(luma_nw + luma_sw) - (luma_ne + luma_se)
+	);
+	float direction_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * reduce_multiplier, FXAA_REDUCE_MIN);
+	float inverse_direction_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+	direction = clamp(direction * inverse_direction_min, vec2(-span_max), vec2(span_max)) * texel;
+
+	vec3 rgb_a = 0.5 * (
+		sample_input(v_uv + direction * (1.0 / 3.0 - 0.5)).rgb +
+		sample_input(v_uv + direction * (2.0 / 3.0 - 0.5)).rgb
+	);
+	vec3 rgb_b = rgb_a * 0.5 + 0.25 * (
+		sample_input(v_uv + direction * -0.5).rgb +
+		sample_input(v_uv + direction * 0.5).rgb
+	);
+	float luma_b = get_luma(rgb_b);
+	if (luma_b < luma_min || luma_b > luma_max) {
+		f_color = vec4(rgb_a, rgba_m.a);
+	} else {
+		f_color = vec4(rgb_b, rgba_m.a);
+	}
+}
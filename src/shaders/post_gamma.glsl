@@ -0,0 +1,8 @@
+#version 450
+
+#include <post.glsl>
+
+void main() {
+	vec4 color = sample_input(v_uv);
+	f_color = vec4(pow(max(color.rgb, vec3(0.0)), vec3(1.0 / max(parameters.x, 1e-4))), color.a);
+}
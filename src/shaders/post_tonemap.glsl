@@ -0,0 +1,48 @@
+#version 450
+
+#include <post.glsl>
+
+vec3 tonemap_reinhard(vec3 color) {
+	return color / (1.0 + color);
+}
+
+vec3 tonemap_aces(vec3 color) {
+	const float a = 2.51;
+	const float b = 0.03;
+	const float c = 2.43;
+	const float d = 0.59;
+	const float e = 0.14;
+	return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+vec3 uncharted2_curve(vec3 x) {
+	const float a = 0.15;
+	const float b = 0.50;
+	const float c = 0.10;
+	const float d = 0.20;
+	const float e = 0.02;
+	const float f = 0.30;
+	return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+vec3 tonemap_uncharted2(vec3 color) {
+	float white = max(parameters.z, 1e-4);
+	vec3 curve = uncharted2_curve(2.0 * color);
+	vec3 white_scale = 1.0 / uncharted2_curve(vec3(white));
+	return curve * white_scale;
+}
+
+void main() {
+	vec4 color = sample_input(v_uv);
+	vec3 exposed = max(color.rgb * parameters.x, vec3(0.0));
+	int mode = int(parameters.y + 0.5);
+	vec3 mapped;
+	if (mode == 1) {
+		mapped = tonemap_aces(exposed);
+	} else if (mode == 2) {
+		mapped = tonemap_uncharted2(exposed);
+	} else {
+		mapped = tonemap_reinhard(exposed);
+	}
+	f_color = vec4(mapped, color.a);
+}
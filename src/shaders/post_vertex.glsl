@@ -0,0 +1,10 @@
+#version 450
+
+layout(location=0) in vec3 a_position;
+
+layout(location=0) out vec2 v_uv;
+
+void main() {
+	v_uv = vec2(a_position.x * 0.5 + 0.5, 0.5 - a_position.y * 0.5);
+	gl_Position = vec4(a_position.xy, 0.0, 1.0);
+}
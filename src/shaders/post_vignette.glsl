@@ -0,0 +1,10 @@
+#version 450
+
+#include <post.glsl>
+
+void main() {
+	vec4 color = sample_input(v_uv);
+	vec2 offset = (v_uv - 0.5) * vec2(resolution.x / resolution.y, 1.0);
+	float vignette = 1.0 - smoothstep(parameters.y - parameters.z, parameters.y, length(offset));
+	f_color = vec4(color.rgb * mix(1.0, vignette, parameters.x), color.a);
+}
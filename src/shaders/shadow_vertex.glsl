@@ -0,0 +1,21 @@
+#version 450
+
+layout(location=0) in vec3 a_position;
+
+layout(set=0, binding=0)
+uniform Camera {
+	mat4 view;
+	mat4 projection;
+	mat4 view_projection;
+	vec4 camera_position;
+};
+
+layout(set=1, binding=0)
+uniform Model {
+	mat4 model;
+	mat4 normal_matrix;
+};
+
+void main() {
+	gl_Position = view_projection * model * vec4(a_position, 1.0);
+}
@@ -0,0 +1,68 @@
+#ifndef SHADOWS_SET
+#define SHADOWS_SET 0
+#endif
+
+#ifndef SHADOWS_BINDING
+#define SHADOWS_BINDING 0
+#endif
+
+#define MAX_SHADOW_LAYERS 8
+#define MAX_CASCADES 4
+
+layout(set=SHADOWS_SET, binding=SHADOWS_BINDING) uniform texture2DArray t_shadow;
+layout(set=SHADOWS_SET, binding=SHADOWS_BINDING + 1) uniform samplerShadow s_shadow;
+
+layout(set=SHADOWS_SET, binding=SHADOWS_BINDING + 2)
+uniform Shadows {
+	mat4 shadow_matrices[MAX_SHADOW_LAYERS];
+	vec4 cascade_splits;
+	vec4 shadow_params;
+	uvec4 shadow_counts;
+};
+
+float sample_shadow_pcf(int layer, vec3 position, vec3 normal, vec3 to_light) {
+	if (layer < 0 || layer >= int(shadow_counts.y)) {
+		return 1.0;
+	}
+	float normal_offset = shadow_params.y * (1.0 - clamp(dot(normal, to_light), 0.0, 1.0));
+	vec4 clip = shadow_matrices[layer] * vec4(position + normal * normal_offset, 1.0);
+	vec3 ndc = clip.xyz / clip.w;
+	vec2 uv = vec2(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+	if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 || ndc.z > 1.0) {
+		return 1.0;
+	}
+	float depth = ndc.z - shadow_params.x;
+	int radius = int(shadow_params.z);
+	float sum = 0.0;
+	for (int x = -radius; x <= radius; x++) {
+		for (int y = -radius; y <= radius; y++) {
+			vec2 offset = vec2(float(x), float(y)) * shadow_params.w;
+			sum += texture(sampler2DArrayShadow(t_shadow, s_shadow), vec4(uv + offset, float(layer), depth));
+		}
+	}
+	float size = float(radius * 2 + 1);
+	return sum / (size * size);
+}
+
+int get_cascade_layer(int base, float view_depth) {
+	int cascade = int(shadow_counts.x) - 1;
+	for (int i = 0; i < int(shadow_counts.x) && i < MAX_CASCADES; i++) {
+		if (view_depth <= cascade_splits[i]) {
+			cascade = i;
+			break;
+		}
+	}
+	return base + cascade;
+}
+
+float get_shadow(vec4 position_type, vec4 cone, vec3 position, float view_depth, vec3 normal, vec3 to_light) {
+	int base = int(floor(cone.z + 0.5));
+	if (base < 0) {
+		return 1.0;
+	}
+	int layer = base;
+	if (int(position_type.w + 0.5) == 0 && shadow_counts.x > 0u) {
+		layer = get_cascade_layer(base, view_depth);
+	}
+	return sample_shadow_pcf(layer, position, normal, to_light);
+}
@@ -0,0 +1,19 @@
+#version 450
+
+layout(location=0) in vec3 a_position;
+
+layout(location=0) out vec3 v_direction;
+
+layout(set=0, binding=0)
+uniform Camera {
+	mat4 view;
+	mat4 projection;
+	mat4 view_projection;
+	vec4 camera_position;
+};
+
+void main() {
+	vec4 near_point = inverse(projection) * vec4(a_position.xy, 0.0, 1.0);
+	v_direction = transpose(mat3(view)) * (near_point.xyz / near_point.w);
+	gl_Position = vec4(a_position.xy, 1.0, 1.0);
+}
@@ -0,0 +1,366 @@
+use crate::{Bind, Camera, CameraUniform, Renderer, Uniform, Pipeline, VertexLayoutTrait, IndexTrait};
+use crate::camera::{Projection, OPENGL_TO_WGPU_MATRIX};
+use crate::pipeline::PipelineOptions;
+use crate::renderer::DEPTH_FORMAT;
+use crate::scene::ModelUniform;
+use bytemuck::{bytes_of, Pod, Zeroable};
+use cgmath::{Matrix4, Point3, Vector3, Vector4, Rad, InnerSpace, EuclideanSpace, SquareMatrix, perspective, ortho};
+use shaderc::Compiler;
+use std::any::TypeId;
+use std::mem::size_of;
+use wgpu::*;
+
+pub const MAX_SHADOW_LAYERS: usize = 8;
+pub const MAX_CASCADES: usize = 4;
+
+const SHADOW_VERTEX_SHADER: &str = include_str!("shaders/shadow_vertex.glsl");
+const SHADOW_FRAGMENT_SHADER: &str = include_str!("shaders/shadow_fragment.glsl");
+const CASTER_EXTENT: f32 = 4.0;
+const MAX_SPOT_FOV: f32 = 3.1;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct ShadowUniform {
+	matrices: [[[f32; 4]; 4]; MAX_SHADOW_LAYERS],
+	cascade_splits: [f32; 4],
+	params: [f32; 4],
+	counts: [u32; 4],
+}
+
+unsafe impl Pod for ShadowUniform {}
+
+unsafe impl Zeroable for ShadowUniform {}
+
+pub struct ShadowMap {
+	_texture: wgpu::Texture,
+	array_view: TextureView,
+	layer_views: Vec<TextureView>,
+	sampler: Sampler,
+	buffer: Buffer,
+	bind_group: BindGroup,
+	cameras: Vec<Uniform<CameraUniform>>,
+	data: ShadowUniform,
+	size: u32,
+}
+
+impl ShadowMap {
+	pub fn new(renderer: &Renderer, size: u32, layer_count: u32) -> ShadowMap {
+		if layer_count == 0 || layer_count as usize > MAX_SHADOW_LAYERS {
+			panic!("shadow_layer_count_invalid");
+		}
+		let texture_desc = TextureDescriptor {
+			size: Extent3d {
+				width: size,
+				height: size,
+				depth: 1,
+			},
+			array_layer_count: layer_count,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: TextureDimension::D2,
+			format: DEPTH_FORMAT,
+			usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+			label: Some("shadow_map"),
+		};
+		let _texture = renderer.get_device().create_texture(&texture_desc);
+		let array_view = _texture.create_view(&TextureViewDescriptor {
+			format: DEPTH_FORMAT,
+			dimension: TextureViewDimension::D2Array,
+			aspect: TextureAspect::All,
+			base_mip_level: 0,
+			level_count: 1,
+			base_array_layer: 0,
+			array_layer_count: layer_count,
+		});
+		let layer_views = (0..layer_count).map(|layer| _texture.create_view(&TextureViewDescriptor {
+			format: DEPTH_FORMAT,
+			dimension: TextureViewDimension::D2,
+			aspect: TextureAspect::All,
+			base_mip_level: 0,
+			level_count: 1,
+			base_array_layer: layer,
+			array_layer_count: 1,
+		})).collect();
+		let sampler_desc = SamplerDescriptor {
+			address_mode_u: AddressMode::ClampToEdge,
+			address_mode_v: AddressMode::ClampToEdge,
+			address_mode_w: AddressMode::ClampToEdge,
+			mag_filter: FilterMode::Linear,
+			min_filter: FilterMode::Linear,
+			mipmap_filter: FilterMode::Nearest,
+			lod_min_clamp: -100.0,
+			lod_max_clamp: 100.0,
+			compare: CompareFunction::LessEqual,
+		};
+		let sampler = renderer.get_device().create_sampler(&sampler_desc);
+		let data = ShadowUniform {
+			matrices: [Matrix4::identity().into(); MAX_SHADOW_LAYERS],
+			cascade_splits: [0.0; 4],
+			params: [0.0005, 0.02, 1.0, 1.0 / size as f32],
+			counts: [0, layer_count, 0, 0],
+		};
+		let buffer = renderer
+			.get_device()
+			.create_buffer_with_data(bytes_of(&data), BufferUsage::UNIFORM | BufferUsage::COPY_DST);
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("shadow_map_type_not_registered"),
+			bindings: &[
+				Binding {
+					binding: 0,
+					resource: BindingResource::TextureView(&array_view),
+				},
+				Binding {
+					binding: 1,
+					resource: BindingResource::Sampler(&sampler),
+				},
+				Binding {
+					binding: 2,
+					resource: BindingResource::Buffer {
+						buffer: &buffer,
+						range: 0..size_of::<ShadowUniform>() as BufferAddress,
+					},
+				},
+			],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+		let camera = CameraUniform {
+			view: Matrix4::identity().into(),
+			projection: Matrix4::identity().into(),
+			view_projection: Matrix4::identity().into(),
+			position: [0.0; 4],
+		};
+		let cameras = (0..layer_count).map(|_| Uniform::new(renderer, &camera)).collect();
+
+		ShadowMap {
+			_texture,
+			array_view,
+			layer_views,
+			sampler,
+			buffer,
+			bind_group,
+			cameras,
+			data,
+			size,
+		}
+	}
+
+	pub fn set_layer_matrix(&mut self, layer: u32, view_projection: Matrix4<f32>) {
+		if layer >= self.get_layer_count() {
+			panic!("shadow_layer_out_of_range");
+		}
+		self.data.matrices[layer as usize] = view_projection.into();
+	}
+
+	pub fn get_layer_matrix(&self, layer: u32) -> Matrix4<f32> {
+		self.data.matrices[layer as usize].into()
+	}
+
+	pub fn set_cascades(&mut self, first_layer: u32, camera: &Camera, direction: Vector3<f32>, cascade_count: u32, lambda: f32) {
+		if cascade_count == 0 || cascade_count as usize > MAX_CASCADES || first_layer + cascade_count > self.get_layer_count() {
+			panic!("shadow_cascade_count_invalid");
+		}
+		let (near, far) = get_near_far(camera);
+		let splits = compute_cascade_splits(near, far, cascade_count, lambda);
+		let mut previous = near;
+		for (index, split) in splits.iter().enumerate() {
+			let matrix = compute_cascade_matrix(camera, previous, *split, direction, self.size);
+			self.set_layer_matrix(first_layer + index as u32, matrix);
+			self.data.cascade_splits[index] = *split;
+			previous = *split;
+		}
+		self.data.counts[0] = cascade_count;
+	}
+
+	pub fn set_spot(&mut self, layer: u32, position: Vector3<f32>, direction: Vector3<f32>, outer_angle: f32, range: f32) {
+		self.set_layer_matrix(layer, compute_spot_matrix(position, direction, outer_angle, range));
+	}
+
+	pub fn set_bias(&mut self, depth_bias: f32, normal_bias: f32) {
+		self.data.params[0] = depth_bias;
+		self.data.params[1] = normal_bias;
+	}
+
+	pub fn get_bias(&self) -> (f32, f32) {
+		(self.data.params[0], self.data.params[1])
+	}
+
+	pub fn set_filter_radius(&mut self, radius: u32) {
+		self.data.params[2] = radius as f32;
+	}
+
+	pub fn get_filter_radius(&self) -> u32 {
+		self.data.params[2] as u32
+	}
+
+	pub fn update(&self, renderer: &Renderer) {
+		for (camera, matrix) in self.cameras.iter().zip(&self.data.matrices) {
+			let camera_data = CameraUniform {
+				view: Matrix4::identity().into(),
+				projection: *matrix,
+				view_projection: *matrix,
+				position: [0.0; 4],
+			};
+			camera.update(renderer, &camera_data);
+		}
+		let data = bytes_of(&self.data);
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("shadow_update_encoder"),
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		let staging_buffer = renderer.get_device().create_buffer_with_data(data, BufferUsage::COPY_SRC);
+		encoder.copy_buffer_to_buffer(&staging_buffer, 0, &self.buffer, 0, data.len() as u64);
+		renderer.get_queue().submit(&[encoder.finish()]);
+	}
+
+	pub fn get_layer_view(&self, layer: u32) -> &TextureView {
+		&self.layer_views[layer as usize]
+	}
+
+	pub fn get_layer_camera(&self, layer: u32) -> &Uniform<CameraUniform> {
+		&self.cameras[layer as usize]
+	}
+
+	pub fn get_layer_count(&self) -> u32 {
+		self.layer_views.len() as u32
+	}
+
+	pub fn get_size(&self) -> u32 {
+		self.size
+	}
+
+	pub fn get_view(&self) -> &TextureView {
+		&self.array_view
+	}
+
+	pub fn get_sampler(&self) -> &Sampler {
+		&self.sampler
+	}
+
+	pub fn get_buffer(&self) -> &Buffer {
+		&self.buffer
+	}
+}
+
+impl Bind for ShadowMap {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[
+				BindGroupLayoutEntry {
+					binding: 0,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::SampledTexture {
+						multisampled: false,
+						dimension: TextureViewDimension::D2Array,
+						component_type: TextureComponentType::Float,
+					},
+				},
+				BindGroupLayoutEntry {
+					binding: 1,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::Sampler {
+						comparison: true,
+					},
+				},
+				BindGroupLayoutEntry {
+					binding: 2,
+					visibility: ShaderStage::FRAGMENT,
+					ty: BindingType::UniformBuffer { dynamic: false },
+				},
+			],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+pub fn get_shadow_binds() -> [TypeId; 2] {
+	[
+		TypeId::of::<Uniform<CameraUniform>>(),
+		TypeId::of::<Uniform<ModelUniform>>(),
+	]
+}
+
+pub fn register_shadows(renderer: &mut Renderer) {
+	if renderer.get_bind_group_layout::<Uniform<CameraUniform>>().is_none() {
+		renderer.register_bind_group_layout::<Uniform<CameraUniform>>();
+	}
+	if renderer.get_bind_group_layout::<Uniform<ModelUniform>>().is_none() {
+		renderer.register_bind_group_layout::<Uniform<ModelUniform>>();
+	}
+	if renderer.get_bind_group_layout::<ShadowMap>().is_none() {
+		renderer.register_bind_group_layout::<ShadowMap>();
+	}
+	let binds = get_shadow_binds();
+	if renderer.get_pipeline_layout(&binds).is_none() {
+		renderer.register_pipeline_layout(&binds);
+	}
+}
+
+pub fn create_shadow_pipeline<V: VertexLayoutTrait, I: IndexTrait>(renderer: &Renderer, compiler: &mut Compiler, options: &PipelineOptions) -> Pipeline<V, I> {
+	Pipeline::new_with_options(renderer, compiler, SHADOW_VERTEX_SHADER, SHADOW_FRAGMENT_SHADER, &get_shadow_binds(), options)
+}
+
+pub fn compute_cascade_splits(near: f32, far: f32, cascade_count: u32, lambda: f32) -> Vec<f32> {
+	(1..=cascade_count).map(|index| {
+		let ratio = index as f32 / cascade_count as f32;
+		let logarithmic = near * (far / near).powf(ratio);
+		let uniform = near + (far - near) * ratio;
+		lambda * logarithmic + (1.0 - lambda) * uniform
+	}).collect()
+}
+
+pub fn compute_cascade_matrix(camera: &Camera, near: f32, far: f32, direction: Vector3<f32>, resolution: u32) -> Matrix4<f32> {
+	let projection = match camera.projection {
+		Projection::Perspective { fov_y, .. } => perspective(Rad(fov_y), camera.aspect, near, far),
+		Projection::Orthographic { height, .. } => {
+			let half_height = height * 0.5;
+			let half_width = half_height * camera.aspect;
+			ortho(-half_width, half_width, -half_height, half_height, near, far)
+		}
+	};
+	let inverse = (OPENGL_TO_WGPU_MATRIX * projection * camera.get_view_matrix()).invert().expect("camera_matrix_not_invertible");
+	let mut corners = Vec::with_capacity(8);
+	for &x in &[-1.0, 1.0] {
+		for &y in &[-1.0, 1.0] {
+			for &z in &[0.0, 1.0] {
+				let corner = inverse * Vector4::new(x, y, z, 1.0);
+				corners.push(corner.truncate() / corner.w);
+			}
+		}
+	}
+	let center = corners.iter().fold(Vector3::new(0.0, 0.0, 0.0), |sum, corner| sum + corner) / corners.len() as f32;
+	let radius = corners.iter().map(|corner| (corner - center).magnitude()).fold(0.0, f32::max);
+	let radius = (radius * 16.0).ceil() / 16.0;
+
+	let direction = direction.normalize();
+	let up = if direction.y.abs() > 0.99 { Vector3::unit_z() } else { Vector3::unit_y() };
+	let view = Matrix4::look_at(Point3::from_vec(center), Point3::from_vec(center + direction), up);
+	let projection = OPENGL_TO_WGPU_MATRIX * ortho(-radius, radius, -radius, radius, -radius * CASTER_EXTENT, radius);
+	let matrix = projection * view;
+
+	let origin = matrix * Vector4::new(0.0, 0.0, 0.0, 1.0);
+	let texel_scale = resolution as f32 * 0.5;
+	let offset_x = (origin.x * texel_scale).round() / texel_scale - origin.x;
+	let offset_y = (origin.y * texel_scale).round() / texel_scale - origin.y;
+	Matrix4::from_translation(Vector3::new(offset_x, offset_y, 0.0)) * matrix
+}
+
+pub fn compute_spot_matrix(position: Vector3<f32>, direction: Vector3<f32>, outer_angle: f32, range: f32) -> Matrix4<f32> {
+	let direction = direction.normalize();
+	let up = if direction.y.abs() > 0.99 { Vector3::unit_z() } else { Vector3::unit_y() };
+	let view = Matrix4::look_at(Point3::from_vec(position), Point3::from_vec(position + direction), up);
+	let far = if range > 0.0 { range } else { 1000.0 };
+	OPENGL_TO_WGPU_MATRIX * perspective(Rad((outer_angle * 2.0).min(MAX_SPOT_FOV)), 1.0, far * 0.001, far) * view
+}
+
+fn get_near_far(camera: &Camera) -> (f32, f32) {
+	match camera.projection {
+		Projection::Perspective { near, far, .. } => (near, far),
+		Projection::Orthographic { near, far, .. } => (near, far),
+	}
+}
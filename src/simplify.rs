@@ -0,0 +1,382 @@
+use crate::{AttributeTrait, IndexTrait, MeshData};
+use cgmath::{Vector3, InnerSpace};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+const BOUNDARY_WEIGHT: f64 = 10.0;
+
+#[derive(Clone, Copy, Default)]
+struct Quadric {
+	values: [f64; 10],
+	weight: f64,
+}
+
+impl Quadric {
+	fn from_plane(normal: Vector3<f64>, point: Vector3<f64>, weight: f64) -> Quadric {
+		let d = -normal.dot(point);
+		let (x, y, z) = (normal.x, normal.y, normal.z);
+		let mut values = [x * x, x * y, x * z, x * d, y * y, y * z, y * d, z * z, z * d, d * d];
+		for value in values.iter_mut() {
+			*value *= weight;
+		}
+		Quadric {
+			values,
+			weight,
+		}
+	}
+
+	fn add(&mut self, other: &Quadric) {
+		for (value, other) in self.values.iter_mut().zip(other.values.iter()) {
+			*value += other;
+		}
+		self.weight += other.weight;
+	}
+
+	fn evaluate(&self, point: Vector3<f64>) -> f64 {
+		let q = &self.values;
+		let (x, y, z) = (point.x, point.y, point.z);
+		let error = x * x * q[0] + 2.0 * x * y * q[1] + 2.0 * x * z * q[2] + 2.0 * x * q[3]
+			+ y * y * q[4] + 2.0 * y * z * q[5] + 2.0 * y * q[6]
+			+ z * z * q[7] + 2.0 * z * q[8] + q[9];
+		if self.weight > 0.0 { (error / self.weight).max(0.0) } else { 0.0 }
+	}
+}
+
+struct Candidate {
+	cost: f64,
+	from: u32,
+	to: u32,
+	stamps: (u32, u32),
+}
+
+impl PartialEq for Candidate {
+	fn eq(&self, other: &Candidate) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+	fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Candidate {
+	fn cmp(&self, other: &Candidate) -> Ordering {
+		other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+	}
+}
+
+struct Simplifier {
+	positions: Vec<Vector3<f64>>,
+	groups: Vec<u32>,
+	members: Vec<Vec<u32>>,
+	quadrics: Vec<Quadric>,
+	triangles: Vec<[u32; 3]>,
+	alive: Vec<bool>,
+	adjacency: Vec<Vec<u32>>,
+	stamps: Vec<u32>,
+	removed: Vec<bool>,
+	heap: BinaryHeap<Candidate>,
+}
+
+pub(crate) fn simplify<V: AttributeTrait, I: IndexTrait>(data: &MeshData<V, I>, target_triangle_count: usize, target_error: f32) -> (Vec<I>, f32) {
+	let radius = match data.compute_bounding_sphere() {
+		Some(sphere) if sphere.radius > 0.0 => sphere.radius as f64,
+		_ => return (data.indices.clone(), 0.0),
+	};
+
+	let mut lookup: HashMap<[u32; 3], u32> = HashMap::new();
+	let mut positions = Vec::new();
+	let mut members: Vec<Vec<u32>> = Vec::new();
+	let mut groups = Vec::with_capacity(data.vertices.len());
+	for (index, vertex) in data.vertices.iter().enumerate() {
+		let position = vertex.get_position();
+		let key = [(position[0] + 0.0).to_bits(), (position[1] + 0.0).to_bits(), (position[2] + 0.0).to_bits()];
+		let group = *lookup.entry(key).or_insert_with(|| {
+			positions.push(Vector3::new(position[0] as f64, position[1] as f64, position[2] as f64));
+			members.push(Vec::new());
+			(positions.len() - 1) as u32
+		});
+		members[group as usize].push(index as u32);
+		groups.push(group);
+	}
+
+	let triangles: Vec<[u32; 3]> = data.indices
+		.chunks_exact(3)
+		.map(|triangle| [triangle[0].to_u32(), triangle[1].to_u32(), triangle[2].to_u32()])
+		.filter(|triangle| {
+			let [a, b, c] = [groups[triangle[0] as usize], groups[triangle[1] as usize], groups[triangle[2] as usize]];
+			a != b && b != c && c != a
+		})
+		.collect();
+
+	let mut simplifier = Simplifier {
+		quadrics: vec![Quadric::default(); positions.len()],
+		adjacency: vec![Vec::new(); positions.len()],
+		stamps: vec![0; positions.len()],
+		removed: vec![false; positions.len()],
+		alive: vec![true; triangles.len()],
+		heap: BinaryHeap::new(),
+		positions,
+		groups,
+		members,
+		triangles,
+	};
+	simplifier.build_quadrics();
+
+	let mut alive_count = simplifier.triangles.len();
+	let mut max_error = 0.0f64;
+	let limit = target_error as f64 * radius;
+	while alive_count > target_triangle_count {
+		let candidate = match simplifier.heap.pop() {
+			Some(candidate) => candidate,
+			None => break,
+		};
+		if simplifier.removed[candidate.from as usize] || simplifier.removed[candidate.to as usize]
+			|| simplifier.stamps[candidate.from as usize] != candidate.stamps.0
+			|| simplifier.stamps[candidate.to as usize] != candidate.stamps.1 {
+			continue;
+		}
+		let error = candidate.cost.sqrt();
+		if error > limit {
+			break;
+		}
+		if !simplifier.can_collapse(candidate.from, candidate.to) {
+			continue;
+		}
+		alive_count -= simplifier.collapse(&data.vertices, candidate.from, candidate.to);
+		max_error = max_error.max(error);
+	}
+
+	let indices = simplifier.triangles.iter()
+		.zip(&simplifier.alive)
+		.filter(|(_, alive)| **alive)
+		.flat_map(|(triangle, _)| triangle.iter().map(|index| I::from_u32(*index)))
+		.collect();
+	(indices, (max_error / radius) as f32)
+}
+
+impl Simplifier {
+	fn build_quadrics(&mut self) {
+		let mut edges: HashMap<(u32, u32), (u32, usize)> = HashMap::new();
+		for (index, triangle) in self.triangles.iter().enumerate() {
+			let corners = self.get_corners(triangle);
+			let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+			let length = normal.magnitude();
+			if length > 0.0 {
+				let quadric = Quadric::from_plane(normal / length, corners[0], length * 0.5);
+				for vertex in triangle {
+					self.quadrics[self.groups[*vertex as usize] as usize].add(&quadric);
+				}
+			}
+			for k in 0..3 {
+				let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+				let entry = edges.entry((a.min(b), a.max(b))).or_insert((0, index));
+				entry.0 += 1;
+			}
+			for vertex in triangle {
+				self.adjacency[self.groups[*vertex as usize] as usize].push(index as u32);
+			}
+		}
+
+		for ((a, b), (count, index)) in edges {
+			if count != 1 {
+				continue;
+			}
+			let corners = self.get_corners(&self.triangles[index]);
+			let face = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+			let (group_a, group_b) = (self.groups[a as usize], self.groups[b as usize]);
+			let edge = self.positions[group_b as usize] - self.positions[group_a as usize];
+			let normal = edge.cross(face);
+			if normal.magnitude2() <= 0.0 {
+				continue;
+			}
+			let quadric = Quadric::from_plane(normal.normalize(), self.positions[group_a as usize], edge.magnitude2() * BOUNDARY_WEIGHT);
+			self.quadrics[group_a as usize].add(&quadric);
+			self.quadrics[group_b as usize].add(&quadric);
+		}
+
+		let mut pairs: Vec<(u32, u32)> = Vec::with_capacity(self.triangles.len() * 3);
+		for triangle in &self.triangles {
+			for k in 0..3 {
+				let a = self.groups[triangle[k] as usize];
+				let b = self.groups[triangle[(k + 1) % 3] as usize];
+				pairs.push((a.min(b), a.max(b)));
+			}
+		}
+		pairs.sort_unstable();
+		pairs.dedup();
+		for (a, b) in pairs {
+			self.push_candidate(a, b);
+		}
+	}
+
+	fn get_corners(&self, triangle: &[u32; 3]) -> [Vector3<f64>; 3] {
+		[
+			self.positions[self.groups[triangle[0] as usize] as usize],
+			self.positions[self.groups[triangle[1] as usize] as usize],
+			self.positions[self.groups[triangle[2] as usize] as usize],
+		]
+	}
+
+	fn push_candidate(&mut self, a: u32, b: u32) {
+		let mut quadric = self.quadrics[a as usize];
+		quadric.add(&self.quadrics[b as usize]);
+		let to_b = quadric.evaluate(self.positions[b as usize]);
+		let to_a = quadric.evaluate(self.positions[a as usize]);
+		let (from, to, cost) = if to_b <= to_a { (a, b, to_b) } else { (b, a, to_a) };
+		self.heap.push(Candidate {
+			cost,
+			from,
+			to,
+			stamps: (self.stamps[from as usize], self.stamps[to as usize]),
+		});
+	}
+
+	fn get_neighbors(&self, group: u32) -> HashSet<u32> {
+		self.adjacency[group as usize].iter()
+			.filter(|triangle| self.alive[**triangle as usize])
+			.flat_map(|triangle| self.triangles[*triangle as usize].iter().map(|vertex| self.groups[*vertex as usize]))
+			.filter(|neighbor| *neighbor != group)
+			.collect()
+	}
+
+	fn contains(&self, triangle: u32, group: u32) -> bool {
+		self.triangles[triangle as usize].iter().any(|vertex| self.groups[*vertex as usize] == group)
+	}
+
+	fn can_collapse(&self, from: u32, to: u32) -> bool {
+		let shared = self.adjacency[from as usize].iter()
+			.filter(|triangle| self.alive[**triangle as usize] && self.contains(**triangle, to))
+			.count();
+		if shared == 0 {
+			return false;
+		}
+		let common = self.get_neighbors(from).intersection(&self.get_neighbors(to)).count();
+		if common > shared {
+			return false;
+		}
+		let target = self.positions[to as usize];
+		for triangle in &self.adjacency[from as usize] {
+			if !self.alive[*triangle as usize] || self.contains(*triangle, to) {
+				continue;
+			}
+			let vertices = self.triangles[*triangle as usize];
+			let corners = self.get_corners(&vertices);
+			let moved: Vec<Vector3<f64>> = vertices.iter()
+				.zip(corners.iter())
+				.map(|(vertex, corner)| if self.groups[*vertex as usize] == from { target } else { *corner })
+				.collect();
+			let before = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+			let after = (moved[1] - moved[0]).cross(moved[2] - moved[0]);
+			if after.dot(before) <= 0.25 * after.magnitude() * before.magnitude() {
+				return false;
+			}
+		}
+		true
+	}
+
+	fn collapse<V: AttributeTrait>(&mut self, vertices: &[V], from: u32, to: u32) -> usize {
+		let mut remap: HashMap<u32, u32> = HashMap::new();
+		let triangles: Vec<u32> = self.adjacency[from as usize].iter().copied().filter(|triangle| self.alive[*triangle as usize]).collect();
+		for triangle in &triangles {
+			let corners = self.triangles[*triangle as usize];
+			if let Some(target) = corners.iter().find(|vertex| self.groups[**vertex as usize] == to) {
+				for vertex in corners.iter().filter(|vertex| self.groups[**vertex as usize] == from) {
+					remap.entry(*vertex).or_insert(*target);
+				}
+			}
+		}
+
+		let mut killed = 0;
+		for triangle in &triangles {
+			let mut corners = self.triangles[*triangle as usize];
+			for vertex in corners.iter_mut() {
+				if self.groups[*vertex as usize] != from {
+					continue;
+				}
+				let source = *vertex;
+				let members = &self.members[to as usize];
+				*vertex = *remap.entry(source).or_insert_with(|| {
+					*members.iter()
+						.min_by(|a, b| {
+							let a = attribute_distance(&vertices[source as usize], &vertices[**a as usize]);
+							let b = attribute_distance(&vertices[source as usize], &vertices[**b as usize]);
+							a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+						})
+						.expect("empty_vertex_group")
+				});
+			}
+			let groups = [self.groups[corners[0] as usize], self.groups[corners[1] as usize], self.groups[corners[2] as usize]];
+			self.triangles[*triangle as usize] = corners;
+			if groups[0] == groups[1] || groups[1] == groups[2] || groups[2] == groups[0] {
+				self.alive[*triangle as usize] = false;
+				killed += 1;
+			}
+		}
+
+		let quadric = self.quadrics[from as usize];
+		self.quadrics[to as usize].add(&quadric);
+		self.removed[from as usize] = true;
+		self.stamps[to as usize] += 1;
+		let mut adjacency = std::mem::take(&mut self.adjacency[from as usize]);
+		adjacency.append(&mut self.adjacency[to as usize]);
+		adjacency.sort_unstable();
+		adjacency.dedup();
+		adjacency.retain(|triangle| self.alive[*triangle as usize]);
+		self.adjacency[to as usize] = adjacency;
+
+		let mut neighbors: Vec<u32> = self.get_neighbors(to).into_iter().collect();
+		neighbors.sort_unstable();
+		for neighbor in neighbors {
+			self.push_candidate(to, neighbor);
+		}
+		killed
+	}
+}
+
+fn attribute_distance<V: AttributeTrait>(a: &V, b: &V) -> f32 {
+	let mut distance = 0.0;
+	if let (Some(a), Some(b)) = (a.get_uv(), b.get_uv()) {
+		distance += (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2);
+	}
+	if let (Some(a), Some(b)) = (a.get_normal(), b.get_normal()) {
+		distance += (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2);
+	}
+	distance
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PbrVertex;
+	use crate::primitive::{plane, uv_sphere};
+
+	#[test]
+	fn collapses_flat_plane_without_error() {
+		let data: MeshData<PbrVertex, u32> = plane(1.0, 1.0, 8, 8);
+		let (indices, error) = simplify(&data, 8, 0.01);
+		assert!(indices.len() / 3 <= 8);
+		assert!(error < 1e-4);
+		assert!(indices.iter().all(|index| (*index as usize) < data.vertices.len()));
+	}
+
+	#[test]
+	fn respects_target_error() {
+		let data: MeshData<PbrVertex, u32> = uv_sphere(1.0, 24, 12);
+		let (coarse, coarse_error) = simplify(&data, 0, 0.05);
+		let (fine, fine_error) = simplify(&data, 0, 0.005);
+		assert!(coarse_error <= 0.05 && fine_error <= 0.005);
+		assert!(coarse.len() < fine.len() && fine.len() < data.indices.len());
+	}
+
+	#[test]
+	fn keeps_degenerate_input() {
+		let vertices = vec![PbrVertex::new([1.0; 3], [0.0; 3], [0.0; 2], [0.0; 4]); 3];
+		let data: MeshData<PbrVertex, u16> = MeshData::new(vertices, vec![0, 1, 2]);
+		assert_eq!(simplify(&data, 0, 1.0), (vec![0, 1, 2], 0.0));
+	}
+}
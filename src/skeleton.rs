@@ -0,0 +1,173 @@
+use crate::{Bind, Renderer, Transform};
+use cgmath::{Matrix4, SquareMatrix};
+use bytemuck::cast_slice;
+use wgpu::*;
+
+pub const MAX_JOINTS: usize = 128;
+
+#[derive(Clone, Debug)]
+pub struct Joint {
+	pub name: Option<String>,
+	pub parent: Option<usize>,
+	pub rest: Transform,
+	pub inverse_bind_matrix: Matrix4<f32>,
+	pub parent_matrix: Matrix4<f32>,
+}
+
+impl Joint {
+	pub fn new(name: Option<String>, parent: Option<usize>, rest: Transform, inverse_bind_matrix: Matrix4<f32>) -> Joint {
+		Joint {
+			name,
+			parent,
+			rest,
+			inverse_bind_matrix,
+			parent_matrix: Matrix4::identity(),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+	joints: Vec<Joint>,
+	order: Vec<usize>,
+}
+
+impl Skeleton {
+	pub fn new(joints: Vec<Joint>) -> Skeleton {
+		let mut depths: Vec<Option<usize>> = vec![None; joints.len()];
+		for index in 0..joints.len() {
+			let mut chain = Vec::new();
+			let mut current = Some(index);
+			let mut base = 0;
+			while let Some(joint) = current {
+				if let Some(depth) = depths[joint] {
+					base = depth + 1;
+					break;
+				}
+				if chain.len() > joints.len() {
+					panic!("joint_hierarchy_cycle");
+				}
+				chain.push(joint);
+				current = joints[joint].parent;
+			}
+			for (offset, joint) in chain.into_iter().rev().enumerate() {
+				depths[joint] = Some(base + offset);
+			}
+		}
+		let mut order: Vec<usize> = (0..joints.len()).collect();
+		order.sort_by_key(|joint| depths[*joint]);
+
+		Skeleton {
+			joints,
+			order,
+		}
+	}
+
+	pub fn get_joints(&self) -> &[Joint] {
+		&self.joints
+	}
+
+	pub fn get_joint_count(&self) -> usize {
+		self.joints.len()
+	}
+
+	pub fn find_joint(&self, name: &str) -> Option<usize> {
+		self.joints.iter().position(|joint| joint.name.as_deref() == Some(name))
+	}
+
+	pub fn get_rest_pose(&self) -> Vec<Transform> {
+		self.joints.iter().map(|joint| joint.rest).collect()
+	}
+
+	pub fn compute_global_matrices(&self, pose: &[Transform]) -> Vec<Matrix4<f32>> {
+		let mut matrices = vec![Matrix4::identity(); self.joints.len()];
+		for index in &self.order {
+			let joint = &self.joints[*index];
+			let local = joint.parent_matrix * pose.get(*index).unwrap_or(&joint.rest).to_matrix();
+			matrices[*index] = match joint.parent {
+				Some(parent) => matrices[parent] * local,
+				None => local,
+			};
+		}
+		matrices
+	}
+
+	pub fn compute_palette(&self, pose: &[Transform]) -> Vec<Matrix4<f32>> {
+		self.compute_global_matrices(pose)
+			.into_iter()
+			.zip(&self.joints)
+			.map(|(global, joint)| global * joint.inverse_bind_matrix)
+			.collect()
+	}
+}
+
+pub struct JointPalette {
+	buffer: Buffer,
+	bind_group: BindGroup,
+}
+
+impl JointPalette {
+	pub fn new(renderer: &Renderer, matrices: &[Matrix4<f32>]) -> JointPalette {
+		let data = palette_data(matrices);
+		let buffer = renderer
+			.get_device()
+			.create_buffer_with_data(cast_slice(&data), BufferUsage::UNIFORM | BufferUsage::COPY_DST);
+		let binding = Binding {
+			binding: 0,
+			resource: BindingResource::Buffer {
+				buffer: &buffer,
+				range: 0..(data.len() * 64) as BufferAddress,
+			},
+		};
+		let bind_group_desc = BindGroupDescriptor {
+			layout: renderer.get_bind_group_layout::<Self>().expect("joint_palette_type_not_registered"),
+			bindings: &[binding],
+			label: None,
+		};
+		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
+
+		JointPalette {
+			buffer,
+			bind_group,
+		}
+	}
+
+	pub fn update(&self, renderer: &Renderer, matrices: &[Matrix4<f32>]) {
+		let data = palette_data(matrices);
+		let data: &[u8] = cast_slice(&data);
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("joint_palette_update_encoder"),
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		let staging_buffer = renderer.get_device().create_buffer_with_data(data, BufferUsage::COPY_SRC);
+		encoder.copy_buffer_to_buffer(&staging_buffer, 0, &self.buffer, 0, data.len() as u64);
+		renderer.get_queue().submit(&[encoder.finish()]);
+	}
+}
+
+impl Bind for JointPalette {
+	fn get_bind_group(&self) -> &BindGroup {
+		&self.bind_group
+	}
+
+	fn get_bind_group_layout(device: &Device) -> BindGroupLayout {
+		let layout_desc = BindGroupLayoutDescriptor {
+			bindings: &[BindGroupLayoutEntry {
+				binding: 0,
+				visibility: ShaderStage::VERTEX,
+				ty: BindingType::UniformBuffer { dynamic: false },
+			}],
+			label: None,
+		};
+		device.create_bind_group_layout(&layout_desc)
+	}
+}
+
+fn palette_data(matrices: &[Matrix4<f32>]) -> Vec<[[f32; 4]; 4]> {
+	if matrices.len() > MAX_JOINTS {
+		panic!("joint_palette_overflow");
+	}
+	let mut data: Vec<[[f32; 4]; 4]> = matrices.iter().map(|matrix| (*matrix).into()).collect();
+	data.resize(MAX_JOINTS, Matrix4::<f32>::identity().into());
+	data
+}
@@ -0,0 +1,207 @@
+use crate::{AttributeTrait, IndexTrait, MeshData, LoadError};
+use crate::geometry::face_normal;
+use std::convert::TryInto;
+use std::fmt::Write;
+
+type Facet = ([f32; 3], [[f32; 3]; 3]);
+
+pub fn read_stl<V: AttributeTrait>(data: &[u8], tolerance: Option<f32>) -> Result<MeshData<V, u32>, LoadError> {
+	let facets = if is_binary(data) {
+		read_binary(data)?
+	} else {
+		read_ascii(std::str::from_utf8(data).map_err(|_| LoadError::invalid("invalid_utf8"))?)?
+	};
+	let mut vertices = Vec::with_capacity(facets.len() * 3);
+	for (normal, corners) in &facets {
+		for corner in corners {
+			let mut vertex = V::zeroed();
+			vertex.set_position(*corner);
+			vertex.set_color([1.0; 4]);
+			if tolerance.is_none() {
+				vertex.set_normal(*normal);
+			}
+			vertices.push(vertex);
+		}
+	}
+	let indices: Vec<u32> = (0..vertices.len() as u32).collect();
+	let mut data = MeshData::new(vertices, indices);
+	if let Some(tolerance) = tolerance {
+		data.weld(tolerance);
+		data.compute_smooth_normals();
+	}
+	Ok(data)
+}
+
+pub fn write_stl_binary<V: AttributeTrait, I: IndexTrait>(vertices: &[V], indices: &[I]) -> Vec<u8> {
+	let count = indices.len() / 3;
+	let mut data = Vec::with_capacity(84 + count * 50);
+	let mut header = [0u8; 80];
+	header[..6].copy_from_slice(b"meshed");
+	data.extend_from_slice(&header);
+	data.extend_from_slice(&(count as u32).to_le_bytes());
+	for triangle in indices.chunks_exact(3) {
+		let corners = get_corners(vertices, triangle);
+		let normal = face_normal(corners[0], corners[1], corners[2]);
+		for value in normal.iter().chain(corners.iter().flatten()) {
+			data.extend_from_slice(&value.to_le_bytes());
+		}
+		data.extend_from_slice(&0u16.to_le_bytes());
+	}
+	data
+}
+
+pub fn write_stl_ascii<V: AttributeTrait, I: IndexTrait>(name: &str, vertices: &[V], indices: &[I]) -> String {
+	let mut output = String::new();
+	writeln!(output, "solid {}", name).expect("stl_writing_failed");
+	for triangle in indices.chunks_exact(3) {
+		let corners = get_corners(vertices, triangle);
+		let normal = face_normal(corners[0], corners[1], corners[2]);
+		writeln!(output, "  facet normal {:e} {:e} {:e}", normal[0], normal[1], normal[2]).expect("stl_writing_failed");
+		writeln!(output, "    outer loop").expect("stl_writing_failed");
+		for corner in &corners {
+			writeln!(output, "      vertex {:e} {:e} {:e}", corner[0], corner[1], corner[2]).expect("stl_writing_failed");
+		}
+		writeln!(output, "    endloop").expect("stl_writing_failed");
+		writeln!(output, "  endfacet").expect("stl_writing_failed");
+	}
+	writeln!(output, "endsolid {}", name).expect("stl_writing_failed");
+	output
+}
+
+fn get_corners<V: AttributeTrait, I: IndexTrait>(vertices: &[V], triangle: &[I]) -> [[f32; 3]; 3] {
+	[
+		vertices[triangle[0].to_u32() as usize].get_position(),
+		vertices[triangle[1].to_u32() as usize].get_position(),
+		vertices[triangle[2].to_u32() as usize].get_position(),
+	]
+}
+
+fn is_binary(data: &[u8]) -> bool {
+	if data.len() < 84 {
+		return false;
+	}
+	let count = u32::from_le_bytes(data[80..84].try_into().expect("slice_size_mismatch")) as usize;
+	if data.len() == 84 + count * 50 {
+		return true;
+	}
+	!data.starts_with(b"solid")
+}
+
+fn read_binary(data: &[u8]) -> Result<Vec<Facet>, LoadError> {
+	if data.len() < 84 {
+		return Err(LoadError::invalid("truncated_header"));
+	}
+	let count = u32::from_le_bytes(data[80..84].try_into().expect("slice_size_mismatch")) as usize;
+	if data.len() < 84 + count * 50 {
+		return Err(LoadError::invalid("truncated_facet_data"));
+	}
+	let mut facets = Vec::with_capacity(count);
+	for facet in data[84..84 + count * 50].chunks_exact(50) {
+		let mut values = [0.0f32; 12];
+		for (i, value) in values.iter_mut().enumerate() {
+			*value = f32::from_le_bytes(facet[i * 4..i * 4 + 4].try_into().expect("slice_size_mismatch"));
+		}
+		facets.push((
+			[values[0], values[1], values[2]],
+			[[values[3], values[4], values[5]], [values[6], values[7], values[8]], [values[9], values[10], values[11]]],
+		));
+	}
+	Ok(facets)
+}
+
+fn read_ascii(source: &str) -> Result<Vec<Facet>, LoadError> {
+	let mut facets = Vec::new();
+	let mut normal = [0.0f32; 3];
+	let mut corners: Vec<[f32; 3]> = Vec::with_capacity(3);
+	let mut in_facet = false;
+	for (number, line) in source.lines().enumerate() {
+		let number = number + 1;
+		let mut tokens = line.split_whitespace();
+		let keyword = match tokens.next() {
+			Some(keyword) => keyword,
+			None => continue,
+		};
+		match keyword {
+			"solid" | "endsolid" | "outer" | "endloop" => (),
+			"facet" => {
+				if in_facet {
+					return Err(LoadError::parse(number, "unterminated_facet"));
+				}
+				if tokens.next() != Some("normal") {
+					return Err(LoadError::parse(number, "expected_facet_normal"));
+				}
+				normal = parse_vector(number, tokens)?;
+				corners.clear();
+				in_facet = true;
+			}
+			"vertex" => {
+				if !in_facet || corners.len() == 3 {
+					return Err(LoadError::parse(number, "unexpected_vertex"));
+				}
+				corners.push(parse_vector(number, tokens)?);
+			}
+			"endfacet" => {
+				if !in_facet || corners.len() != 3 {
+					return Err(LoadError::parse(number, "facet_without_three_vertices"));
+				}
+				facets.push((normal, [corners[0], corners[1], corners[2]]));
+				in_facet = false;
+			}
+			_ => return Err(LoadError::parse(number, "unknown_keyword")),
+		}
+	}
+	if in_facet {
+		return Err(LoadError::parse(source.lines().count(), "unterminated_facet"));
+	}
+	Ok(facets)
+}
+
+fn parse_vector<'a, T: Iterator<Item = &'a str>>(number: usize, tokens: T) -> Result<[f32; 3], LoadError> {
+	let values = tokens
+		.map(|token| token.parse::<f32>().map_err(|_| LoadError::parse(number, "invalid_number")))
+		.collect::<Result<Vec<f32>, LoadError>>()?;
+	if values.len() != 3 {
+		return Err(LoadError::parse(number, "expected_three_components"));
+	}
+	Ok([values[0], values[1], values[2]])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PbrVertex;
+
+	fn quad() -> (Vec<PbrVertex>, Vec<u32>) {
+		let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]].iter()
+			.map(|position| PbrVertex::new(*position, [0.0; 3], [0.0; 2], [0.0; 4]))
+			.collect();
+		(vertices, vec![0, 1, 2, 0, 2, 3])
+	}
+
+	#[test]
+	fn round_trips_binary_with_facet_normals() {
+		let (vertices, indices) = quad();
+		let data: MeshData<PbrVertex, u32> = read_stl(&write_stl_binary(&vertices, &indices), None).expect("stl_reading_failed");
+		assert_eq!(data.vertices.len(), 6);
+		assert_eq!(data.get_triangle_count(), 2);
+		assert!(data.vertices.iter().all(|vertex| vertex.get_normal() == Some([0.0, 0.0, 1.0])));
+	}
+
+	#[test]
+	fn welds_ascii_and_computes_smooth_normals() {
+		let (vertices, indices) = quad();
+		let source = write_stl_ascii("quad", &vertices, &indices);
+		let data: MeshData<PbrVertex, u32> = read_stl(source.as_bytes(), Some(1e-5)).expect("stl_reading_failed");
+		assert_eq!(data.vertices.len(), 4);
+		assert_eq!(data.get_triangle_count(), 2);
+		assert!(data.vertices.iter().all(|vertex| vertex.get_normal() == Some([0.0, 0.0, 1.0])));
+	}
+
+	#[test]
+	fn rejects_truncated_data() {
+		let (vertices, indices) = quad();
+		let data = write_stl_binary(&vertices, &indices);
+		assert!(read_stl::<PbrVertex>(&data[..data.len() - 10], None).is_err());
+		assert!(read_stl::<PbrVertex>(b"solid broken\n  facet normal 0 0 1\n    outer loop\n      vertex 0 0 0\n", None).is_err());
+	}
+}
@@ -0,0 +1,160 @@
+use cgmath::{Vector3, InnerSpace, Zero};
+use std::collections::HashMap;
+
+const NO_GROUP: usize = usize::MAX;
+
+struct Triangle {
+	vertices: [usize; 3],
+	tangent: Vector3<f32>,
+	orientation: bool,
+	group_with_any: bool,
+	degenerate: bool,
+	neighbors: [Option<usize>; 3],
+	groups: [usize; 3],
+}
+
+struct Group {
+	vertex: usize,
+	orientation: bool,
+	faces: Vec<usize>,
+}
+
+pub(crate) fn generate_tangents(positions: &[[f32; 3]], normals: &[[f32; 3]], uvs: &[[f32; 2]], indices: &[u32]) -> Vec<[f32; 4]> {
+	let canonical = get_shared_vertices(positions, normals, uvs);
+	let mut triangles: Vec<Triangle> = indices.chunks_exact(3).map(|triangle| {
+		let vertices = [canonical[triangle[0] as usize], canonical[triangle[1] as usize], canonical[triangle[2] as usize]];
+		let degenerate = vertices[0] == vertices[1] || vertices[0] == vertices[2] || vertices[1] == vertices[2];
+		let p = [Vector3::from(positions[vertices[0]]), Vector3::from(positions[vertices[1]]), Vector3::from(positions[vertices[2]])];
+		let t = [uvs[vertices[0]], uvs[vertices[1]], uvs[vertices[2]]];
+		let (t21x, t21y) = (t[1][0] - t[0][0], t[1][1] - t[0][1]);
+		let (t31x, t31y) = (t[2][0] - t[0][0], t[2][1] - t[0][1]);
+		let (d1, d2) = (p[1] - p[0], p[2] - p[0]);
+		let signed_area = t21x * t31y - t21y * t31x;
+		let orientation = signed_area > 0.0;
+		let mut tangent = Vector3::zero();
+		if signed_area != 0.0 {
+			let direction = d1 * t31y - d2 * t21y;
+			if direction.magnitude2() > 0.0 {
+				tangent = direction.normalize() * if orientation { 1.0 } else { -1.0 };
+			}
+		}
+
+		Triangle {
+			vertices,
+			tangent,
+			orientation,
+			group_with_any: !degenerate && signed_area == 0.0,
+			degenerate,
+			neighbors: [None; 3],
+			groups: [NO_GROUP; 3],
+		}
+	}).collect();
+
+	let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+	for (index, triangle) in triangles.iter().enumerate().filter(|(_, triangle)| !triangle.degenerate) {
+		for k in 0..3 {
+			edges.entry((triangle.vertices[k], triangle.vertices[(k + 1) % 3])).or_insert(index);
+		}
+	}
+	for triangle in triangles.iter_mut().filter(|triangle| !triangle.degenerate) {
+		for k in 0..3 {
+			triangle.neighbors[k] = edges.get(&(triangle.vertices[(k + 1) % 3], triangle.vertices[k])).copied();
+		}
+	}
+
+	let mut groups: Vec<Group> = Vec::new();
+	for index in 0..triangles.len() {
+		if triangles[index].degenerate || triangles[index].group_with_any {
+			continue;
+		}
+		for k in 0..3 {
+			if triangles[index].groups[k] != NO_GROUP {
+				continue;
+			}
+			groups.push(Group {
+				vertex: triangles[index].vertices[k],
+				orientation: triangles[index].orientation,
+				faces: Vec::new(),
+			});
+			assign_group(&mut triangles, &mut groups, index);
+		}
+	}
+
+	let mut group_tangents = Vec::with_capacity(groups.len());
+	for group in &groups {
+		let normal = Vector3::from(normals[group.vertex]);
+		let mut sum = Vector3::zero();
+		for face in &group.faces {
+			let triangle = &triangles[*face];
+			let k = triangle.vertices.iter().position(|vertex| *vertex == group.vertex).expect("group_vertex_missing");
+			let projected = project(triangle.tangent, normal);
+			let position = Vector3::from(positions[group.vertex]);
+			let to_next = project(Vector3::from(positions[triangle.vertices[(k + 1) % 3]]) - position, normal);
+			let to_previous = project(Vector3::from(positions[triangle.vertices[(k + 2) % 3]]) - position, normal);
+			let angle = to_next.dot(to_previous).clamp(-1.0, 1.0).acos();
+			sum += projected * angle;
+		}
+		let tangent = if sum.magnitude2() > 0.0 { sum.normalize() } else { sum };
+		group_tangents.push([tangent.x, tangent.y, tangent.z, if group.orientation { 1.0 } else { -1.0 }]);
+	}
+
+	let mut tangents = vec![[0.0, 0.0, 0.0, 1.0]; triangles.len() * 3];
+	let mut vertex_tangents: HashMap<usize, [f32; 4]> = HashMap::new();
+	for (index, triangle) in triangles.iter().enumerate() {
+		for k in 0..3 {
+			if triangle.groups[k] != NO_GROUP {
+				let tangent = group_tangents[triangle.groups[k]];
+				tangents[index * 3 + k] = tangent;
+				vertex_tangents.entry(triangle.vertices[k]).or_insert(tangent);
+			}
+		}
+	}
+	for (index, triangle) in triangles.iter().enumerate().filter(|(_, triangle)| triangle.degenerate) {
+		for k in 0..3 {
+			if let Some(tangent) = vertex_tangents.get(&triangle.vertices[k]) {
+				tangents[index * 3 + k] = *tangent;
+			}
+		}
+	}
+	tangents
+}
+
+fn get_shared_vertices(positions: &[[f32; 3]], normals: &[[f32; 3]], uvs: &[[f32; 2]]) -> Vec<usize> {
+	let mut shared: HashMap<[u32; 8], usize> = HashMap::new();
+	(0..positions.len()).map(|index| {
+		let (p, n, t) = (positions[index], normals[index], uvs[index]);
+		let key = [p[0], p[1], p[2], n[0], n[1], n[2], t[0], t[1]];
+		*shared.entry(key.map(|value| (value + 0.0).to_bits())).or_insert(index)
+	}).collect()
+}
+
+fn assign_group(triangles: &mut [Triangle], groups: &mut [Group], start: usize) {
+	let group_index = groups.len() - 1;
+	let group = &mut groups[group_index];
+	let mut stack = vec![start];
+	while let Some(index) = stack.pop() {
+		let triangle = &mut triangles[index];
+		let k = match triangle.vertices.iter().position(|vertex| *vertex == group.vertex) {
+			Some(k) => k,
+			None => continue,
+		};
+		if triangle.groups[k] != NO_GROUP {
+			continue;
+		}
+		if triangle.group_with_any && triangle.groups.iter().all(|group| *group == NO_GROUP) {
+			triangle.orientation = group.orientation;
+		}
+		if triangle.orientation != group.orientation {
+			continue;
+		}
+		triangle.groups[k] = group_index;
+		group.faces.push(index);
+		stack.extend(triangle.neighbors[(k + 2) % 3]);
+		stack.extend(triangle.neighbors[k]);
+	}
+}
+
+fn project(vector: Vector3<f32>, normal: Vector3<f32>) -> Vector3<f32> {
+	let projected = vector - normal * normal.dot(vector);
+	if projected.magnitude2() > 0.0 { projected.normalize() } else { projected }
+}
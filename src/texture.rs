@@ -1,71 +1,246 @@
-use crate::{Renderer, Bind};
+use crate::{Renderer, Bind, LoadError, SamplerOptions};
+use crate::hdr::{read_hdr, HdrImage};
+use crate::mipmap::{decode_rgba8, encode_rgba8, generate_mip_chain, MipFilter};
+use crate::quantize::{f32_to_half, half_to_f32, from_unorm8, to_unorm8};
 use image::GenericImageView;
+use std::cell::RefCell;
+use std::rc::Rc;
 use wgpu::*;
 
+pub(crate) const COPY_ROW_ALIGNMENT: usize = 256;
+
+#[derive(Clone, Debug)]
+pub struct TextureOptions {
+	pub srgb: bool,
+	pub flip_y: bool,
+	pub usage: TextureUsage,
+	pub mip_filter: Option<MipFilter>,
+	pub sampler: SamplerOptions,
+	pub shared_sampler: Option<Rc<crate::Sampler>>,
+}
+
+impl Default for TextureOptions {
+	fn default() -> TextureOptions {
+		TextureOptions {
+			srgb: true,
+			flip_y: true,
+			usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+			mip_filter: Some(MipFilter::Box),
+			sampler: SamplerOptions::default(),
+			shared_sampler: None,
+		}
+	}
+}
+
+impl TextureOptions {
+	pub fn linear() -> TextureOptions {
+		TextureOptions {
+			srgb: false,
+			..TextureOptions::default()
+		}
+	}
+
+	pub fn with_sampler(sampler: SamplerOptions) -> TextureOptions {
+		TextureOptions {
+			sampler,
+			..TextureOptions::default()
+		}
+	}
+
+	pub fn with_shared_sampler(sampler: Rc<crate::Sampler>) -> TextureOptions {
+		TextureOptions {
+			shared_sampler: Some(sampler),
+			..TextureOptions::default()
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+	R8,
+	Rg8,
+	Rgba8,
+	Rgba16Float,
+	Rgba32Float,
+}
+
+impl PixelFormat {
+	pub fn get_bytes_per_pixel(self) -> usize {
+		match self {
+			PixelFormat::R8 => 1,
+			PixelFormat::Rg8 => 2,
+			PixelFormat::Rgba8 => 4,
+			PixelFormat::Rgba16Float => 8,
+			PixelFormat::Rgba32Float => 16,
+		}
+	}
+
+	pub fn get_texture_format(self, srgb: bool) -> TextureFormat {
+		match self {
+			PixelFormat::R8 => TextureFormat::R8Unorm,
+			PixelFormat::Rg8 => TextureFormat::Rg8Unorm,
+			PixelFormat::Rgba8 if srgb => TextureFormat::Rgba8UnormSrgb,
+			PixelFormat::Rgba8 => TextureFormat::Rgba8Unorm,
+			PixelFormat::Rgba16Float => TextureFormat::Rgba16Float,
+			PixelFormat::Rgba32Float => TextureFormat::Rgba32Float,
+		}
+	}
+
+	pub fn decode(self, pixels: &[u8], srgb: bool) -> Vec<[f32; 4]> {
+		match self {
+			PixelFormat::R8 => pixels.iter().map(|&red| [from_unorm8(red), 0.0, 0.0, 1.0]).collect(),
+			PixelFormat::Rg8 => pixels.chunks_exact(2).map(|pixel| [from_unorm8(pixel[0]), from_unorm8(pixel[1]), 0.0, 1.0]).collect(),
+			PixelFormat::Rgba8 => decode_rgba8(pixels, srgb),
+			PixelFormat::Rgba16Float => pixels.chunks_exact(8).map(|pixel| {
+				let mut decoded = [0.0; 4];
+				for (channel, bytes) in decoded.iter_mut().zip(pixel.chunks_exact(2)) {
+					*channel = half_to_f32(u16::from_le_bytes([bytes[0], bytes[1]]));
+				}
+				decoded
+			}).collect(),
+			PixelFormat::Rgba32Float => pixels.chunks_exact(16).map(|pixel| {
+				let mut decoded = [0.0; 4];
+				for (channel, bytes) in decoded.iter_mut().zip(pixel.chunks_exact(4)) {
+					*channel = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+				}
+				decoded
+			}).collect(),
+		}
+	}
+
+	pub fn encode(self, pixels: &[[f32; 4]], srgb: bool) -> Vec<u8> {
+		match self {
+			PixelFormat::R8 => pixels.iter().map(|pixel| to_unorm8(pixel[0])).collect(),
+			PixelFormat::Rg8 => pixels.iter().flat_map(|pixel| vec![to_unorm8(pixel[0]), to_unorm8(pixel[1])]).collect(),
+			PixelFormat::Rgba8 => encode_rgba8(pixels, srgb),
+			PixelFormat::Rgba16Float => pixels.iter().flatten().flat_map(|&channel| f32_to_half(channel).to_le_bytes().to_vec()).collect(),
+			PixelFormat::Rgba32Float => pixels.iter().flatten().flat_map(|&channel| channel.to_le_bytes().to_vec()).collect(),
+		}
+	}
+}
+
+enum TextureSampler {
+	Owned(Sampler),
+	Shared(Rc<crate::Sampler>),
+}
+
+impl TextureSampler {
+	fn get(&self) -> &Sampler {
+		match self {
+			TextureSampler::Owned(sampler) => sampler,
+			TextureSampler::Shared(sampler) => sampler.get_sampler(),
+		}
+	}
+}
+
 pub struct Texture {
-	_texture: wgpu::Texture,
+	texture: wgpu::Texture,
 	_view: TextureView,
-	_sampler: Sampler,
+	sampler: TextureSampler,
 	bind_group: BindGroup,
+	width: u32,
+	height: u32,
+	format: TextureFormat,
+	pixel_format: PixelFormat,
+	mip_level_count: u32,
+	mip_filter: Option<MipFilter>,
+	base_level: Option<RefCell<Vec<u8>>>,
+	flip_y: bool,
 }
 
 impl Texture {
 	pub fn new(renderer: &Renderer, data: &[u8]) -> Texture {
-		let image = image::load_from_memory(data).expect("image_reading_failed").flipv();
-		let (width, height) = image.dimensions();
-		let rgba = image.as_rgba8().expect("image_conversion_failed").clone().into_raw();
+		Texture::with_options(renderer, data, &TextureOptions::default())
+	}
+
+	pub fn new_linear(renderer: &Renderer, data: &[u8]) -> Texture {
+		Texture::with_options(renderer, data, &TextureOptions::linear())
+	}
+
+	pub fn with_options(renderer: &Renderer, data: &[u8], options: &TextureOptions) -> Texture {
+		let (width, height, rgba) = decode(data);
+		Texture::from_rgba_with_options(renderer, width, height, &rgba, options)
+	}
+
+	pub fn from_rgba(renderer: &Renderer, width: u32, height: u32, rgba: &[u8], srgb: bool) -> Texture {
+		let options = TextureOptions {
+			srgb,
+			flip_y: false,
+			..TextureOptions::default()
+		};
+		Texture::from_rgba_with_options(renderer, width, height, rgba, &options)
+	}
+
+	pub fn from_rgba_with_options(renderer: &Renderer, width: u32, height: u32, rgba: &[u8], options: &TextureOptions) -> Texture {
+		Texture::from_raw(renderer, width, height, rgba, PixelFormat::Rgba8, options)
+	}
+
+	pub fn new_hdr(renderer: &Renderer, data: &[u8]) -> Result<Texture, LoadError> {
+		let image = read_hdr(data)?;
+		Ok(Texture::from_hdr(renderer, &image))
+	}
+
+	pub fn from_hdr(renderer: &Renderer, image: &HdrImage) -> Texture {
+		Texture::from_hdr_with_options(renderer, image, &TextureOptions::linear())
+	}
+
+	pub fn from_hdr_with_options(renderer: &Renderer, image: &HdrImage, options: &TextureOptions) -> Texture {
+		Texture::from_rgba_f32_with_options(renderer, image.width, image.height, &image.pixels, options)
+	}
+
+	pub fn from_rgba_f32(renderer: &Renderer, width: u32, height: u32, pixels: &[[f32; 4]]) -> Texture {
+		let options = TextureOptions {
+			srgb: false,
+			flip_y: false,
+			..TextureOptions::default()
+		};
+		Texture::from_rgba_f32_with_options(renderer, width, height, pixels, &options)
+	}
+
+	pub fn from_rgba_f32_with_options(renderer: &Renderer, width: u32, height: u32, pixels: &[[f32; 4]], options: &TextureOptions) -> Texture {
+		let half = PixelFormat::Rgba16Float.encode(pixels, false);
+		Texture::from_raw(renderer, width, height, &half, PixelFormat::Rgba16Float, options)
+	}
+
+	pub fn from_raw(renderer: &Renderer, width: u32, height: u32, pixels: &[u8], pixel_format: PixelFormat, options: &TextureOptions) -> Texture {
+		let row_size = width as usize * pixel_format.get_bytes_per_pixel();
+		if pixels.len() != row_size * height as usize {
+			panic!("texture_size_mismatch");
+		}
+		let pixels = if options.flip_y { flip_rows(pixels, row_size) } else { pixels.to_vec() };
+		let srgb = options.srgb && pixel_format == PixelFormat::Rgba8;
+		let mut levels = Vec::new();
+		if let Some(filter) = options.mip_filter {
+			let chain = generate_mip_chain(width, height, pixel_format.decode(&pixels, srgb), filter);
+			levels.extend(chain.iter().skip(1).map(|level| (level.width, level.height, pixel_format.encode(&level.pixels, srgb))));
+		}
+		levels.insert(0, (width, height, pixels));
+		Texture::from_levels(renderer, &levels, pixel_format, pixel_format.get_texture_format(srgb), options)
+	}
+
+	fn from_levels(renderer: &Renderer, levels: &[(u32, u32, Vec<u8>)], pixel_format: PixelFormat, format: TextureFormat, options: &TextureOptions) -> Texture {
+		let (width, height, _) = levels[0];
+		let mip_level_count = levels.len() as u32;
 		let size = wgpu::Extent3d {
 			width,
 			height,
 			depth: 1,
 		};
-		let _texture = renderer.get_device().create_texture(&wgpu::TextureDescriptor {
+		let texture = renderer.get_device().create_texture(&wgpu::TextureDescriptor {
 			size,
 			array_layer_count: 1,
-			mip_level_count: 1,
+			mip_level_count,
 			sample_count: 1,
 			dimension: wgpu::TextureDimension::D2,
-			format: wgpu::TextureFormat::Rgba8UnormSrgb,
-			usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+			format,
+			usage: options.usage | TextureUsage::COPY_DST,
 			label: None,
 		});
-		let buffer = renderer.get_device().create_buffer_with_data(rgba.as_slice(), BufferUsage::COPY_SRC);
-		let encoder_desc = CommandEncoderDescriptor {
-			label: Some("texture_data_copy_encoder")
+		let sampler = match &options.shared_sampler {
+			Some(sampler) => TextureSampler::Shared(sampler.clone()),
+			None => TextureSampler::Owned(options.sampler.create_sampler(renderer.get_device())),
 		};
-		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
-		let buffer_copy_view = BufferCopyView {
-			buffer: &buffer,
-			offset: 0,
-			bytes_per_row: 4 * width,
-			rows_per_image: height,
-		};
-		let texture_copy_view = TextureCopyView {
-			texture: &_texture,
-			mip_level: 0,
-			array_layer: 0,
-			origin: Origin3d::ZERO,
-		};
-		let copy_size = Extent3d {
-			width,
-			height,
-			depth: 1,
-		};
-		encoder.copy_buffer_to_texture(buffer_copy_view, texture_copy_view, copy_size);
-		renderer.get_queue().submit(&[encoder.finish()]);
-		let sampler_desc = SamplerDescriptor {
-			address_mode_u: wgpu::AddressMode::ClampToEdge,
-			address_mode_v: wgpu::AddressMode::ClampToEdge,
-			address_mode_w: wgpu::AddressMode::ClampToEdge,
-			mag_filter: wgpu::FilterMode::Linear,
-			min_filter: wgpu::FilterMode::Nearest,
-			mipmap_filter: wgpu::FilterMode::Nearest,
-			lod_min_clamp: -100.0,
-			lod_max_clamp: 100.0,
-			compare: wgpu::CompareFunction::Always,
-		};
-		let _sampler = renderer.get_device().create_sampler(&sampler_desc);
-		let _view = _texture.create_default_view();
+		let _view = texture.create_default_view();
 		let bind_group_desc = BindGroupDescriptor {
 			layout: renderer.get_bind_group_layout::<Self>().expect("texture_type_not_registered"),
 			bindings: &[
@@ -75,24 +250,148 @@ impl Texture {
 				},
 				wgpu::Binding {
 					binding: 1,
-					resource: wgpu::BindingResource::Sampler(&_sampler),
+					resource: wgpu::BindingResource::Sampler(sampler.get()),
 				}
 			],
 			label: None,
 		};
 		let bind_group = renderer.get_device().create_bind_group(&bind_group_desc);
 
-		Texture {
-			_texture,
+		let texture = Texture {
+			texture,
 			_view,
-			_sampler,
+			sampler,
 			bind_group,
+			width,
+			height,
+			format,
+			pixel_format,
+			mip_level_count,
+			mip_filter: options.mip_filter,
+			base_level: if mip_level_count > 1 { Some(RefCell::new(levels[0].2.clone())) } else { None },
+			flip_y: options.flip_y,
+		};
+		for (mip_level, (level_width, level_height, pixels)) in levels.iter().enumerate() {
+			texture.write(renderer, mip_level as u32, [0, 0], [*level_width, *level_height], pixels);
 		}
+		texture
+	}
+
+	pub fn update(&self, renderer: &Renderer, pixels: &[u8]) {
+		self.update_region(renderer, 0, 0, self.width, self.height, pixels);
+	}
+
+	pub fn update_region(&self, renderer: &Renderer, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) {
+		let bottom = match (x.checked_add(width), y.checked_add(height)) {
+			(Some(right), Some(bottom)) if right <= self.width && bottom <= self.height => bottom,
+			_ => panic!("texture_region_out_of_bounds"),
+		};
+		let bytes_per_pixel = self.pixel_format.get_bytes_per_pixel();
+		let row_size = bytes_per_pixel * width as usize;
+		if pixels.len() != row_size * height as usize {
+			panic!("texture_size_mismatch");
+		}
+		if width == 0 || height == 0 {
+			return;
+		}
+		let flipped;
+		let (y, pixels) = if self.flip_y {
+			flipped = flip_rows(pixels, row_size);
+			(self.height - bottom, &flipped[..])
+		} else {
+			(y, pixels)
+		};
+		self.write(renderer, 0, [x, y], [width, height], pixels);
+
+		if let (Some(base_level), Some(filter)) = (&self.base_level, self.mip_filter) {
+			let mut base_level = base_level.borrow_mut();
+			let stride = bytes_per_pixel * self.width as usize;
+			for (row, source) in pixels.chunks_exact(row_size).enumerate() {
+				let start = (y as usize + row) * stride + x as usize * bytes_per_pixel;
+				base_level[start..start + row_size].copy_from_slice(source);
+			}
+			let srgb = self.format == TextureFormat::Rgba8UnormSrgb;
+			let chain = generate_mip_chain(self.width, self.height, self.pixel_format.decode(&base_level, srgb), filter);
+			for (mip_level, level) in chain.iter().enumerate().skip(1) {
+				self.write(renderer, mip_level as u32, [0, 0], [level.width, level.height], &self.pixel_format.encode(&level.pixels, srgb));
+			}
+		}
+	}
+
+	fn write(&self, renderer: &Renderer, mip_level: u32, origin: [u32; 2], size: [u32; 2], pixels: &[u8]) {
+		let row_size = self.pixel_format.get_bytes_per_pixel() * size[0] as usize;
+		if pixels.len() != row_size * size[1] as usize {
+			panic!("texture_size_mismatch");
+		}
+		if row_size == 0 || size[1] == 0 {
+			return;
+		}
+		let padded_row_size = row_size.div_ceil(COPY_ROW_ALIGNMENT) * COPY_ROW_ALIGNMENT;
+		let mut padded = vec![0; padded_row_size * size[1] as usize];
+		for (source, destination) in pixels.chunks_exact(row_size).zip(padded.chunks_exact_mut(padded_row_size)) {
+			destination[..row_size].copy_from_slice(source);
+		}
+		let buffer = renderer.get_device().create_buffer_with_data(&padded, BufferUsage::COPY_SRC);
+		let encoder_desc = CommandEncoderDescriptor {
+			label: Some("texture_data_copy_encoder")
+		};
+		let mut encoder = renderer.get_device().create_command_encoder(&encoder_desc);
+		let buffer_copy_view = BufferCopyView {
+			buffer: &buffer,
+			offset: 0,
+			bytes_per_row: padded_row_size as u32,
+			rows_per_image: size[1],
+		};
+		let texture_copy_view = TextureCopyView {
+			texture: &self.texture,
+			mip_level,
+			array_layer: 0,
+			origin: Origin3d {
+				x: origin[0],
+				y: origin[1],
+				z: 0,
+			},
+		};
+		let copy_size = Extent3d {
+			width: size[0],
+			height: size[1],
+			depth: 1,
+		};
+		encoder.copy_buffer_to_texture(buffer_copy_view, texture_copy_view, copy_size);
+		renderer.get_queue().submit(&[encoder.finish()]);
 	}
 
 	pub fn get_bind_group(&self) -> &BindGroup {
 		&self.bind_group
 	}
+
+	pub fn get_view(&self) -> &TextureView {
+		&self._view
+	}
+
+	pub fn get_sampler(&self) -> &Sampler {
+		self.sampler.get()
+	}
+
+	pub fn get_width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn get_height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn get_format(&self) -> TextureFormat {
+		self.format
+	}
+
+	pub fn get_pixel_format(&self) -> PixelFormat {
+		self.pixel_format
+	}
+
+	pub fn get_mip_level_count(&self) -> u32 {
+		self.mip_level_count
+	}
 }
 
 impl Bind for Texture {
@@ -109,7 +408,7 @@ impl Bind for Texture {
 					ty: wgpu::BindingType::SampledTexture {
 						multisampled: false,
 						dimension: wgpu::TextureViewDimension::D2,
-						component_type: wgpu::TextureComponentType::Uint,
+						component_type: wgpu::TextureComponentType::Float,
 					},
 				},
 				wgpu::BindGroupLayoutEntry {
@@ -124,4 +423,18 @@ impl Bind for Texture {
 		};
 		device.create_bind_group_layout(&layout)
 	}
+}
+
+fn decode(data: &[u8]) -> (u32, u32, Vec<u8>) {
+	let image = image::load_from_memory(data).expect("image_reading_failed");
+	let (width, height) = image.dimensions();
+	(width, height, image.to_rgba8().into_raw())
+}
+
+fn flip_rows<T: Clone>(pixels: &[T], row_size: usize) -> Vec<T> {
+	let mut flipped = Vec::with_capacity(pixels.len());
+	for row in pixels.chunks_exact(row_size.max(1)).rev() {
+		flipped.extend_from_slice(row);
+	}
+	flipped
 }
\ No newline at end of file
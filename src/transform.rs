@@ -0,0 +1,34 @@
+use cgmath::{Matrix4, Quaternion, Vector3, One};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+	pub translation: Vector3<f32>,
+	pub rotation: Quaternion<f32>,
+	pub scale: Vector3<f32>,
+}
+
+impl Transform {
+	pub fn new(translation: Vector3<f32>, rotation: Quaternion<f32>, scale: Vector3<f32>) -> Transform {
+		Transform {
+			translation,
+			rotation,
+			scale,
+		}
+	}
+
+	pub fn identity() -> Transform {
+		Transform::new(Vector3::new(0.0, 0.0, 0.0), Quaternion::one(), Vector3::new(1.0, 1.0, 1.0))
+	}
+
+	pub fn to_matrix(&self) -> Matrix4<f32> {
+		Matrix4::from_translation(self.translation)
+			* Matrix4::from(self.rotation)
+			* Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+	}
+}
+
+impl Default for Transform {
+	fn default() -> Transform {
+		Transform::identity()
+	}
+}
@@ -19,7 +19,7 @@ impl<T: Pod> Uniform<T> {
 			binding: 0,
 			resource: wgpu::BindingResource::Buffer {
 				buffer: &buffer,
-				range: 0..size_of_val(&data) as wgpu::BufferAddress,
+				range: 0..size_of_val(data) as wgpu::BufferAddress,
 			},
 		};
 		let bind_group_desc = BindGroupDescriptor {
@@ -48,6 +48,10 @@ impl<T: Pod> Uniform<T> {
 		encoder.copy_buffer_to_buffer(&staging_buffer, 0, &self.buffer, 0, data.len() as u64);
 		renderer.get_queue().submit(&[encoder.finish()]);
 	}
+
+	pub fn get_buffer(&self) -> &Buffer {
+		&self.buffer
+	}
 }
 
 impl<T: Pod> Bind for Uniform<T> {
@@ -59,7 +63,7 @@ impl<T: Pod> Bind for Uniform<T> {
 		let layout_desc = BindGroupLayoutDescriptor {
 			bindings: &[wgpu::BindGroupLayoutEntry {
 				binding: 0,
-				visibility: wgpu::ShaderStage::VERTEX,
+				visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
 				ty: wgpu::BindingType::UniformBuffer { dynamic: false },
 			}],
 			label: None,
@@ -1,3 +1,4 @@
+use crate::quantize::*;
 use wgpu::*;
 use bytemuck::{Pod, Zeroable};
 use std::mem::size_of;
@@ -8,6 +9,57 @@ pub trait VertexTrait: Pod {
 
 pub trait IndexTrait: Pod {
 	fn index_format() -> IndexFormat;
+	fn to_u32(self) -> u32;
+	fn try_from_u32(value: u32) -> Option<Self>;
+
+	fn from_u32(value: u32) -> Self {
+		Self::try_from_u32(value).expect("index_out_of_range")
+	}
+}
+
+pub trait AttributeTrait: VertexTrait {
+	fn get_position(&self) -> [f32; 3];
+	fn set_position(&mut self, position: [f32; 3]);
+
+	fn get_normal(&self) -> Option<[f32; 3]> {
+		None
+	}
+
+	fn set_normal(&mut self, _normal: [f32; 3]) {}
+
+	fn get_uv(&self) -> Option<[f32; 2]> {
+		None
+	}
+
+	fn set_uv(&mut self, _uv: [f32; 2]) {}
+
+	fn get_color(&self) -> Option<[f32; 4]> {
+		None
+	}
+
+	fn set_color(&mut self, _color: [f32; 4]) {}
+
+	fn get_tangent(&self) -> Option<[f32; 4]> {
+		None
+	}
+
+	fn set_tangent(&mut self, _tangent: [f32; 4]) {}
+
+	fn get_joints(&self) -> Option<[u16; 4]> {
+		None
+	}
+
+	fn set_joints(&mut self, _joints: [u16; 4]) {}
+
+	fn get_weights(&self) -> Option<[f32; 4]> {
+		None
+	}
+
+	fn set_weights(&mut self, _weights: [f32; 4]) {}
+}
+
+pub trait VertexLayoutTrait {
+	fn descriptors<'a>() -> Vec<VertexBufferDescriptor<'a>>;
 }
 
 #[repr(C)]
@@ -58,14 +110,554 @@ impl VertexTrait for Vertex {
 	}
 }
 
+impl AttributeTrait for Vertex {
+	fn get_position(&self) -> [f32; 3] {
+		self.position
+	}
+
+	fn set_position(&mut self, position: [f32; 3]) {
+		self.position = position;
+	}
+
+	fn get_uv(&self) -> Option<[f32; 2]> {
+		Some(self.uv)
+	}
+
+	fn set_uv(&mut self, uv: [f32; 2]) {
+		self.uv = uv;
+	}
+
+	fn get_color(&self) -> Option<[f32; 4]> {
+		Some([self.color[0], self.color[1], self.color[2], 1.0])
+	}
+
+	fn set_color(&mut self, color: [f32; 4]) {
+		self.color = [color[0], color[1], color[2]];
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PositionVertex {
+	position: [f32; 3],
+}
+
+impl PositionVertex {
+	pub fn new(position: [f32; 3]) -> PositionVertex {
+		PositionVertex {
+			position,
+		}
+	}
+}
+
+unsafe impl Pod for PositionVertex {}
+
+unsafe impl Zeroable for PositionVertex {}
+
+impl VertexTrait for PositionVertex {
+	fn descriptor<'a>() -> VertexBufferDescriptor<'a> {
+		VertexBufferDescriptor {
+			stride: size_of::<PositionVertex>() as BufferAddress,
+			step_mode: InputStepMode::Vertex,
+			attributes: &[
+				VertexAttributeDescriptor {
+					offset: 0,
+					shader_location: 0,
+					format: VertexFormat::Float3,
+				},
+			],
+		}
+	}
+}
+
+impl AttributeTrait for PositionVertex {
+	fn get_position(&self) -> [f32; 3] {
+		self.position
+	}
+
+	fn set_position(&mut self, position: [f32; 3]) {
+		self.position = position;
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CompactVertex {
+	position: [u16; 4],
+	color: [u8; 4],
+	uv: [u16; 2],
+}
+
+impl CompactVertex {
+	pub fn new(position: [f32; 3], color: [f32; 4], uv: [f32; 2]) -> CompactVertex {
+		CompactVertex {
+			position: encode_position(position),
+			color: encode_color(color),
+			uv: encode_uv(uv),
+		}
+	}
+}
+
+unsafe impl Pod for CompactVertex {}
+
+unsafe impl Zeroable for CompactVertex {}
+
+impl VertexTrait for CompactVertex {
+	fn descriptor<'a>() -> VertexBufferDescriptor<'a> {
+		VertexBufferDescriptor {
+			stride: size_of::<CompactVertex>() as BufferAddress,
+			step_mode: InputStepMode::Vertex,
+			attributes: &[
+				VertexAttributeDescriptor {
+					offset: 0,
+					shader_location: 0,
+					format: VertexFormat::Half4,
+				},
+				VertexAttributeDescriptor {
+					offset: size_of::<[u16; 4]>() as BufferAddress,
+					shader_location: 1,
+					format: VertexFormat::Uchar4Norm,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[u16; 4]>() + size_of::<[u8; 4]>()) as BufferAddress,
+					shader_location: 2,
+					format: VertexFormat::Half2,
+				},
+			],
+		}
+	}
+}
+
+impl AttributeTrait for CompactVertex {
+	fn get_position(&self) -> [f32; 3] {
+		decode_position(self.position)
+	}
+
+	fn set_position(&mut self, position: [f32; 3]) {
+		self.position = encode_position(position);
+	}
+
+	fn get_uv(&self) -> Option<[f32; 2]> {
+		Some(decode_uv(self.uv))
+	}
+
+	fn set_uv(&mut self, uv: [f32; 2]) {
+		self.uv = encode_uv(uv);
+	}
+
+	fn get_color(&self) -> Option<[f32; 4]> {
+		Some(decode_color(self.color))
+	}
+
+	fn set_color(&mut self, color: [f32; 4]) {
+		self.color = encode_color(color);
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CompactNormalVertex {
+	position: [u16; 4],
+	color: [u8; 4],
+	uv: [u16; 2],
+	normal: [i16; 2],
+	tangent: [i16; 4],
+}
+
+impl CompactNormalVertex {
+	pub fn new(position: [f32; 3], color: [f32; 4], uv: [f32; 2], normal: [f32; 3], tangent: [f32; 4]) -> CompactNormalVertex {
+		CompactNormalVertex {
+			position: encode_position(position),
+			color: encode_color(color),
+			uv: encode_uv(uv),
+			normal: to_octahedral_snorm16(normal),
+			tangent: encode_tangent(tangent),
+		}
+	}
+}
+
+unsafe impl Pod for CompactNormalVertex {}
+
+unsafe impl Zeroable for CompactNormalVertex {}
+
+impl VertexTrait for CompactNormalVertex {
+	fn descriptor<'a>() -> VertexBufferDescriptor<'a> {
+		VertexBufferDescriptor {
+			stride: size_of::<CompactNormalVertex>() as BufferAddress,
+			step_mode: InputStepMode::Vertex,
+			attributes: &[
+				VertexAttributeDescriptor {
+					offset: 0,
+					shader_location: 0,
+					format: VertexFormat::Half4,
+				},
+				VertexAttributeDescriptor {
+					offset: size_of::<[u16; 4]>() as BufferAddress,
+					shader_location: 1,
+					format: VertexFormat::Uchar4Norm,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[u16; 4]>() + size_of::<[u8; 4]>()) as BufferAddress,
+					shader_location: 2,
+					format: VertexFormat::Half2,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[u16; 4]>() + size_of::<[u8; 4]>() + size_of::<[u16; 2]>()) as BufferAddress,
+					shader_location: 3,
+					format: VertexFormat::Short2Norm,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[u16; 4]>() + size_of::<[u8; 4]>() + size_of::<[u16; 2]>() + size_of::<[i16; 2]>()) as BufferAddress,
+					shader_location: 4,
+					format: VertexFormat::Short4Norm,
+				},
+			],
+		}
+	}
+}
+
+impl AttributeTrait for CompactNormalVertex {
+	fn get_position(&self) -> [f32; 3] {
+		decode_position(self.position)
+	}
+
+	fn set_position(&mut self, position: [f32; 3]) {
+		self.position = encode_position(position);
+	}
+
+	fn get_normal(&self) -> Option<[f32; 3]> {
+		Some(from_octahedral_snorm16(self.normal))
+	}
+
+	fn set_normal(&mut self, normal: [f32; 3]) {
+		self.normal = to_octahedral_snorm16(normal);
+	}
+
+	fn get_uv(&self) -> Option<[f32; 2]> {
+		Some(decode_uv(self.uv))
+	}
+
+	fn set_uv(&mut self, uv: [f32; 2]) {
+		self.uv = encode_uv(uv);
+	}
+
+	fn get_color(&self) -> Option<[f32; 4]> {
+		Some(decode_color(self.color))
+	}
+
+	fn set_color(&mut self, color: [f32; 4]) {
+		self.color = encode_color(color);
+	}
+
+	fn get_tangent(&self) -> Option<[f32; 4]> {
+		let tangent = self.tangent;
+		Some([from_snorm16(tangent[0]), from_snorm16(tangent[1]), from_snorm16(tangent[2]), if tangent[3] < 0 { -1.0 } else { 1.0 }])
+	}
+
+	fn set_tangent(&mut self, tangent: [f32; 4]) {
+		self.tangent = encode_tangent(tangent);
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct SkinnedVertex {
+	position: [f32; 3],
+	normal: [f32; 3],
+	uv: [f32; 2],
+	tangent: [f32; 4],
+	joints: [u16; 4],
+	weights: [f32; 4],
+}
+
+impl SkinnedVertex {
+	pub fn new(position: [f32; 3], normal: [f32; 3], uv: [f32; 2], joints: [u16; 4], weights: [f32; 4]) -> SkinnedVertex {
+		SkinnedVertex {
+			position,
+			normal,
+			uv,
+			tangent: [1.0, 0.0, 0.0, 1.0],
+			joints,
+			weights,
+		}
+	}
+}
+
+unsafe impl Pod for SkinnedVertex {}
+
+unsafe impl Zeroable for SkinnedVertex {}
+
+impl VertexTrait for SkinnedVertex {
+	fn descriptor<'a>() -> VertexBufferDescriptor<'a> {
+		VertexBufferDescriptor {
+			stride: size_of::<SkinnedVertex>() as BufferAddress,
+			step_mode: InputStepMode::Vertex,
+			attributes: &[
+				VertexAttributeDescriptor {
+					offset: 0,
+					shader_location: 0,
+					format: VertexFormat::Float3,
+				},
+				VertexAttributeDescriptor {
+					offset: size_of::<[f32; 3]>() as BufferAddress,
+					shader_location: 3,
+					format: VertexFormat::Float3,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2) as BufferAddress,
+					shader_location: 2,
+					format: VertexFormat::Float2,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2 + size_of::<[f32; 2]>()) as BufferAddress,
+					shader_location: 4,
+					format: VertexFormat::Float4,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2 + size_of::<[f32; 2]>() + size_of::<[f32; 4]>()) as BufferAddress,
+					shader_location: 5,
+					format: VertexFormat::Ushort4,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2 + size_of::<[f32; 2]>() + size_of::<[f32; 4]>() + size_of::<[u16; 4]>()) as BufferAddress,
+					shader_location: 6,
+					format: VertexFormat::Float4,
+				},
+			],
+		}
+	}
+}
+
+impl AttributeTrait for SkinnedVertex {
+	fn get_position(&self) -> [f32; 3] {
+		self.position
+	}
+
+	fn set_position(&mut self, position: [f32; 3]) {
+		self.position = position;
+	}
+
+	fn get_normal(&self) -> Option<[f32; 3]> {
+		Some(self.normal)
+	}
+
+	fn set_normal(&mut self, normal: [f32; 3]) {
+		self.normal = normal;
+	}
+
+	fn get_uv(&self) -> Option<[f32; 2]> {
+		Some(self.uv)
+	}
+
+	fn set_uv(&mut self, uv: [f32; 2]) {
+		self.uv = uv;
+	}
+
+	fn get_tangent(&self) -> Option<[f32; 4]> {
+		Some(self.tangent)
+	}
+
+	fn set_tangent(&mut self, tangent: [f32; 4]) {
+		self.tangent = tangent;
+	}
+
+	fn get_joints(&self) -> Option<[u16; 4]> {
+		Some(self.joints)
+	}
+
+	fn set_joints(&mut self, joints: [u16; 4]) {
+		self.joints = joints;
+	}
+
+	fn get_weights(&self) -> Option<[f32; 4]> {
+		Some(self.weights)
+	}
+
+	fn set_weights(&mut self, weights: [f32; 4]) {
+		self.weights = weights;
+	}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PbrVertex {
+	position: [f32; 3],
+	normal: [f32; 3],
+	uv: [f32; 2],
+	tangent: [f32; 4],
+	color: [f32; 4],
+}
+
+impl PbrVertex {
+	pub fn new(position: [f32; 3], normal: [f32; 3], uv: [f32; 2], tangent: [f32; 4]) -> PbrVertex {
+		PbrVertex {
+			position,
+			normal,
+			uv,
+			tangent,
+			color: [1.0; 4],
+		}
+	}
+}
+
+unsafe impl Pod for PbrVertex {}
+
+unsafe impl Zeroable for PbrVertex {}
+
+impl VertexTrait for PbrVertex {
+	fn descriptor<'a>() -> VertexBufferDescriptor<'a> {
+		VertexBufferDescriptor {
+			stride: size_of::<PbrVertex>() as BufferAddress,
+			step_mode: InputStepMode::Vertex,
+			attributes: &[
+				VertexAttributeDescriptor {
+					offset: 0,
+					shader_location: 0,
+					format: VertexFormat::Float3,
+				},
+				VertexAttributeDescriptor {
+					offset: size_of::<[f32; 3]>() as BufferAddress,
+					shader_location: 3,
+					format: VertexFormat::Float3,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2) as BufferAddress,
+					shader_location: 2,
+					format: VertexFormat::Float2,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2 + size_of::<[f32; 2]>()) as BufferAddress,
+					shader_location: 4,
+					format: VertexFormat::Float4,
+				},
+				VertexAttributeDescriptor {
+					offset: (size_of::<[f32; 3]>() * 2 + size_of::<[f32; 2]>() + size_of::<[f32; 4]>()) as BufferAddress,
+					shader_location: 1,
+					format: VertexFormat::Float4,
+				},
+			],
+		}
+	}
+}
+
+impl AttributeTrait for PbrVertex {
+	fn get_position(&self) -> [f32; 3] {
+		self.position
+	}
+
+	fn set_position(&mut self, position: [f32; 3]) {
+		self.position = position;
+	}
+
+	fn get_normal(&self) -> Option<[f32; 3]> {
+		Some(self.normal)
+	}
+
+	fn set_normal(&mut self, normal: [f32; 3]) {
+		self.normal = normal;
+	}
+
+	fn get_uv(&self) -> Option<[f32; 2]> {
+		Some(self.uv)
+	}
+
+	fn set_uv(&mut self, uv: [f32; 2]) {
+		self.uv = uv;
+	}
+
+	fn get_color(&self) -> Option<[f32; 4]> {
+		Some(self.color)
+	}
+
+	fn set_color(&mut self, color: [f32; 4]) {
+		self.color = color;
+	}
+
+	fn get_tangent(&self) -> Option<[f32; 4]> {
+		Some(self.tangent)
+	}
+
+	fn set_tangent(&mut self, tangent: [f32; 4]) {
+		self.tangent = tangent;
+	}
+}
+
+impl<V: VertexTrait> VertexLayoutTrait for V {
+	fn descriptors<'a>() -> Vec<VertexBufferDescriptor<'a>> {
+		vec![V::descriptor()]
+	}
+}
+
+impl<A: VertexTrait, B: VertexTrait> VertexLayoutTrait for (A, B) {
+	fn descriptors<'a>() -> Vec<VertexBufferDescriptor<'a>> {
+		vec![A::descriptor(), B::descriptor()]
+	}
+}
+
+impl<A: VertexTrait, B: VertexTrait, C: VertexTrait> VertexLayoutTrait for (A, B, C) {
+	fn descriptors<'a>() -> Vec<VertexBufferDescriptor<'a>> {
+		vec![A::descriptor(), B::descriptor(), C::descriptor()]
+	}
+}
+
+impl<A: VertexTrait, B: VertexTrait, C: VertexTrait, D: VertexTrait> VertexLayoutTrait for (A, B, C, D) {
+	fn descriptors<'a>() -> Vec<VertexBufferDescriptor<'a>> {
+		vec![A::descriptor(), B::descriptor(), C::descriptor(), D::descriptor()]
+	}
+}
+
 impl IndexTrait for u16 {
 	fn index_format() -> IndexFormat {
 		IndexFormat::Uint16
 	}
+
+	fn to_u32(self) -> u32 {
+		self as u32
+	}
+
+	fn try_from_u32(value: u32) -> Option<u16> {
+		if value > u16::MAX as u32 { None } else { Some(value as u16) }
+	}
 }
 
 impl IndexTrait for u32 {
 	fn index_format() -> IndexFormat {
 		IndexFormat::Uint32
 	}
-}
\ No newline at end of file
+
+	fn to_u32(self) -> u32 {
+		self
+	}
+
+	fn try_from_u32(value: u32) -> Option<u32> {
+		Some(value)
+	}
+}
+
+fn encode_position(position: [f32; 3]) -> [u16; 4] {
+	[f32_to_half(position[0]), f32_to_half(position[1]), f32_to_half(position[2]), f32_to_half(1.0)]
+}
+
+fn decode_position(position: [u16; 4]) -> [f32; 3] {
+	[half_to_f32(position[0]), half_to_f32(position[1]), half_to_f32(position[2])]
+}
+
+fn encode_uv(uv: [f32; 2]) -> [u16; 2] {
+	[f32_to_half(uv[0]), f32_to_half(uv[1])]
+}
+
+fn decode_uv(uv: [u16; 2]) -> [f32; 2] {
+	[half_to_f32(uv[0]), half_to_f32(uv[1])]
+}
+
+fn encode_color(color: [f32; 4]) -> [u8; 4] {
+	[to_unorm8(color[0]), to_unorm8(color[1]), to_unorm8(color[2]), to_unorm8(color[3])]
+}
+
+fn decode_color(color: [u8; 4]) -> [f32; 4] {
+	[from_unorm8(color[0]), from_unorm8(color[1]), from_unorm8(color[2]), from_unorm8(color[3])]
+}
+
+fn encode_tangent(tangent: [f32; 4]) -> [i16; 4] {
+	[to_snorm16(tangent[0]), to_snorm16(tangent[1]), to_snorm16(tangent[2]), if tangent[3] < 0.0 { -32767 } else { 32767 }]
+}